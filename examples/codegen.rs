@@ -15,6 +15,7 @@ fn main() {
     // let wat_filename = project_path("examples/wat/valid/mem-table-start.wat");
     // let wat_filename = project_path("examples/wat/invalid/start-parameter.wat");
     // let wat_filename = project_path("examples/wat/valid/start.wat");
+    // let wat_filename = project_path("examples/wat/valid/control-flow.wat");
 
     let wasm_binary = match convert_wat_to_wasm(&wat_filename) {
         Err(error) => panic!("Conversion Error! = {:?}", error),
@@ -23,7 +24,12 @@ fn main() {
 
     let options = &CodegenOptions::default();
 
-    let result = ModuleGenerator::new(&wasm_binary, options).generate_module();
+    let mut generator = match ModuleGenerator::new(&wasm_binary, options) {
+        Err(error) => panic!("Target Error! = {:?}", error),
+        Ok(generator) => generator,
+    };
+
+    let result = generator.generate_module();
 
     // Error handing
     let result = match result {
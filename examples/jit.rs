@@ -4,9 +4,7 @@ use wasmo_utils::debug;
 
 use wasmo_llvm::types::{function_type, BasicType};
 use wasmo_llvm::values::IntValue;
-use wasmo_llvm::{
-    Builder, CompilerResult, Context, ExecutionEngine, Func, Module, OptimizationLevel,
-};
+use wasmo_llvm::{Builder, CompilerResult, Context, Func, Module, OptimizationLevel, ORCJIT};
 
 fn main() {
     println!("\n=== [ jit_example ] ===\n");
@@ -23,12 +21,16 @@ pub fn jit_example() -> CompilerResult<()> {
 
     let builder = context.create_builder();
 
-    let execution_engine = module.create_jit_execution_engine(OptimizationLevel::None)?;
-
-    let sum = unsafe { jit_compile_sum(&context, &module, &builder, &execution_engine)? };
+    build_sum(&context, &module, &builder)?;
 
     println!("module => {}", module);
 
+    let mut jit = ORCJIT::create_native(OptimizationLevel::None)?;
+
+    jit.add_module(module)?;
+
+    let sum: Func<SumFunc> = unsafe { jit.get_function("sum")? };
+
     let (x, y, z) = (1, 2, 3);
 
     println!("{} + {} + {} = {}", x, y, z, unsafe { sum.call(x, y, z) });
@@ -38,12 +40,7 @@ pub fn jit_example() -> CompilerResult<()> {
 
 type SumFunc = unsafe extern "C" fn(u64, u64, u64) -> u64;
 
-unsafe fn jit_compile_sum(
-    context: &Context,
-    module: &Module,
-    builder: &Builder,
-    execution_engine: &ExecutionEngine,
-) -> CompilerResult<Func<SumFunc>> {
+fn build_sum(context: &Context, module: &Module, builder: &Builder) -> CompilerResult<()> {
     let i64_type: BasicType = context.i64_type().into();
 
     let func_type = function_type(&[i64_type, i64_type, i64_type], i64_type, false);
@@ -64,5 +61,5 @@ unsafe fn jit_compile_sum(
 
     builder.build_return(Some(sum.into()));
 
-    Ok(execution_engine.get_function("sum")?)
+    Ok(())
 }
@@ -1,15 +1,13 @@
-//! USAGE: cargo run --example codegen --features "verbose"
+//! USAGE: cargo run --example runtime_aot --features "verbose"
 
-use wasmo_codegen::generator::ModuleGenerator;
-use wasmo_codegen::options::CodegenOptions;
-use wasmo_runtime::module::{Module, ModuleAOT};
-use wasmo_runtime::options::Options;
+use wasmo_codegen::compile::Compile;
+use wasmo_runtime::module::ModuleAOT;
 use wasmo_utils::file::convert_wat_to_wasm;
 use wasmo_utils::path::project_path;
 use wasmo_utils::verbose;
 
 fn main() {
-    verbose!("\n=== [ codegen_example ] ===\n");
+    verbose!("\n=== [ runtime_aot_example ] ===\n");
 
     let wat_file_path = project_path("examples/wat/valid/func-body.wat");
 
@@ -18,25 +16,22 @@ fn main() {
         Ok(binary) => binary,
     };
 
-    let options = &CodegenOptions::default();
-
-    let result = ModuleGenerator::new(&wasm_binary, options).generate_module();
-
-    // Error handing
-    let result = match result {
-        Err(error) => panic!("Parsing Error! = {:?}", error),
-        Ok(result) => {
-            verbose!("LLVM Module generated! = {:?}", result.0);
-            verbose!("Runtime Module Data generated! = {:?}", result.1);
-            result
-        }
+    let module = match ModuleAOT::new(&wasm_binary) {
+        Err(error) => panic!("Compilation Error! = {:?}", error),
+        Ok(module) => module,
     };
 
-    let options = &Options::default();
+    let object_file_path = std::env::temp_dir().join("func-body.o");
 
-    let module: ModuleAOT = Module::create_aot_with_llvm_module(result.0, options);
+    if let Err(error) = module.write_object(&object_file_path) {
+        panic!("Write Error! = {:?}", error);
+    }
 
-    verbose!("Runtime Module generated! = {:?}", module);
+    verbose!(
+        "Object file written! = {:?} ({} bytes)",
+        object_file_path,
+        module.object().len()
+    );
 
-    verbose!("\n=== [ codegen_example ] ===\n");
+    verbose!("\n=== [ runtime_aot_example ] ===\n");
 }
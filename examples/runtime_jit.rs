@@ -0,0 +1,45 @@
+//! USAGE: cargo run --example runtime_jit --features "verbose"
+
+use wasmo_codegen::compile::Compile;
+use wasmo_runtime::imports::Linker;
+use wasmo_runtime::module::Module;
+use wasmo_runtime::types::Value;
+use wasmo_utils::file::convert_wat_to_wasm;
+use wasmo_utils::path::project_path;
+use wasmo_utils::verbose;
+
+fn main() {
+    verbose!("\n=== [ runtime_jit_example ] ===\n");
+
+    let wat_file_path = project_path("examples/wat/valid/control-flow.wat");
+
+    let wasm_binary = match convert_wat_to_wasm(&wat_file_path) {
+        Err(error) => panic!("Conversion Error! = {:?}", error),
+        Ok(binary) => binary,
+    };
+
+    let module = match Module::new(&wasm_binary) {
+        Err(error) => panic!("Compilation Error! = {:?}", error),
+        Ok(module) => module,
+    };
+
+    let instance = match module.instantiate(&Linker::new()) {
+        Err(error) => panic!("Instantiation Error! = {:?}", error),
+        Ok(instance) => instance,
+    };
+
+    let fac = instance.get_func("fac").expect("Missing export");
+
+    let result = fac.call(&[Value::I64(5)]);
+
+    verbose!("fac(5) = {:?}", result);
+
+    let max = match instance.get_typed_func::<(i32, i32), i32>("max") {
+        Err(error) => panic!("Signature Error! = {:?}", error),
+        Ok(max) => max,
+    };
+
+    verbose!("max(3, 7) = {:?}", max.call(3, 7));
+
+    verbose!("\n=== [ runtime_jit_example ] ===\n");
+}
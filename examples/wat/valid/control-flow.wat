@@ -0,0 +1,35 @@
+(module
+    (func (export "fac") (param i64) (result i64)
+        (local i64)
+        (local.set 1 (i64.const 1))
+        (block $done
+            (loop $loop
+                (br_if $done (i64.eqz (local.get 0)))
+                (local.set 1 (i64.mul (local.get 1) (local.get 0)))
+                (local.set 0 (i64.sub (local.get 0) (i64.const 1)))
+                (br $loop)
+            )
+        )
+        (local.get 1)
+    )
+
+    (func (export "pick") (param i32) (result i32)
+        (block $c
+            (block $b
+                (block $a
+                    (br_table $a $b $c (local.get 0))
+                )
+                (return (i32.const 10))
+            )
+            (return (i32.const 20))
+        )
+        (i32.const 30)
+    )
+
+    (func (export "max") (param i32 i32) (result i32)
+        (if (result i32) (i32.gt_s (local.get 0) (local.get 1))
+            (then (local.get 0))
+            (else (local.get 1))
+        )
+    )
+)
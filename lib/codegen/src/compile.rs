@@ -0,0 +1,85 @@
+//! This module creates runtime modules straight from wasm binaries.
+use crate::error::CompileError;
+use crate::generator::ModuleGenerator;
+use crate::options::CodegenOptions;
+use wasmo_llvm::CodeGenFileType;
+use wasmo_runtime::module::{Module, ModuleAOT};
+
+/// Generates and compiles a module from a wasm binary. `Module` is jit-compiled and `ModuleAOT`
+/// is compiled to a relocatable object.
+///
+/// The runtime doesn't depend on codegen, so this is an extension trait of the runtime module
+/// rather than an inherent constructor. Bring it into scope to call `Module::new(&wasm)`.
+pub trait Compile: Sized {
+    fn new(wasm_binary: &[u8]) -> Result<Self, CompileError> {
+        Self::with_options(wasm_binary, &CodegenOptions::default())
+    }
+
+    fn with_options(wasm_binary: &[u8], options: &CodegenOptions) -> Result<Self, CompileError>;
+}
+
+impl Compile for Module {
+    fn with_options(wasm_binary: &[u8], options: &CodegenOptions) -> Result<Self, CompileError> {
+        let (module, data) = ModuleGenerator::new(wasm_binary, options)?.generate_module()?;
+
+        Ok(Module::compile(module, data)?)
+    }
+}
+
+impl Compile for ModuleAOT {
+    fn with_options(wasm_binary: &[u8], options: &CodegenOptions) -> Result<Self, CompileError> {
+        let mut generator = ModuleGenerator::new(wasm_binary, options)?;
+        let (module, data) = generator.generate_module()?;
+        let object = generator
+            .target_machine()
+            .emit_to_memory_buffer(&module, CodeGenFileType::ObjectFile)?;
+
+        Ok(ModuleAOT::from_object(object.as_slice().to_vec(), data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmo_utils::file::{convert_wat_source_to_wasm, convert_wat_to_wasm};
+
+    fn compile_error(wat_file: &str) -> CompileError {
+        let path = format!("{}/../../examples/wat/{}", env!("CARGO_MANIFEST_DIR"), wat_file);
+        let wasm = convert_wat_to_wasm(path.as_str()).unwrap();
+
+        match Module::new(&wasm) {
+            Err(error) => error,
+            Ok(_) => panic!("Expected {} to fail to compile", wat_file),
+        }
+    }
+
+    fn assert_parser_error(error: CompileError, message: &str) {
+        match error {
+            CompileError::Parser(error) => assert_eq!(error.message(), message),
+            error => panic!("Expected a parser error, got {:?}", error),
+        }
+    }
+
+    #[test]
+    fn ill_typed_modules_fail_to_compile() {
+        let source = r#"(module (func (export "f") (result i32) (i64.const 0)))"#;
+        let wasm = convert_wat_source_to_wasm(source).unwrap();
+
+        match Module::new(&wasm) {
+            Err(CompileError::Parser(_)) => (),
+            other => panic!("Expected a parser error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn start_functions_must_not_take_parameters() {
+        let error = compile_error("invalid/start-parameter.wat");
+        assert_parser_error(error, "Start function must not take parameters");
+    }
+
+    #[test]
+    fn start_functions_must_not_return_values() {
+        let error = compile_error("invalid/start-return-type.wat");
+        assert_parser_error(error, "Start function must not return values");
+    }
+}
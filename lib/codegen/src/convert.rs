@@ -1,11 +1,17 @@
 //! Conversions from wasmparser types.
 use wasmo_llvm::types::{function_type, BasicType, FunctionType, PointerType};
 use wasmo_llvm::{AddressSpace, Context};
-use wasmo_runtime::data::Data;
+use wasmo_runtime::data::{
+    Data, FuncData, GlobalData, InitExpression, MemoryData, ResizableLimits, TableData,
+};
 use wasmo_runtime::types::{ExportKind, FuncType, ValueType};
-use wasmparser::{ExternalKind, FuncType as ParserFuncType, Type};
+use wasmparser::{
+    ExternalKind, FuncType as ParserFuncType, ImportSectionEntryType, Operator,
+    ResizableLimits as ParserResizableLimits, Type, TypeOrFuncType,
+};
 
 use std::iter::once;
+use std::ptr::null;
 
 pub struct LLVM();
 
@@ -31,6 +37,18 @@ impl LLVM {
         Ok(function_type(&params, returns, false))
     }
 
+    /// Gets the result types of a `block`, `loop` or `if`.
+    pub fn block_type(
+        context: &Context,
+        ty: &TypeOrFuncType,
+    ) -> Result<Vec<BasicType>, &'static str> {
+        Ok(match ty {
+            TypeOrFuncType::Type(Type::EmptyBlockType) => Vec::new(),
+            TypeOrFuncType::Type(ty) => vec![LLVM::basic_type(context, ty)?],
+            TypeOrFuncType::FuncType(_) => return Err("Multi-value blocks not supported yet!"),
+        })
+    }
+
     pub fn basic_type(context: &Context, ty: &Type) -> Result<BasicType, &'static str> {
         Ok(match ty {
             Type::I32 => context.i32_type().into(),
@@ -69,6 +87,41 @@ impl Runtime {
         })
     }
 
+    /// Converts the single operator of a constant expression.
+    pub fn init_expression(operator: &Operator) -> Result<InitExpression, &'static str> {
+        Ok(match operator {
+            Operator::I32Const { value } => InitExpression::Const(u64::from(*value as u32)),
+            Operator::I64Const { value } => InitExpression::Const(*value as u64),
+            Operator::F32Const { value } => InitExpression::Const(u64::from(value.bits())),
+            Operator::F64Const { value } => InitExpression::Const(value.bits()),
+            Operator::GetGlobal { global_index } => InitExpression::GetGlobal(*global_index),
+            _ => return Err("Expected a constant or global.get init expression"),
+        })
+    }
+
+    pub fn resizable_limits(limits: &ParserResizableLimits) -> ResizableLimits {
+        ResizableLimits::new(limits.initial, limits.maximum)
+    }
+
+    /// Converts the expected type of an import.
+    pub fn import(ty: &ImportSectionEntryType) -> Result<Data, &'static str> {
+        Ok(match ty {
+            ImportSectionEntryType::Function(type_index) => {
+                Data::Func(FuncData::new(null(), *type_index))
+            }
+            ImportSectionEntryType::Table(ty) => {
+                Data::Table(TableData::new(Runtime::resizable_limits(&ty.limits)))
+            }
+            ImportSectionEntryType::Memory(ty) => {
+                Data::Memory(MemoryData::new(Runtime::resizable_limits(&ty.limits)))
+            }
+            ImportSectionEntryType::Global(ty) => Data::Global(GlobalData::import(
+                Runtime::value_type(&ty.content_type)?,
+                ty.mutable,
+            )),
+        })
+    }
+
     pub fn export(kind: &ExternalKind, index: u32) -> ExportKind {
         match kind {
             ExternalKind::Memory => ExportKind::Memory(index),
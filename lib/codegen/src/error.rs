@@ -1,3 +1,6 @@
+use wasmo_llvm::CompilerError;
+use wasmparser::BinaryReaderError;
+
 #[derive(Debug)]
 pub struct ParserError {
     message: &'static str,
@@ -15,6 +18,10 @@ impl ParserError {
     pub fn new(message: &'static str, offset: Offset ) -> Self {
         Self { message, offset }
     }
+
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
 }
 
 impl From<&'static str> for ParserError {
@@ -26,4 +33,29 @@ impl From<&'static str> for ParserError {
     }
 }
 
+impl From<BinaryReaderError> for ParserError {
+    fn from(error: BinaryReaderError) -> Self {
+        Self::new(error.message, Offset::Number(error.offset))
+    }
+}
+
 pub type ParserResult<T> = Result<T, ParserError>;
+
+/// Errors from compiling a wasm binary to a runtime module.
+#[derive(Debug)]
+pub enum CompileError {
+    Parser(ParserError),
+    LLVM(CompilerError),
+}
+
+impl From<ParserError> for CompileError {
+    fn from(error: ParserError) -> Self {
+        CompileError::Parser(error)
+    }
+}
+
+impl From<CompilerError> for CompileError {
+    fn from(error: CompilerError) -> Self {
+        CompileError::LLVM(error)
+    }
+}
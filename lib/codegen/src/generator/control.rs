@@ -0,0 +1,64 @@
+use wasmo_llvm::types::BasicType;
+use wasmo_llvm::values::BasicValue;
+use wasmo_llvm::BasicBlock;
+
+/// What opened a control frame.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) enum ControlKind {
+    Function,
+    Block,
+    Loop,
+    If { else_block: BasicBlock },
+    Else,
+}
+
+/// An edge into the block that follows a control frame.
+#[derive(Debug)]
+pub(crate) struct Incoming {
+    pub(crate) block: BasicBlock,
+    pub(crate) results: Vec<BasicValue>,
+}
+
+/// Represents a `block`, `loop`, `if`/`else` or the function body itself while its operators
+/// are being generated.
+#[derive(Debug)]
+pub(crate) struct ControlFrame {
+    pub(crate) kind: ControlKind,
+    /// The block a `br` to this frame jumps to. This is the header block for loops.
+    pub(crate) branch_block: BasicBlock,
+    /// The block where execution continues after the frame's `end`.
+    pub(crate) next_block: BasicBlock,
+    pub(crate) result_types: Vec<BasicType>,
+    /// Operand stack height when the frame was entered.
+    pub(crate) stack_height: usize,
+    /// Edges into `next_block` along with the values they carry.
+    pub(crate) incoming: Vec<Incoming>,
+}
+
+impl ControlFrame {
+    pub(crate) fn new(
+        kind: ControlKind,
+        branch_block: BasicBlock,
+        next_block: BasicBlock,
+        result_types: Vec<BasicType>,
+        stack_height: usize,
+    ) -> Self {
+        Self {
+            kind,
+            branch_block,
+            next_block,
+            result_types,
+            stack_height,
+            incoming: Vec::new(),
+        }
+    }
+
+    /// Number of operand stack values a `br` to this frame carries.
+    /// Branching to a loop goes back to its header, which takes no values.
+    pub(crate) fn branch_arity(&self) -> usize {
+        match self.kind {
+            ControlKind::Loop => 0,
+            _ => self.result_types.len(),
+        }
+    }
+}
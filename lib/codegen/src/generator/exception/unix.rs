@@ -0,0 +1,54 @@
+use crate::generator::memory::MemoryGenerator;
+use crate::generator::module::Reusables;
+use wasmo_llvm::types::{function_type, BasicType, FunctionType};
+use wasmo_llvm::values::{IntValue, PointerValue};
+use wasmo_llvm::{Builder, Context};
+use wasmo_runtime::intrinsics::TRAP_INDEX;
+
+pub struct ExceptionGenerator();
+
+impl ExceptionGenerator {
+    /// Calls the runtime's trap function with a `TrapCode` and the offset of the trapping operator.
+    /// The call unwinds to the embedder and never returns.
+    pub fn generate_trap_code(
+        code: IntValue,
+        offset: IntValue,
+        instance_context: PointerValue,
+        instance_context_type: BasicType,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) {
+        let function_pointer = MemoryGenerator::generate_intrinsic_pointer(
+            TRAP_INDEX,
+            ExceptionGenerator::trap_function_type(instance_context_type, context, reusables),
+            instance_context,
+            builder,
+            reusables,
+        );
+
+        builder.build_indirect_call(
+            function_pointer,
+            &[instance_context.into(), code.into(), offset.into()],
+            "",
+        );
+        builder.build_unreachable();
+    }
+
+    /// `fn (*mut InstanceContext, code: u32, offset: u32) -> !`
+    fn trap_function_type(
+        instance_context_type: BasicType,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> FunctionType {
+        function_type(
+            &[
+                instance_context_type,
+                reusables.i32_type.into(),
+                reusables.i32_type.into(),
+            ],
+            context.void_type().into(),
+            false,
+        )
+    }
+}
@@ -0,0 +1,4 @@
+pub struct ExceptionGenerator();
+
+impl ExceptionGenerator {
+}
@@ -1,39 +1,102 @@
-use super::module::Reusables;
+#![allow(clippy::too_many_arguments)]
+
+use super::control::{ControlFrame, ControlKind, Incoming};
+use super::module::{Declarations, Reusables};
+use super::{ExceptionGenerator, GlobalGenerator, MemoryGenerator, TableGenerator};
 use crate::convert::LLVM;
-use wasmo_llvm::types::{function_type, BasicType, FunctionType};
-use wasmo_llvm::values::{BasicValue, FloatValue, FunctionValue, IntValue};
-use wasmo_llvm::{Builder, Context, Module};
+use crate::options::{CodegenOptions, MemoryStrategy};
+use wasmo_llvm::types::{function_type, BasicType, FloatType, FunctionType, IntType, PointerType};
+use wasmo_llvm::values::{BasicValue, FloatValue, FunctionValue, IntValue, PhiValue, PointerValue};
+use wasmo_llvm::{
+    AddressSpace, BasicBlock, Builder, Context, FloatPredicate, IntPredicate, Linkage, Module,
+};
+use wasmo_runtime::context::{
+    CODE_END_NAME, FUNCTIONS_FIELD, FUNCTION_CONTEXTS_FIELD, INITIALIZE_FUNCTION_NAME,
+    TRAMPOLINE_NAME_PREFIX,
+};
+use wasmo_runtime::data::{InitExpression, ModuleData, ResizableLimits};
+use wasmo_runtime::executable::{
+    MAIN_NAME, MODULE_DATA_ADD_FUNCTION_NAME, MODULE_DATA_ADD_GLOBAL_NAME,
+    MODULE_DATA_ADD_MEMORY_NAME, MODULE_DATA_ADD_TABLE_NAME, MODULE_DATA_NEW_NAME,
+    MODULE_DATA_SET_CODE_END_NAME, NO_MAXIMUM, NO_START_FUNCTION,
+};
+use wasmo_runtime::library::{INSTANCE_CALL_NAME, INSTANTIATE_NAME};
+use wasmo_runtime::trap::{TrapCode, UNKNOWN_OFFSET};
+use wasmo_runtime::types::{ExportKind, ValueType};
 use wasmo_utils::{debug, verbose};
-use wasmparser::{FuncType, Operator, Parser, ParserState, WasmDecoder};
+use wasmparser::{MemoryImmediate, Operator, Parser, ParserState, WasmDecoder};
 
-///
+/// Each local lives in a stack slot allocated in the entry block. The mem2reg pass promotes
+/// these slots to SSA registers, with phi nodes at join points, once the function is generated.
 #[derive(Debug)]
 struct Local {
     ty: BasicType,
-    current_ssa_reference: Option<BasicValue>,
+    pointer: PointerValue,
 }
 
 impl Local {
-    fn new(ty: &BasicType) -> Self {
-        Self {
-            ty: *ty,
-            current_ssa_reference: None,
-        }
+    fn new(ty: &BasicType, pointer: PointerValue) -> Self {
+        Self { ty: *ty, pointer }
     }
 }
 
+/// The block every runtime check of a function branches to when it fails. It calls the runtime's
+/// trap function with the code and offset of the failed check, which the phis select by
+/// predecessor.
+#[derive(Debug)]
+struct TrapBlock {
+    block: BasicBlock,
+    code: PhiValue,
+    offset: PhiValue,
+}
+
+/// Integer shift operators.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum ShiftKind {
+    Left,
+    SignedRight,
+    UnsignedRight,
+}
+
+/// Integer division operators. They all trap when the divisor is zero.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum DivisionKind {
+    SignedDiv,
+    UnsignedDiv,
+    SignedRem,
+    UnsignedRem,
+}
+
 ///
 pub struct FunctionGenerator {
     stack: Vec<BasicValue>,
     locals: Vec<Local>,
+    control_frames: Vec<ControlFrame>,
+    /// False when the operators being read come after a `br`, `br_table`, `return` or
+    /// `unreachable` in the same frame. Such operators are validated but not generated.
+    reachable: bool,
+    /// Number of frames entered while generating unreachable code.
+    unreachable_depth: u32,
+    /// Block every runtime check of the function branches to when it fails.
+    /// It is only created when a check is generated.
+    trap_block: Option<TrapBlock>,
+    /// Offset in the wasm binary of the operator being generated. Traps report it.
+    offset: u32,
+    options: CodegenOptions,
 }
 
 impl FunctionGenerator {
     ///
-    pub fn new() -> Self {
+    pub fn new(options: &CodegenOptions) -> Self {
         Self {
             stack: Vec::new(),
             locals: Vec::new(),
+            control_frames: Vec::new(),
+            reachable: true,
+            unreachable_depth: 0,
+            trap_block: None,
+            offset: UNKNOWN_OFFSET,
+            options: options.clone(),
         }
     }
 
@@ -42,155 +105,711 @@ impl FunctionGenerator {
         &mut self,
         module: &mut Module,
         parser: &mut Parser<'b>,
-        function_types: &[FunctionType],
+        declarations: &Declarations,
         builder: &Builder,
         context: &Context,
         reusables: &Reusables,
         index: u32,
-    ) -> Result<(), &'static str> {
-        //
-        let function_type = function_types[index as usize];
-        let function = module.add_function("", function_type, None);
-        let basic_block = function.append_basic_block("entry", &context);
+    ) -> Result<FunctionValue, &'static str> {
+        // Functions are declared when the function section is read.
+        let function = *declarations
+            .functions
+            .get(index as usize)
+            .ok_or("Function index out of bounds")?;
+        let function_type = function.get_type().unwrap();
+        let basic_block = function.append_basic_block("entry", context);
+        let return_block = function.append_basic_block("return", context);
         builder.position_at_end(&basic_block);
 
+        // The function body is the outermost frame. Branching to it is a return.
+        let result_types = match function_type.get_return_type() {
+            BasicType::VoidType(_) => Vec::new(),
+            ty => vec![ty],
+        };
+
+        self.control_frames.push(ControlFrame::new(
+            ControlKind::Function,
+            return_block,
+            return_block,
+            result_types,
+            0,
+        ));
+
         loop {
-            //
+            // Operators start where the parser stops before reading them.
+            let position = parser.current_position();
             let state = parser.read();
 
             match state {
                 ParserState::EndFunctionBody => {
-                    // Create a function return type from what's left on stack.
-                    match self.stack.as_slice() {
-                        &[value] => builder.build_return(Some(value)),
-                        &[] => builder.build_return(None),
-                        _ => return Err("Multiple return values not supported yet"),
-                    };
+                    // The function frame's `end` already generated the return.
+                    if !self.control_frames.is_empty() {
+                        return Err("Function body ended with unterminated control frames");
+                    }
 
                     break;
                 }
                 ParserState::FunctionBodyLocals { locals } => {
-                    // Get argument locals
-                    for ty in function
+                    // Get argument locals. The first parameter is the InstanceContext pointer,
+                    // which isn't a wasm local.
+                    for (param_index, ty) in function
                         .get_type()
                         .as_ref()
                         .unwrap()
                         .get_param_types()
                         .iter()
+                        .enumerate()
+                        .skip(1)
                     {
-                        self.locals.push(Local::new(ty))
+                        let value = function.get_nth_param(param_index as _).unwrap();
+                        let pointer = builder.build_alloca(*ty, "param");
+                        builder.build_store(value, pointer);
+                        self.locals.push(Local::new(ty, pointer))
                     }
 
-                    // Get body locals.
+                    // Get body locals. They are zero-initialized.
                     for data in locals.iter() {
                         for _ in 0..(data.0) {
-                            self.locals
-                                .push(Local::new(&LLVM::basic_type(context, &data.1)?))
+                            let ty = LLVM::basic_type(context, &data.1)?;
+                            let pointer = builder.build_alloca(ty, "local");
+                            builder.build_store(ty.zero(false)?, pointer);
+                            self.locals.push(Local::new(&ty, pointer))
                         }
                     }
                 }
                 ParserState::CodeOperator(operator) => {
-                    self.generate_operator_code(operator, &function, builder, context, reusables)?;
+                    self.offset = position as u32;
+                    self.generate_operator_code(
+                        operator,
+                        module,
+                        declarations,
+                        &function,
+                        builder,
+                        context,
+                        reusables,
+                    )?;
                 }
                 _ => break,
             };
         }
 
-        Ok(())
+        Ok(function)
     }
 
     ///
     pub fn generate_operator_code(
         &mut self,
         operator: &Operator,
+        module: &Module,
+        declarations: &Declarations,
         function: &FunctionValue,
         builder: &Builder,
         context: &Context,
         reusables: &Reusables,
     ) -> Result<(), &'static str> {
+        // Skip unreachable operators but keep track of the frames they open and close.
+        if !self.reachable {
+            match operator {
+                Operator::Block { .. } | Operator::Loop { .. } | Operator::If { .. } => {
+                    self.unreachable_depth += 1;
+                    return Ok(());
+                }
+                Operator::End if self.unreachable_depth > 0 => {
+                    self.unreachable_depth -= 1;
+                    return Ok(());
+                }
+                Operator::Else | Operator::End if self.unreachable_depth == 0 => (),
+                _ => return Ok(()),
+            }
+        }
+
         match operator {
-            Operator::Unreachable => {}
+            Operator::Unreachable => {
+                let trap_block = self.get_trap_block(
+                    TrapCode::Unreachable,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+
+                builder.build_br(&trap_block);
+                self.enter_unreachable_code()?;
+            }
             Operator::Nop => {}
-            Operator::Block { ty } => {}
-            Operator::Loop { ty } => {}
-            Operator::If { ty } => {}
-            Operator::Else => {}
-            Operator::End => {}
-            Operator::Br { relative_depth } => {}
-            Operator::BrIf { relative_depth } => {}
-            Operator::BrTable { table } => {}
-            Operator::Return => {}
-            Operator::Call { function_index } => {}
-            Operator::CallIndirect { index, table_index } => {}
+            Operator::Block { ty } => {
+                let next_block = function.append_basic_block("block.next", context);
+
+                self.control_frames.push(ControlFrame::new(
+                    ControlKind::Block,
+                    next_block,
+                    next_block,
+                    LLVM::block_type(context, ty)?,
+                    self.stack.len(),
+                ));
+            }
+            Operator::Loop { ty } => {
+                let header_block = function.append_basic_block("loop", context);
+                let next_block = function.append_basic_block("loop.next", context);
+
+                builder.build_br(&header_block);
+                builder.position_at_end(&header_block);
+
+                self.control_frames.push(ControlFrame::new(
+                    ControlKind::Loop,
+                    header_block,
+                    next_block,
+                    LLVM::block_type(context, ty)?,
+                    self.stack.len(),
+                ));
+            }
+            Operator::If { ty } => {
+                // Guarantee: parser already type checked stack values.
+                let condition = self.pop_condition(builder, reusables);
+                let then_block = function.append_basic_block("if.then", context);
+                let else_block = function.append_basic_block("if.else", context);
+                let next_block = function.append_basic_block("if.next", context);
+
+                builder.build_cond_br(condition, &then_block, &else_block);
+                builder.position_at_end(&then_block);
+
+                self.control_frames.push(ControlFrame::new(
+                    ControlKind::If { else_block },
+                    next_block,
+                    next_block,
+                    LLVM::block_type(context, ty)?,
+                    self.stack.len(),
+                ));
+            }
+            Operator::Else => {
+                // Fall through from the `then` arm to the frame's next block.
+                if self.reachable {
+                    self.add_fallthrough_edge(builder)?;
+                }
+
+                let frame = self
+                    .control_frames
+                    .last_mut()
+                    .ok_or("Else without a frame")?;
+                let else_block = match frame.kind {
+                    ControlKind::If { else_block } => else_block,
+                    _ => return Err("Else without a matching if"),
+                };
+
+                // The `else` arm starts with the stack the `if` had.
+                frame.kind = ControlKind::Else;
+                self.stack.truncate(frame.stack_height);
+
+                if let Some(current_block) = builder.get_insert_block() {
+                    else_block.move_after(&current_block);
+                }
+                builder.position_at_end(&else_block);
+                self.reachable = true;
+            }
+            Operator::End => {
+                self.generate_end_code(builder)?;
+            }
+            Operator::Br { relative_depth } => {
+                let current_block = builder.get_insert_block().unwrap();
+                let target_block = self.add_branch_edge(*relative_depth, current_block)?;
+
+                builder.build_br(&target_block);
+                self.enter_unreachable_code()?;
+            }
+            Operator::BrIf { relative_depth } => {
+                // Guarantee: parser already type checked stack values.
+                let condition = self.pop_condition(builder, reusables);
+                let current_block = builder.get_insert_block().unwrap();
+                let target_block = self.add_branch_edge(*relative_depth, current_block)?;
+                let else_block = function.append_basic_block("br_if.else", context);
+
+                builder.build_cond_br(condition, &target_block, &else_block);
+                builder.position_at_end(&else_block);
+            }
+            Operator::BrTable { table } => {
+                // Guarantee: parser already type checked stack values.
+                let index: IntValue = self.stack.pop().unwrap().into();
+                let current_block = builder.get_insert_block().unwrap();
+                let (depths, default_depth) = table
+                    .read_table()
+                    .map_err(|_| "Unable to read br_table targets")?;
+
+                // Every case is a separate edge, so each one is added to the target's incoming.
+                let mut cases = Vec::new();
+                for (case_index, depth) in depths.iter().enumerate() {
+                    let target_block = self.add_branch_edge(*depth, current_block)?;
+                    let case_value = reusables.i32_type.const_int(case_index as _, false);
+                    cases.push((case_value, target_block));
+                }
+
+                let default_block = self.add_branch_edge(default_depth, current_block)?;
+
+                builder.build_switch(index, &default_block, &cases);
+                self.enter_unreachable_code()?;
+            }
+            Operator::Return => {
+                let current_block = builder.get_insert_block().unwrap();
+                let depth = self.control_frames.len() as u32 - 1;
+                let target_block = self.add_branch_edge(depth, current_block)?;
+
+                builder.build_br(&target_block);
+                self.enter_unreachable_code()?;
+            }
+            Operator::Call { function_index } => {
+                self.generate_call_code(
+                    *function_index,
+                    declarations,
+                    function,
+                    builder,
+                    reusables,
+                )?;
+            }
+            Operator::CallIndirect { index, table_index } => {
+                let callee_type = *declarations
+                    .function_types
+                    .get(*index as usize)
+                    .ok_or("Type index out of bounds")?;
+                let signature_id = declarations.signature_ids[*index as usize];
+
+                // Guarantee: parser already type checked stack values.
+                let element_index: IntValue = self.stack.pop().unwrap().into();
+                let mut args = self.pop_call_arguments(&callee_type, function);
+                let (function_pointer, callee_context) = self.generate_table_function_pointer(
+                    *table_index,
+                    element_index,
+                    signature_id,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+                let function_pointer = builder.build_pointer_cast(
+                    function_pointer,
+                    callee_type.ptr_type(&AddressSpace::Generic),
+                    "",
+                );
+                // The element runs with the context of the instance or host it came from.
+                args[0] = Self::generate_callee_context_code(callee_context, &callee_type, builder);
+
+                if let Some(value) =
+                    builder.build_indirect_call(function_pointer, &args, "call_indirect")
+                {
+                    self.stack.push(value);
+                }
+            }
             Operator::Drop => {
                 self.stack.pop();
             }
-            Operator::Select => {}
+            Operator::Select => {
+                // Guarantee: parser already type checked stack values.
+                let condition = self.pop_condition(builder, reusables);
+                let else_value = self.stack.pop().unwrap();
+                let then_value = self.stack.pop().unwrap();
+                let value = builder.build_select(condition, then_value, else_value, "select");
+                self.stack.push(value);
+            }
             Operator::GetLocal { local_index } => {
-                // Skip the first instance_context_type param
-                let local_index = *local_index + 1;
-                let local = &mut self.locals[local_index as usize];
-
-                // If this is our first local.get for a specific index.
-                if local.current_ssa_reference.is_none() {
-                    local.current_ssa_reference = Some(
-                        // If local is a param, get param, otherwise initialize to zero.
-                        if local_index < function.count_params() {
-                            function.get_nth_param(local_index).unwrap()
-                        } else {
-                            local.ty.zero(false)?
-                        },
-                    );
-                }
+                let local = &self.locals[*local_index as usize];
 
                 // Push value to stack.
-                // Guarantee: The checks above ensure local holds a basic value.
-                self.stack
-                    .push(*local.current_ssa_reference.as_ref().unwrap());
+                let value = builder.build_load(local.pointer, "local.get");
+                self.stack.push(value);
             }
             Operator::SetLocal { local_index } => {
-                // Skip the first instance_context_type param
-                let local_index = *local_index + 1;
                 // Guarantee: parser already done stack validation
                 let stack_value = self.stack.pop().unwrap();
-                let local = &mut self.locals[local_index as usize];
-                local.current_ssa_reference = Some(stack_value);
+                let local = &self.locals[*local_index as usize];
+                builder.build_store(stack_value, local.pointer);
             }
             Operator::TeeLocal { local_index } => {
-                // Skip the first instance_context_type param
-                let local_index = *local_index + 1;
                 // Guarantee: parser already done stack validation
                 let stack_value = self.stack.last().unwrap();
-                let local = &mut self.locals[local_index as usize];
-                local.current_ssa_reference = Some(stack_value.clone());
-            }
-            Operator::GetGlobal { global_index } => {}
-            Operator::SetGlobal { global_index } => {}
-            Operator::I32Load { memarg } => {}
-            Operator::I64Load { memarg } => {}
-            Operator::F32Load { memarg } => {}
-            Operator::F64Load { memarg } => {}
-            Operator::I32Load8S { memarg } => {}
-            Operator::I32Load8U { memarg } => {}
-            Operator::I32Load16S { memarg } => {}
-            Operator::I32Load16U { memarg } => {}
-            Operator::I64Load8S { memarg } => {}
-            Operator::I64Load8U { memarg } => {}
-            Operator::I64Load16S { memarg } => {}
-            Operator::I64Load16U { memarg } => {}
-            Operator::I64Load32S { memarg } => {}
-            Operator::I64Load32U { memarg } => {}
-            Operator::I32Store { memarg } => {}
-            Operator::I64Store { memarg } => {}
-            Operator::F32Store { memarg } => {}
-            Operator::F64Store { memarg } => {}
-            Operator::I32Store8 { memarg } => {}
-            Operator::I32Store16 { memarg } => {}
-            Operator::I64Store8 { memarg } => {}
-            Operator::I64Store16 { memarg } => {}
-            Operator::I64Store32 { memarg } => {}
-            Operator::MemorySize { reserved } => {}
-            Operator::MemoryGrow { reserved } => {}
+                let local = &self.locals[*local_index as usize];
+                builder.build_store(*stack_value, local.pointer);
+            }
+            Operator::GetGlobal { global_index } => {
+                let global = declarations
+                    .globals
+                    .get(*global_index as usize)
+                    .ok_or("Global index out of bounds")?;
+
+                let value = match global.constant {
+                    Some(bits) => {
+                        Self::generate_constant_code(bits, &global.ty, builder, reusables)
+                    }
+                    None => {
+                        let pointer = self.generate_global_pointer(
+                            *global_index,
+                            &global.ty,
+                            function,
+                            builder,
+                            reusables,
+                        );
+                        builder.build_load(pointer, "global.get")
+                    }
+                };
+
+                self.stack.push(value);
+            }
+            Operator::SetGlobal { global_index } => {
+                let global = declarations
+                    .globals
+                    .get(*global_index as usize)
+                    .ok_or("Global index out of bounds")?;
+
+                if !global.mutable {
+                    return Err("Cannot set an immutable global");
+                }
+
+                // Guarantee: parser already done stack validation
+                let value = self.stack.pop().unwrap();
+                let pointer = self.generate_global_pointer(
+                    *global_index,
+                    &global.ty,
+                    function,
+                    builder,
+                    reusables,
+                );
+                builder.build_store(value, pointer);
+            }
+            Operator::I32Load { memarg } => {
+                self.generate_load_code(
+                    memarg,
+                    reusables.i32_type.ptr_type(&AddressSpace::Global),
+                    4,
+                    "i32.load",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Load { memarg } => {
+                self.generate_load_code(
+                    memarg,
+                    reusables.i64_type.ptr_type(&AddressSpace::Global),
+                    8,
+                    "i64.load",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::F32Load { memarg } => {
+                self.generate_load_code(
+                    memarg,
+                    reusables.f32_type.ptr_type(&AddressSpace::Global),
+                    4,
+                    "f32.load",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::F64Load { memarg } => {
+                self.generate_load_code(
+                    memarg,
+                    reusables.f64_type.ptr_type(&AddressSpace::Global),
+                    8,
+                    "f64.load",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32Load8S { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    reusables.i8_type,
+                    reusables.i32_type,
+                    true,
+                    "i32.load8_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32Load8U { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    reusables.i8_type,
+                    reusables.i32_type,
+                    false,
+                    "i32.load8_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32Load16S { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    context.i16_type(),
+                    reusables.i32_type,
+                    true,
+                    "i32.load16_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32Load16U { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    context.i16_type(),
+                    reusables.i32_type,
+                    false,
+                    "i32.load16_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Load8S { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    reusables.i8_type,
+                    reusables.i64_type,
+                    true,
+                    "i64.load8_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Load8U { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    reusables.i8_type,
+                    reusables.i64_type,
+                    false,
+                    "i64.load8_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Load16S { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    context.i16_type(),
+                    reusables.i64_type,
+                    true,
+                    "i64.load16_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Load16U { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    context.i16_type(),
+                    reusables.i64_type,
+                    false,
+                    "i64.load16_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Load32S { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    reusables.i32_type,
+                    reusables.i64_type,
+                    true,
+                    "i64.load32_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Load32U { memarg } => {
+                self.generate_extending_load_code(
+                    memarg,
+                    reusables.i32_type,
+                    reusables.i64_type,
+                    false,
+                    "i64.load32_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32Store { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                self.generate_store_code(
+                    memarg,
+                    value,
+                    reusables.i32_type.ptr_type(&AddressSpace::Global),
+                    4,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Store { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                self.generate_store_code(
+                    memarg,
+                    value,
+                    reusables.i64_type.ptr_type(&AddressSpace::Global),
+                    8,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::F32Store { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                self.generate_store_code(
+                    memarg,
+                    value,
+                    reusables.f32_type.ptr_type(&AddressSpace::Global),
+                    4,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::F64Store { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                self.generate_store_code(
+                    memarg,
+                    value,
+                    reusables.f64_type.ptr_type(&AddressSpace::Global),
+                    8,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32Store8 { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, reusables.i8_type, "i32.store8");
+                self.generate_store_code(
+                    memarg,
+                    value.into(),
+                    reusables.i8_type.ptr_type(&AddressSpace::Global),
+                    1,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32Store16 { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, context.i16_type(), "i32.store16");
+                self.generate_store_code(
+                    memarg,
+                    value.into(),
+                    context.i16_type().ptr_type(&AddressSpace::Global),
+                    2,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Store8 { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, reusables.i8_type, "i64.store8");
+                self.generate_store_code(
+                    memarg,
+                    value.into(),
+                    reusables.i8_type.ptr_type(&AddressSpace::Global),
+                    1,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Store16 { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, context.i16_type(), "i64.store16");
+                self.generate_store_code(
+                    memarg,
+                    value.into(),
+                    context.i16_type().ptr_type(&AddressSpace::Global),
+                    2,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64Store32 { memarg } => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, reusables.i32_type, "i64.store32");
+                self.generate_store_code(
+                    memarg,
+                    value.into(),
+                    reusables.i32_type.ptr_type(&AddressSpace::Global),
+                    4,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::MemorySize { .. } => {
+                // Guarantee: all functions take the InstanceContext pointer first.
+                let instance_context: PointerValue = function.get_first_param().unwrap().into();
+                let instance_context_type = function.get_type().unwrap().get_param_types()[0];
+                let value = MemoryGenerator::generate_memory_size_code(
+                    0,
+                    instance_context,
+                    instance_context_type,
+                    builder,
+                    reusables,
+                );
+                self.stack.push(value.into());
+            }
+            Operator::MemoryGrow { .. } => {
+                // Guarantee: parser already type checked stack values.
+                let delta: IntValue = self.stack.pop().unwrap().into();
+                // Guarantee: all functions take the InstanceContext pointer first.
+                let instance_context: PointerValue = function.get_first_param().unwrap().into();
+                let instance_context_type = function.get_type().unwrap().get_param_types()[0];
+                let value = MemoryGenerator::generate_memory_grow_code(
+                    delta,
+                    0,
+                    instance_context,
+                    instance_context_type,
+                    builder,
+                    reusables,
+                );
+                self.stack.push(value.into());
+            }
             Operator::I32Const { value } => {
                 let value: BasicValue = reusables.i32_type.const_int(*value as _, false).into();
                 self.stack.push(value);
@@ -200,285 +819,1008 @@ impl FunctionGenerator {
                 self.stack.push(value);
             }
             Operator::F32Const { value } => {
-                let value: BasicValue = reusables
-                    .f32_type
-                    .const_float((value.bits() as f32).into())
+                // Bit cast to preserve the exact bits, NaN payloads included.
+                let bits: BasicValue = reusables
+                    .i32_type
+                    .const_int(u64::from(value.bits()), false)
                     .into();
+                let value = builder.build_bitcast(bits, reusables.f32_type.into(), "f32.const");
                 self.stack.push(value);
             }
             Operator::F64Const { value } => {
-                let value: BasicValue = reusables.f64_type.const_float(value.bits() as f64).into();
+                // Bit cast to preserve the exact bits, NaN payloads included.
+                let bits: BasicValue = reusables.i64_type.const_int(value.bits(), false).into();
+                let value = builder.build_bitcast(bits, reusables.f64_type.into(), "f64.const");
                 self.stack.push(value);
             }
             Operator::RefNull => {}
             Operator::RefIsNull => {}
-            Operator::I32Eqz => {}
-            Operator::I32Eq => {}
-            Operator::I32Ne => {}
-            Operator::I32LtS => {}
-            Operator::I32LtU => {}
-            Operator::I32GtS => {}
-            Operator::I32GtU => {}
-            Operator::I32LeS => {}
-            Operator::I32LeU => {}
-            Operator::I32GeS => {}
-            Operator::I32GeU => {}
-            Operator::I64Eqz => {}
-            Operator::I64Eq => {}
-            Operator::I64Ne => {}
-            Operator::I64LtS => {}
-            Operator::I64LtU => {}
-            Operator::I64GtS => {}
-            Operator::I64GtU => {}
-            Operator::I64LeS => {}
-            Operator::I64LeU => {}
-            Operator::I64GeS => {}
-            Operator::I64GeU => {}
-            Operator::F32Eq => {}
-            Operator::F32Ne => {}
-            Operator::F32Lt => {}
-            Operator::F32Gt => {}
-            Operator::F32Le => {}
-            Operator::F32Ge => {}
-            Operator::F64Eq => {}
-            Operator::F64Ne => {}
-            Operator::F64Lt => {}
-            Operator::F64Gt => {}
-            Operator::F64Le => {}
-            Operator::F64Ge => {}
-            Operator::I32Clz => {}
-            Operator::I32Ctz => {}
-            Operator::I32Popcnt => {}
-            Operator::I32Add => {
+            Operator::I32Eqz => {
                 // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_int_add::<IntValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "i32.add",
-                    )
-                    .into();
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let zero = reusables.i32_type.zero(false);
+                let condition = builder.build_icmp(IntPredicate::EQ, value, zero, "i32.eqz");
+                let value = builder.build_int_z_extend(condition, reusables.i32_type, "i32.eqz");
+                self.stack.push(value.into());
+            }
+            Operator::I32Eq => {
+                self.generate_comparison_code(IntPredicate::EQ, "i32.eq", builder, reusables);
+            }
+            Operator::I32Ne => {
+                self.generate_comparison_code(IntPredicate::NE, "i32.ne", builder, reusables);
+            }
+            Operator::I32LtS => {
+                self.generate_comparison_code(IntPredicate::SLT, "i32.lt_s", builder, reusables);
+            }
+            Operator::I32LtU => {
+                self.generate_comparison_code(IntPredicate::ULT, "i32.lt_u", builder, reusables);
+            }
+            Operator::I32GtS => {
+                self.generate_comparison_code(IntPredicate::SGT, "i32.gt_s", builder, reusables);
+            }
+            Operator::I32GtU => {
+                self.generate_comparison_code(IntPredicate::UGT, "i32.gt_u", builder, reusables);
+            }
+            Operator::I32LeS => {
+                self.generate_comparison_code(IntPredicate::SLE, "i32.le_s", builder, reusables);
+            }
+            Operator::I32LeU => {
+                self.generate_comparison_code(IntPredicate::ULE, "i32.le_u", builder, reusables);
+            }
+            Operator::I32GeS => {
+                self.generate_comparison_code(IntPredicate::SGE, "i32.ge_s", builder, reusables);
+            }
+            Operator::I32GeU => {
+                self.generate_comparison_code(IntPredicate::UGE, "i32.ge_u", builder, reusables);
+            }
+            Operator::I64Eqz => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let zero = reusables.i64_type.zero(false);
+                let condition = builder.build_icmp(IntPredicate::EQ, value, zero, "i64.eqz");
+                let value = builder.build_int_z_extend(condition, reusables.i32_type, "i64.eqz");
+                self.stack.push(value.into());
+            }
+            Operator::I64Eq => {
+                self.generate_comparison_code(IntPredicate::EQ, "i64.eq", builder, reusables);
+            }
+            Operator::I64Ne => {
+                self.generate_comparison_code(IntPredicate::NE, "i64.ne", builder, reusables);
+            }
+            Operator::I64LtS => {
+                self.generate_comparison_code(IntPredicate::SLT, "i64.lt_s", builder, reusables);
+            }
+            Operator::I64LtU => {
+                self.generate_comparison_code(IntPredicate::ULT, "i64.lt_u", builder, reusables);
+            }
+            Operator::I64GtS => {
+                self.generate_comparison_code(IntPredicate::SGT, "i64.gt_s", builder, reusables);
+            }
+            Operator::I64GtU => {
+                self.generate_comparison_code(IntPredicate::UGT, "i64.gt_u", builder, reusables);
+            }
+            Operator::I64LeS => {
+                self.generate_comparison_code(IntPredicate::SLE, "i64.le_s", builder, reusables);
+            }
+            Operator::I64LeU => {
+                self.generate_comparison_code(IntPredicate::ULE, "i64.le_u", builder, reusables);
+            }
+            Operator::I64GeS => {
+                self.generate_comparison_code(IntPredicate::SGE, "i64.ge_s", builder, reusables);
+            }
+            Operator::I64GeU => {
+                self.generate_comparison_code(IntPredicate::UGE, "i64.ge_u", builder, reusables);
+            }
+            Operator::F32Eq => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OEQ,
+                    "f32.eq",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F32Ne => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::UNE,
+                    "f32.ne",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F32Lt => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OLT,
+                    "f32.lt",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F32Gt => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OGT,
+                    "f32.gt",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F32Le => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OLE,
+                    "f32.le",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F32Ge => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OGE,
+                    "f32.ge",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F64Eq => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OEQ,
+                    "f64.eq",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F64Ne => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::UNE,
+                    "f64.ne",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F64Lt => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OLT,
+                    "f64.lt",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F64Gt => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OGT,
+                    "f64.gt",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F64Le => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OLE,
+                    "f64.le",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::F64Ge => {
+                self.generate_float_comparison_code(
+                    FloatPredicate::OGE,
+                    "f64.ge",
+                    builder,
+                    reusables,
+                );
+            }
+            Operator::I32Clz => {
+                self.generate_bit_count_code(
+                    "ctlz",
+                    reusables.i32_type,
+                    "i32.clz",
+                    module,
+                    builder,
+                    context,
+                );
+            }
+            Operator::I32Ctz => {
+                self.generate_bit_count_code(
+                    "cttz",
+                    reusables.i32_type,
+                    "i32.ctz",
+                    module,
+                    builder,
+                    context,
+                );
+            }
+            Operator::I32Popcnt => {
+                self.generate_bit_count_code(
+                    "ctpop",
+                    reusables.i32_type,
+                    "i32.popcnt",
+                    module,
+                    builder,
+                    context,
+                );
+            }
+            Operator::I32Add => {
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_int_add(lhs, rhs, "i32.add").into();
                 self.stack.push(value);
             }
             Operator::I32Sub => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_int_sub::<IntValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "i32.sub",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_int_sub(lhs, rhs, "i32.sub").into();
                 self.stack.push(value);
             }
             Operator::I32Mul => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_int_mul::<IntValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "i32.mul",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_int_mul(lhs, rhs, "i32.mul").into();
                 self.stack.push(value);
             }
             Operator::I32DivS => {
-                // Div by zero checks!
-            }
-            Operator::I32DivU => {}
-            Operator::I32RemS => {}
-            Operator::I32RemU => {}
-            Operator::I32And => {}
-            Operator::I32Or => {}
-            Operator::I32Xor => {}
-            Operator::I32Shl => {}
-            Operator::I32ShrS => {}
-            Operator::I32ShrU => {}
-            Operator::I32Rotl => {}
-            Operator::I32Rotr => {}
-            Operator::I64Clz => {}
-            Operator::I64Ctz => {}
-            Operator::I64Popcnt => {}
+                self.generate_division_code(
+                    DivisionKind::SignedDiv,
+                    reusables.i32_type,
+                    "i32.div_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32DivU => {
+                self.generate_division_code(
+                    DivisionKind::UnsignedDiv,
+                    reusables.i32_type,
+                    "i32.div_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32RemS => {
+                self.generate_division_code(
+                    DivisionKind::SignedRem,
+                    reusables.i32_type,
+                    "i32.rem_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32RemU => {
+                self.generate_division_code(
+                    DivisionKind::UnsignedRem,
+                    reusables.i32_type,
+                    "i32.rem_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32And => {
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_and(lhs, rhs, "i32.and").into();
+                self.stack.push(value);
+            }
+            Operator::I32Or => {
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_or(lhs, rhs, "i32.or").into();
+                self.stack.push(value);
+            }
+            Operator::I32Xor => {
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_xor(lhs, rhs, "i32.xor").into();
+                self.stack.push(value);
+            }
+            Operator::I32Shl => {
+                self.generate_shift_code(ShiftKind::Left, reusables.i32_type, "i32.shl", builder);
+            }
+            Operator::I32ShrS => {
+                self.generate_shift_code(
+                    ShiftKind::SignedRight,
+                    reusables.i32_type,
+                    "i32.shr_s",
+                    builder,
+                );
+            }
+            Operator::I32ShrU => {
+                self.generate_shift_code(
+                    ShiftKind::UnsignedRight,
+                    reusables.i32_type,
+                    "i32.shr_u",
+                    builder,
+                );
+            }
+            Operator::I32Rotl => {
+                self.generate_rotate_code("fshl", reusables.i32_type, "i32.rotl", module, builder);
+            }
+            Operator::I32Rotr => {
+                self.generate_rotate_code("fshr", reusables.i32_type, "i32.rotr", module, builder);
+            }
+            Operator::I64Clz => {
+                self.generate_bit_count_code(
+                    "ctlz",
+                    reusables.i64_type,
+                    "i64.clz",
+                    module,
+                    builder,
+                    context,
+                );
+            }
+            Operator::I64Ctz => {
+                self.generate_bit_count_code(
+                    "cttz",
+                    reusables.i64_type,
+                    "i64.ctz",
+                    module,
+                    builder,
+                    context,
+                );
+            }
+            Operator::I64Popcnt => {
+                self.generate_bit_count_code(
+                    "ctpop",
+                    reusables.i64_type,
+                    "i64.popcnt",
+                    module,
+                    builder,
+                    context,
+                );
+            }
             Operator::I64Add => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_int_add::<IntValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "i64.add",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_int_add(lhs, rhs, "i64.add").into();
                 self.stack.push(value);
             }
             Operator::I64Sub => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_int_sub::<IntValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "i64.sub",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_int_sub(lhs, rhs, "i64.sub").into();
                 self.stack.push(value);
             }
             Operator::I64Mul => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_int_mul::<IntValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "i64.mul",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_int_mul(lhs, rhs, "i64.mul").into();
                 self.stack.push(value);
             }
-            Operator::I64DivS => {}
-            Operator::I64DivU => {}
-            Operator::I64RemS => {}
-            Operator::I64RemU => {}
-            Operator::I64And => {}
-            Operator::I64Or => {}
-            Operator::I64Xor => {}
-            Operator::I64Shl => {}
-            Operator::I64ShrS => {}
-            Operator::I64ShrU => {}
-            Operator::I64Rotl => {}
-            Operator::I64Rotr => {}
-            Operator::F32Abs => {}
-            Operator::F32Neg => {}
-            Operator::F32Ceil => {}
-            Operator::F32Floor => {}
-            Operator::F32Trunc => {}
-            Operator::F32Nearest => {}
-            Operator::F32Sqrt => {}
-            Operator::F32Add => {
+            Operator::I64DivS => {
+                self.generate_division_code(
+                    DivisionKind::SignedDiv,
+                    reusables.i64_type,
+                    "i64.div_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64DivU => {
+                self.generate_division_code(
+                    DivisionKind::UnsignedDiv,
+                    reusables.i64_type,
+                    "i64.div_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64RemS => {
+                self.generate_division_code(
+                    DivisionKind::SignedRem,
+                    reusables.i64_type,
+                    "i64.rem_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64RemU => {
+                self.generate_division_code(
+                    DivisionKind::UnsignedRem,
+                    reusables.i64_type,
+                    "i64.rem_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64And => {
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_and(lhs, rhs, "i64.and").into();
+                self.stack.push(value);
+            }
+            Operator::I64Or => {
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_or(lhs, rhs, "i64.or").into();
+                self.stack.push(value);
+            }
+            Operator::I64Xor => {
+                let (lhs, rhs) = self.pop_int_operands();
+                let value: BasicValue = builder.build_xor(lhs, rhs, "i64.xor").into();
+                self.stack.push(value);
+            }
+            Operator::I64Shl => {
+                self.generate_shift_code(ShiftKind::Left, reusables.i64_type, "i64.shl", builder);
+            }
+            Operator::I64ShrS => {
+                self.generate_shift_code(
+                    ShiftKind::SignedRight,
+                    reusables.i64_type,
+                    "i64.shr_s",
+                    builder,
+                );
+            }
+            Operator::I64ShrU => {
+                self.generate_shift_code(
+                    ShiftKind::UnsignedRight,
+                    reusables.i64_type,
+                    "i64.shr_u",
+                    builder,
+                );
+            }
+            Operator::I64Rotl => {
+                self.generate_rotate_code("fshl", reusables.i64_type, "i64.rotl", module, builder);
+            }
+            Operator::I64Rotr => {
+                self.generate_rotate_code("fshr", reusables.i64_type, "i64.rotr", module, builder);
+            }
+            Operator::F32Abs => {
                 // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_float_add::<FloatValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "f32.add",
-                    )
-                    .into();
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_abs(module, value, "f32.abs");
+                self.stack.push(value.into());
+            }
+            Operator::F32Neg => {
+                self.generate_neg_code(reusables.f32_type, reusables.i32_type, "f32.neg", builder);
+            }
+            Operator::F32Ceil => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_ceil(module, value, "f32.ceil");
+                self.stack.push(value.into());
+            }
+            Operator::F32Floor => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_floor(module, value, "f32.floor");
+                self.stack.push(value.into());
+            }
+            Operator::F32Trunc => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_trunc(module, value, "f32.trunc");
+                self.stack.push(value.into());
+            }
+            Operator::F32Nearest => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_nearest(module, value, "f32.nearest");
+                self.stack.push(value.into());
+            }
+            Operator::F32Sqrt => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_sqrt(module, value, "f32.sqrt");
+                self.stack.push(value.into());
+            }
+            Operator::F32Add => {
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_add(lhs, rhs, "f32.add").into();
                 self.stack.push(value);
             }
             Operator::F32Sub => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_float_sub::<FloatValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "f32.sub",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_sub(lhs, rhs, "f32.sub").into();
                 self.stack.push(value);
             }
             Operator::F32Mul => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_float_mul::<FloatValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "f32.mul",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_mul(lhs, rhs, "f32.mul").into();
                 self.stack.push(value);
             }
-            Operator::F32Div => {}
-            Operator::F32Min => {}
-            Operator::F32Max => {}
-            Operator::F32Copysign => {}
-            Operator::F64Abs => {}
-            Operator::F64Neg => {}
-            Operator::F64Ceil => {}
-            Operator::F64Floor => {}
-            Operator::F64Trunc => {}
-            Operator::F64Nearest => {}
-            Operator::F64Sqrt => {}
-            Operator::F64Add => {
+            Operator::F32Div => {
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_div(lhs, rhs, "f32.div").into();
+                self.stack.push(value);
+            }
+            Operator::F32Min => {
+                self.generate_min_max_code(
+                    true,
+                    reusables.f32_type,
+                    reusables.i32_type,
+                    "f32.min",
+                    builder,
+                );
+            }
+            Operator::F32Max => {
+                self.generate_min_max_code(
+                    false,
+                    reusables.f32_type,
+                    reusables.i32_type,
+                    "f32.max",
+                    builder,
+                );
+            }
+            Operator::F32Copysign => {
+                let (lhs, rhs) = self.pop_float_operands();
+                let value = builder.build_float_copysign(module, lhs, rhs, "f32.copysign");
+                self.stack.push(value.into());
+            }
+            Operator::F64Abs => {
                 // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_float_add::<FloatValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "f64.add",
-                    )
-                    .into();
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_abs(module, value, "f64.abs");
+                self.stack.push(value.into());
+            }
+            Operator::F64Neg => {
+                self.generate_neg_code(reusables.f64_type, reusables.i64_type, "f64.neg", builder);
+            }
+            Operator::F64Ceil => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_ceil(module, value, "f64.ceil");
+                self.stack.push(value.into());
+            }
+            Operator::F64Floor => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_floor(module, value, "f64.floor");
+                self.stack.push(value.into());
+            }
+            Operator::F64Trunc => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_trunc(module, value, "f64.trunc");
+                self.stack.push(value.into());
+            }
+            Operator::F64Nearest => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_nearest(module, value, "f64.nearest");
+                self.stack.push(value.into());
+            }
+            Operator::F64Sqrt => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value = builder.build_float_sqrt(module, value, "f64.sqrt");
+                self.stack.push(value.into());
+            }
+            Operator::F64Add => {
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_add(lhs, rhs, "f64.add").into();
                 self.stack.push(value);
             }
             Operator::F64Sub => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_float_sub::<FloatValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "f64.sub",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_sub(lhs, rhs, "f64.sub").into();
                 self.stack.push(value);
             }
             Operator::F64Mul => {
-                // Guarantee: parser already type checked stack values.
-                let value: BasicValue = builder
-                    .build_float_sub::<FloatValue>(
-                        self.stack.pop().unwrap().into(),
-                        self.stack.pop().unwrap().into(),
-                        "f32.mul",
-                    )
-                    .into();
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_mul(lhs, rhs, "f64.mul").into();
                 self.stack.push(value);
             }
             Operator::F64Div => {
-                // Div by zero checks!
-            }
-            Operator::F64Min => {}
-            Operator::F64Max => {}
-            Operator::F64Copysign => {}
-            Operator::I32WrapI64 => {}
-            Operator::I32TruncSF32 => {}
-            Operator::I32TruncUF32 => {}
-            Operator::I32TruncSF64 => {}
-            Operator::I32TruncUF64 => {}
-            Operator::I64ExtendSI32 => {}
-            Operator::I64ExtendUI32 => {}
-            Operator::I64TruncSF32 => {}
-            Operator::I64TruncUF32 => {}
-            Operator::I64TruncSF64 => {}
-            Operator::I64TruncUF64 => {}
-            Operator::F32ConvertSI32 => {}
-            Operator::F32ConvertUI32 => {}
-            Operator::F32ConvertSI64 => {}
-            Operator::F32ConvertUI64 => {}
-            Operator::F32DemoteF64 => {}
-            Operator::F64ConvertSI32 => {}
-            Operator::F64ConvertUI32 => {}
-            Operator::F64ConvertSI64 => {}
-            Operator::F64ConvertUI64 => {}
-            Operator::F64PromoteF32 => {}
-            Operator::I32ReinterpretF32 => {}
-            Operator::I64ReinterpretF64 => {}
-            Operator::F32ReinterpretI32 => {}
-            Operator::F64ReinterpretI64 => {}
-            Operator::I32Extend8S => {}
-            Operator::I32Extend16S => {}
-            Operator::I64Extend8S => {}
-            Operator::I64Extend16S => {}
-            Operator::I64Extend32S => {}
-
-            // 0xFC operators
-            // Non-trapping Float-to-int Conversions
-            Operator::I32TruncSSatF32 => {}
-            Operator::I32TruncUSatF32 => {}
-            Operator::I32TruncSSatF64 => {}
-            Operator::I32TruncUSatF64 => {}
-            Operator::I64TruncSSatF32 => {}
-            Operator::I64TruncUSatF32 => {}
-            Operator::I64TruncSSatF64 => {}
-            Operator::I64TruncUSatF64 => {}
-
-            // 0xFC operators
-            // bulk memory https://github.com/WebAssembly/bulk-memory-operations/blob/master/proposals/bulk-memory-operations/Overview.md
-            Operator::MemoryInit { .. } => return Err("Bulk memory ops not yet supported!"),
+                let (lhs, rhs) = self.pop_float_operands();
+                let value: BasicValue = builder.build_float_div(lhs, rhs, "f64.div").into();
+                self.stack.push(value);
+            }
+            Operator::F64Min => {
+                self.generate_min_max_code(
+                    true,
+                    reusables.f64_type,
+                    reusables.i64_type,
+                    "f64.min",
+                    builder,
+                );
+            }
+            Operator::F64Max => {
+                self.generate_min_max_code(
+                    false,
+                    reusables.f64_type,
+                    reusables.i64_type,
+                    "f64.max",
+                    builder,
+                );
+            }
+            Operator::F64Copysign => {
+                let (lhs, rhs) = self.pop_float_operands();
+                let value = builder.build_float_copysign(module, lhs, rhs, "f64.copysign");
+                self.stack.push(value.into());
+            }
+            Operator::I32WrapI64 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, reusables.i32_type, "i32.wrap_i64");
+                self.stack.push(value.into());
+            }
+            Operator::I32TruncSF32 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    false,
+                    true,
+                    false,
+                    "i32.trunc_f32_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32TruncUF32 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    false,
+                    false,
+                    false,
+                    "i32.trunc_f32_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32TruncSF64 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    true,
+                    true,
+                    false,
+                    "i32.trunc_f64_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32TruncUF64 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    true,
+                    false,
+                    false,
+                    "i32.trunc_f64_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64ExtendSI32 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value =
+                    builder.build_int_s_extend(value, reusables.i64_type, "i64.extend_i32_s");
+                self.stack.push(value.into());
+            }
+            Operator::I64ExtendUI32 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value =
+                    builder.build_int_z_extend(value, reusables.i64_type, "i64.extend_i32_u");
+                self.stack.push(value.into());
+            }
+            Operator::I64TruncSF32 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    false,
+                    true,
+                    false,
+                    "i64.trunc_f32_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64TruncUF32 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    false,
+                    false,
+                    false,
+                    "i64.trunc_f32_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64TruncSF64 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    true,
+                    true,
+                    false,
+                    "i64.trunc_f64_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64TruncUF64 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    true,
+                    false,
+                    false,
+                    "i64.trunc_f64_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::F32ConvertSI32 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_signed_int_to_float(
+                    value,
+                    reusables.f32_type,
+                    "f32.convert_i32_s",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F32ConvertUI32 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_unsigned_int_to_float(
+                    value,
+                    reusables.f32_type,
+                    "f32.convert_i32_u",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F32ConvertSI64 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_signed_int_to_float(
+                    value,
+                    reusables.f32_type,
+                    "f32.convert_i64_s",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F32ConvertUI64 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_unsigned_int_to_float(
+                    value,
+                    reusables.f32_type,
+                    "f32.convert_i64_u",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F32DemoteF64 => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value =
+                    builder.build_float_truncate(value, reusables.f32_type, "f32.demote_f64");
+                self.stack.push(value.into());
+            }
+            Operator::F64ConvertSI32 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_signed_int_to_float(
+                    value,
+                    reusables.f64_type,
+                    "f64.convert_i32_s",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F64ConvertUI32 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_unsigned_int_to_float(
+                    value,
+                    reusables.f64_type,
+                    "f64.convert_i32_u",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F64ConvertSI64 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_signed_int_to_float(
+                    value,
+                    reusables.f64_type,
+                    "f64.convert_i64_s",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F64ConvertUI64 => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_unsigned_int_to_float(
+                    value,
+                    reusables.f64_type,
+                    "f64.convert_i64_u",
+                );
+                self.stack.push(value.into());
+            }
+            Operator::F64PromoteF32 => {
+                // Guarantee: parser already type checked stack values.
+                let value: FloatValue = self.stack.pop().unwrap().into();
+                let value =
+                    builder.build_float_extend(value, reusables.f64_type, "f64.promote_f32");
+                self.stack.push(value.into());
+            }
+            Operator::I32ReinterpretF32 => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                let value =
+                    builder.build_bitcast(value, reusables.i32_type.into(), "i32.reinterpret_f32");
+                self.stack.push(value);
+            }
+            Operator::I64ReinterpretF64 => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                let value =
+                    builder.build_bitcast(value, reusables.i64_type.into(), "i64.reinterpret_f64");
+                self.stack.push(value);
+            }
+            Operator::F32ReinterpretI32 => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                let value =
+                    builder.build_bitcast(value, reusables.f32_type.into(), "f32.reinterpret_i32");
+                self.stack.push(value);
+            }
+            Operator::F64ReinterpretI64 => {
+                // Guarantee: parser already type checked stack values.
+                let value = self.stack.pop().unwrap();
+                let value =
+                    builder.build_bitcast(value, reusables.f64_type.into(), "f64.reinterpret_i64");
+                self.stack.push(value);
+            }
+            Operator::I32Extend8S => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, reusables.i8_type, "");
+                let value = builder.build_int_s_extend(value, reusables.i32_type, "i32.extend8_s");
+                self.stack.push(value.into());
+            }
+            Operator::I32Extend16S => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, context.i16_type(), "");
+                let value = builder.build_int_s_extend(value, reusables.i32_type, "i32.extend16_s");
+                self.stack.push(value.into());
+            }
+            Operator::I64Extend8S => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, reusables.i8_type, "");
+                let value = builder.build_int_s_extend(value, reusables.i64_type, "i64.extend8_s");
+                self.stack.push(value.into());
+            }
+            Operator::I64Extend16S => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, context.i16_type(), "");
+                let value = builder.build_int_s_extend(value, reusables.i64_type, "i64.extend16_s");
+                self.stack.push(value.into());
+            }
+            Operator::I64Extend32S => {
+                // Guarantee: parser already type checked stack values.
+                let value: IntValue = self.stack.pop().unwrap().into();
+                let value = builder.build_int_truncate(value, context.i32_type(), "");
+                let value = builder.build_int_s_extend(value, reusables.i64_type, "i64.extend32_s");
+                self.stack.push(value.into());
+            }
+
+            // 0xFC operators
+            // Non-trapping Float-to-int Conversions
+            Operator::I32TruncSSatF32 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    false,
+                    true,
+                    true,
+                    "i32.trunc_sat_f32_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32TruncUSatF32 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    false,
+                    false,
+                    true,
+                    "i32.trunc_sat_f32_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32TruncSSatF64 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    true,
+                    true,
+                    true,
+                    "i32.trunc_sat_f64_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I32TruncUSatF64 => {
+                self.generate_truncation_code(
+                    reusables.i32_type,
+                    true,
+                    false,
+                    true,
+                    "i32.trunc_sat_f64_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64TruncSSatF32 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    false,
+                    true,
+                    true,
+                    "i64.trunc_sat_f32_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64TruncUSatF32 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    false,
+                    false,
+                    true,
+                    "i64.trunc_sat_f32_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64TruncSSatF64 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    true,
+                    true,
+                    true,
+                    "i64.trunc_sat_f64_s",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+            Operator::I64TruncUSatF64 => {
+                self.generate_truncation_code(
+                    reusables.i64_type,
+                    true,
+                    false,
+                    true,
+                    "i64.trunc_sat_f64_u",
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+            }
+
+            // 0xFC operators
+            // bulk memory https://github.com/WebAssembly/bulk-memory-operations/blob/master/proposals/bulk-memory-operations/Overview.md
+            Operator::MemoryInit { .. } => return Err("Bulk memory ops not yet supported!"),
             Operator::DataDrop { .. } => return Err("Bulk memory ops not yet supported!"),
             Operator::MemoryCopy => return Err("Bulk memory ops not yet supported!"),
             Operator::MemoryFill => return Err("Bulk memory ops not yet supported!"),
@@ -712,42 +2054,1781 @@ impl FunctionGenerator {
         Ok(())
     }
 
+    /// Pops the two operands of an integer binary operator, returning them as `(lhs, rhs)`.
+    fn pop_int_operands(&mut self) -> (IntValue, IntValue) {
+        // Guarantee: parser already type checked stack values.
+        let rhs: IntValue = self.stack.pop().unwrap().into();
+        let lhs: IntValue = self.stack.pop().unwrap().into();
+
+        (lhs, rhs)
+    }
+
+    /// Pops two integers, compares them and pushes the result as an i32 that is 0 or 1.
+    fn generate_comparison_code(
+        &mut self,
+        predicate: IntPredicate,
+        name: &str,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) {
+        let (lhs, rhs) = self.pop_int_operands();
+        let condition = builder.build_icmp(predicate, lhs, rhs, name);
+        let value = builder.build_int_z_extend(condition, reusables.i32_type, name);
+
+        self.stack.push(value.into());
+    }
+
+    /// Pops the operands of an integer shift and pushes the shifted value.
+    ///
+    /// The shift amount is taken modulo the bit width. LLVM leaves larger amounts undefined.
+    fn generate_shift_code(&mut self, kind: ShiftKind, ty: IntType, name: &str, builder: &Builder) {
+        let (lhs, rhs) = self.pop_int_operands();
+        let mask = ty.const_int(u64::from(ty.get_bit_width() - 1), false);
+        let rhs = builder.build_and(rhs, mask, "shift.amount");
+
+        let value = match kind {
+            ShiftKind::Left => builder.build_left_shift(lhs, rhs, name),
+            ShiftKind::SignedRight => builder.build_right_shift(lhs, rhs, true, name),
+            ShiftKind::UnsignedRight => builder.build_right_shift(lhs, rhs, false, name),
+        };
+
+        self.stack.push(value.into());
+    }
+
+    /// Pops the operands of an integer rotate and pushes the rotated value.
+    ///
+    /// A rotate is a funnel shift of a value with itself, `llvm.fshl` for left and `llvm.fshr`
+    /// for right. Funnel shifts already take the amount modulo the bit width.
+    fn generate_rotate_code(
+        &mut self,
+        intrinsic: &str,
+        ty: IntType,
+        name: &str,
+        module: &Module,
+        builder: &Builder,
+    ) {
+        let (lhs, rhs) = self.pop_int_operands();
+        let intrinsic_type = function_type(&[ty.into(), ty.into(), ty.into()], ty.into(), false);
+        let intrinsic_function = module.get_or_declare_intrinsic(
+            &format!("llvm.{}.i{}", intrinsic, ty.get_bit_width()),
+            intrinsic_type,
+        );
+
+        // Guarantee: intrinsic returns a value.
+        let value = builder
+            .build_call(
+                &intrinsic_function,
+                &[lhs.into(), lhs.into(), rhs.into()],
+                name,
+            )
+            .unwrap();
+
+        self.stack.push(value);
+    }
+
+    /// Pops an integer and pushes its count of leading zeros, trailing zeros or set bits, using
+    /// `llvm.ctlz`, `llvm.cttz` or `llvm.ctpop` respectively.
+    fn generate_bit_count_code(
+        &mut self,
+        intrinsic: &str,
+        ty: IntType,
+        name: &str,
+        module: &Module,
+        builder: &Builder,
+        context: &Context,
+    ) {
+        // Guarantee: parser already type checked stack values.
+        let value: IntValue = self.stack.pop().unwrap().into();
+        let mut arg_types: Vec<BasicType> = vec![ty.into()];
+        let mut args: Vec<BasicValue> = vec![value.into()];
+
+        // `llvm.ctlz` and `llvm.cttz` take a flag that makes a zero operand undefined. Wasm
+        // defines it as the bit width.
+        if intrinsic != "ctpop" {
+            let bool_type = context.bool_type();
+            arg_types.push(bool_type.into());
+            args.push(bool_type.zero(false).into());
+        }
+
+        let intrinsic_function = module.get_or_declare_intrinsic(
+            &format!("llvm.{}.i{}", intrinsic, ty.get_bit_width()),
+            function_type(&arg_types, ty.into(), false),
+        );
+
+        // Guarantee: intrinsic returns a value.
+        let value = builder
+            .build_call(&intrinsic_function, &args, name)
+            .unwrap();
+
+        self.stack.push(value);
+    }
+
+    /// Pops the two operands of a float binary operator, returning them as `(lhs, rhs)`.
+    fn pop_float_operands(&mut self) -> (FloatValue, FloatValue) {
+        // Guarantee: parser already type checked stack values.
+        let rhs: FloatValue = self.stack.pop().unwrap().into();
+        let lhs: FloatValue = self.stack.pop().unwrap().into();
+
+        (lhs, rhs)
+    }
+
+    /// Pops two floats, compares them and pushes the result as an i32 that is 0 or 1.
+    ///
+    /// `ne` uses an unordered predicate since it is the only comparison that holds for NaN.
+    fn generate_float_comparison_code(
+        &mut self,
+        predicate: FloatPredicate,
+        name: &str,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) {
+        let (lhs, rhs) = self.pop_float_operands();
+        let condition = builder.build_fcmp(predicate, lhs, rhs, name);
+        let value = builder.build_int_z_extend(condition, reusables.i32_type, name);
+
+        self.stack.push(value.into());
+    }
+
+    /// Pops a float and pushes it with its sign bit flipped.
+    ///
+    /// Done on the bits since wasm requires NaNs to pass through untouched except for the sign.
+    fn generate_neg_code(
+        &mut self,
+        ty: FloatType,
+        int_type: IntType,
+        name: &str,
+        builder: &Builder,
+    ) {
+        // Guarantee: parser already type checked stack values.
+        let value = self.stack.pop().unwrap();
+        let bits: IntValue = builder.build_bitcast(value, int_type.into(), name).into();
+        let sign_bit = int_type.const_int(1 << (int_type.get_bit_width() - 1), false);
+        let bits = builder.build_xor(bits, sign_bit, name);
+        let value = builder.build_bitcast(bits.into(), ty.into(), name);
+
+        self.stack.push(value);
+    }
+
+    /// Pops two floats and pushes the lesser one if `is_min` is true, the greater one otherwise.
     ///
+    /// Unlike `llvm.minnum` and `llvm.maxnum`, wasm returns NaN if either operand is NaN and
+    /// orders -0 below +0.
+    fn generate_min_max_code(
+        &mut self,
+        is_min: bool,
+        ty: FloatType,
+        int_type: IntType,
+        name: &str,
+        builder: &Builder,
+    ) {
+        let (lhs, rhs) = self.pop_float_operands();
+
+        // Adding the operands yields a NaN if either of them is one.
+        let is_nan = builder.build_fcmp(FloatPredicate::UNO, lhs, rhs, "is_nan");
+        let nan = builder.build_float_add(lhs, rhs, "nan");
+
+        // Equal operands can only differ in sign if they are zeros. Or-ing the bits picks -0
+        // and and-ing them picks +0.
+        let is_equal = builder.build_fcmp(FloatPredicate::OEQ, lhs, rhs, "is_equal");
+        let lhs_bits: IntValue = builder
+            .build_bitcast(lhs.into(), int_type.into(), "")
+            .into();
+        let rhs_bits: IntValue = builder
+            .build_bitcast(rhs.into(), int_type.into(), "")
+            .into();
+        let equal_bits = if is_min {
+            builder.build_or(lhs_bits, rhs_bits, "")
+        } else {
+            builder.build_and(lhs_bits, rhs_bits, "")
+        };
+        let equal = builder.build_bitcast(equal_bits.into(), ty.into(), "equal");
+
+        let predicate = if is_min {
+            FloatPredicate::OLT
+        } else {
+            FloatPredicate::OGT
+        };
+        let is_lhs = builder.build_fcmp(predicate, lhs, rhs, "is_lhs");
+        let value = builder.build_select(is_lhs, lhs.into(), rhs.into(), "");
+        let value = builder.build_select(is_equal, equal, value, "");
+        let value = builder.build_select(is_nan, nan.into(), value, name);
+
+        self.stack.push(value);
+    }
+
+    /// Pops a float and pushes it truncated towards zero to an integer of type `ty`.
+    ///
+    /// NaN and out of range values trap or, if `saturating` is true, convert to 0 and the nearest
+    /// representable integer respectively.
+    fn generate_truncation_code(
+        &mut self,
+        ty: IntType,
+        from_f64: bool,
+        signed: bool,
+        saturating: bool,
+        name: &str,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) {
+        // Guarantee: parser already type checked stack values.
+        let value: FloatValue = self.stack.pop().unwrap().into();
+        let float_type = if from_f64 {
+            context.f64_type()
+        } else {
+            context.f32_type()
+        };
+        let bit_width = ty.get_bit_width();
+        let (lower_bound, upper_bound) = truncation_bounds(bit_width, from_f64, signed);
+        let lower_bound = float_type.const_float(lower_bound);
+        let upper_bound = float_type.const_float(upper_bound);
+
+        if saturating {
+            let (min, max) = if signed {
+                (1 << (bit_width - 1), !0 >> (65 - bit_width))
+            } else {
+                (0, !0 >> (64 - bit_width))
+            };
+
+            let is_nan = builder.build_fcmp(FloatPredicate::UNO, value, value, "is_nan");
+            let is_underflow =
+                builder.build_fcmp(FloatPredicate::OLE, value, lower_bound, "is_underflow");
+            let is_overflow =
+                builder.build_fcmp(FloatPredicate::OGE, value, upper_bound, "is_overflow");
+
+            // Out of range values make the conversion poison, but it is never selected then.
+            let truncated = if signed {
+                builder.build_float_to_signed_int(value, ty, "")
+            } else {
+                builder.build_float_to_unsigned_int(value, ty, "")
+            };
+            let value = builder.build_select(
+                is_underflow,
+                ty.const_int(min, false).into(),
+                truncated.into(),
+                "",
+            );
+            let value =
+                builder.build_select(is_overflow, ty.const_int(max, false).into(), value, "");
+            let value = builder.build_select(is_nan, ty.zero(false).into(), value, name);
+
+            self.stack.push(value);
+        } else {
+            // Unordered comparisons make NaN trap as well.
+            let is_underflow =
+                builder.build_fcmp(FloatPredicate::ULE, value, lower_bound, "is_underflow");
+            let is_overflow =
+                builder.build_fcmp(FloatPredicate::UGE, value, upper_bound, "is_overflow");
+            let is_invalid = builder.build_or(is_underflow, is_overflow, "is_invalid");
+            self.generate_trap_check(
+                TrapCode::InvalidConversionToInteger,
+                is_invalid,
+                function,
+                builder,
+                context,
+                reusables,
+            );
+
+            let value = if signed {
+                builder.build_float_to_signed_int(value, ty, name)
+            } else {
+                builder.build_float_to_unsigned_int(value, ty, name)
+            };
+
+            self.stack.push(value.into());
+        }
+    }
+
+    /// Gets a pointer to the cell of a global, typed after the global's value type.
+    fn generate_global_pointer(
+        &self,
+        global_index: u32,
+        ty: &ValueType,
+        function: &FunctionValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        // Guarantee: all functions take the InstanceContext pointer first.
+        let instance_context: PointerValue = function.get_first_param().unwrap().into();
+        let pointer_type = match ty {
+            ValueType::I32 => reusables.i32_type.ptr_type(&AddressSpace::Global),
+            ValueType::I64 => reusables.i64_type.ptr_type(&AddressSpace::Global),
+            ValueType::F32 => reusables.f32_type.ptr_type(&AddressSpace::Global),
+            ValueType::F64 => reusables.f64_type.ptr_type(&AddressSpace::Global),
+        };
+
+        GlobalGenerator::generate_global_pointer_code(
+            global_index,
+            pointer_type,
+            instance_context,
+            builder,
+            reusables,
+        )
+    }
+
+    /// Materializes a constant of type `ty` from its bits.
+    fn generate_constant_code(
+        bits: u64,
+        ty: &ValueType,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> BasicValue {
+        match ty {
+            ValueType::I32 => reusables.i32_type.const_int(bits, false).into(),
+            ValueType::I64 => reusables.i64_type.const_int(bits, false).into(),
+            // Bit cast to preserve the exact bits, NaN payloads included.
+            ValueType::F32 => {
+                let bits = reusables.i32_type.const_int(bits, false).into();
+                builder.build_bitcast(bits, reusables.f32_type.into(), "")
+            }
+            ValueType::F64 => {
+                let bits = reusables.i64_type.const_int(bits, false).into();
+                builder.build_bitcast(bits, reusables.f64_type.into(), "")
+            }
+        }
+    }
+
+    /// Calls a function in the function index space with arguments popped off the stack and
+    /// pushes its result.
+    fn generate_call_code(
+        &mut self,
+        function_index: u32,
+        declarations: &Declarations,
+        function: &FunctionValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> Result<(), &'static str> {
+        let import_count = declarations.function_imports.len();
+        let function_index = function_index as usize;
+
+        let result = if function_index < import_count {
+            // Imported functions are only known at instantiation.
+            let type_index = declarations.function_imports[function_index];
+            let callee_type = declarations.function_types[type_index as usize];
+            let mut args = self.pop_call_arguments(&callee_type, function);
+            let function_pointer = self.generate_imported_function_pointer(
+                function_index as u32,
+                callee_type,
+                function,
+                builder,
+                reusables,
+            );
+            // Imported functions run with the context of the instance or host providing them.
+            let callee_context = self.generate_imported_function_context(
+                function_index as u32,
+                function,
+                builder,
+                reusables,
+            );
+            args[0] = Self::generate_callee_context_code(callee_context, &callee_type, builder);
+
+            builder.build_indirect_call(function_pointer, &args, "call")
+        } else {
+            let callee = *declarations
+                .functions
+                .get(function_index - import_count)
+                .ok_or("Function index out of bounds")?;
+            // Guarantee: functions always have a function type.
+            let callee_type = callee.get_type().unwrap();
+            let args = self.pop_call_arguments(&callee_type, function);
+
+            builder.build_call(&callee, &args, "call")
+        };
+
+        if let Some(value) = result {
+            self.stack.push(value);
+        }
+
+        Ok(())
+    }
+
+    /// Loads the pointer of an imported function from the `InstanceContext` functions array.
+    fn generate_imported_function_pointer(
+        &self,
+        function_index: u32,
+        function_type: FunctionType,
+        function: &FunctionValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        // Guarantee: all functions take the InstanceContext pointer first.
+        let instance_context: PointerValue = function.get_first_param().unwrap().into();
+
+        let functions_pointer = builder.build_struct_gep(instance_context, FUNCTIONS_FIELD, "");
+        let functions: PointerValue = builder.build_load(functions_pointer, "functions").into();
+        let index = reusables
+            .i32_type
+            .const_int(u64::from(function_index), false);
+        let function_pointer = builder.build_gep(functions, &[index], "");
+        let function_pointer: PointerValue = builder
+            .build_load(function_pointer, "imported_function")
+            .into();
+
+        builder.build_pointer_cast(
+            function_pointer,
+            function_type.ptr_type(&AddressSpace::Generic),
+            "",
+        )
+    }
+
+    /// Loads the context of an imported function from the `InstanceContext` function contexts
+    /// array. It is the InstanceContext of the exporting instance, or the context of a host
+    /// function.
+    fn generate_imported_function_context(
+        &self,
+        function_index: u32,
+        function: &FunctionValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        // Guarantee: all functions take the InstanceContext pointer first.
+        let instance_context: PointerValue = function.get_first_param().unwrap().into();
+
+        let contexts_pointer =
+            builder.build_struct_gep(instance_context, FUNCTION_CONTEXTS_FIELD, "");
+        let contexts: PointerValue = builder
+            .build_load(contexts_pointer, "function_contexts")
+            .into();
+        let index = reusables
+            .i32_type
+            .const_int(u64::from(function_index), false);
+        let context_pointer = builder.build_gep(contexts, &[index], "");
+
+        builder
+            .build_load(context_pointer, "imported_function_context")
+            .into()
+    }
+
+    /// Casts the context of an import or table element to the InstanceContext pointer type
+    /// `callee_type` takes first.
+    fn generate_callee_context_code(
+        context: PointerValue,
+        callee_type: &FunctionType,
+        builder: &Builder,
+    ) -> BasicValue {
+        let context_type = match callee_type.get_param_types()[0] {
+            BasicType::PointerType(ty) => ty,
+            // Guarantee: all functions take the InstanceContext pointer first.
+            _ => unreachable!("Expected an InstanceContext pointer"),
+        };
+
+        builder
+            .build_pointer_cast(context, context_type, "callee_context")
+            .into()
+    }
+
+    /// Pops the arguments of a call to a function of type `callee_type` and prepends the
+    /// InstanceContext pointer to them.
+    fn pop_call_arguments(
+        &mut self,
+        callee_type: &FunctionType,
+        function: &FunctionValue,
+    ) -> Vec<BasicValue> {
+        // The first parameter is the InstanceContext pointer, which isn't on the stack.
+        let param_count = callee_type.count_param_types() as usize - 1;
+        let args = self.stack.split_off(self.stack.len() - param_count);
+        // Guarantee: all functions take the InstanceContext pointer first.
+        let instance_context = function.get_first_param().unwrap();
+
+        std::iter::once(instance_context).chain(args).collect()
+    }
+
+    /// Gets the function pointer and context of a table element for `call_indirect`.
+    ///
+    /// Traps if `element_index` is out of the table's bounds, if the element is uninitialized or
+    /// if the function's canonical type index is not `signature_id`.
+    fn generate_table_function_pointer(
+        &mut self,
+        table_index: u32,
+        element_index: IntValue,
+        signature_id: u32,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> (PointerValue, PointerValue) {
+        // Guarantee: all functions take the InstanceContext pointer first.
+        let instance_context: PointerValue = function.get_first_param().unwrap().into();
+        let element_index =
+            builder.build_int_z_extend(element_index, reusables.usize_type, "element_index");
+
+        let length = TableGenerator::generate_table_length_code(
+            table_index,
+            instance_context,
+            builder,
+            reusables,
+        );
+        let is_out_of_bounds =
+            builder.build_icmp(IntPredicate::UGE, element_index, length, "is_out_of_bounds");
+        self.generate_trap_check(
+            TrapCode::TableOutOfBounds,
+            is_out_of_bounds,
+            function,
+            builder,
+            context,
+            reusables,
+        );
+
+        let (function_pointer, type_id, callee_context) =
+            TableGenerator::generate_table_element_code(
+                table_index,
+                element_index,
+                instance_context,
+                builder,
+                reusables,
+            );
+
+        let is_null = builder.build_is_null(function_pointer, "is_null");
+        self.generate_trap_check(
+            TrapCode::IndirectCallToNull,
+            is_null,
+            function,
+            builder,
+            context,
+            reusables,
+        );
+
+        let signature_id = reusables.i32_type.const_int(u64::from(signature_id), false);
+        let is_mismatch = builder.build_icmp(
+            IntPredicate::NE,
+            type_id,
+            signature_id,
+            "is_signature_mismatch",
+        );
+        self.generate_trap_check(
+            TrapCode::SignatureMismatch,
+            is_mismatch,
+            function,
+            builder,
+            context,
+            reusables,
+        );
+
+        (function_pointer, callee_context)
+    }
+
+    /// Pops an address and pushes the value of type `ty` stored at `memarg.offset` past it.
+    fn generate_load_code(
+        &mut self,
+        memarg: &MemoryImmediate,
+        pointer_type: PointerType,
+        size: u64,
+        name: &str,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) {
+        let pointer = self.generate_memory_access_pointer(
+            memarg,
+            pointer_type,
+            size,
+            function,
+            builder,
+            context,
+            reusables,
+        );
+        let value = builder.build_load(pointer, name);
+
+        // Wasm doesn't require accesses to be aligned. The alignment in `memarg` is only a hint.
+        value.as_instruction().unwrap().set_alignment(1);
+
+        self.stack.push(value);
+    }
+
+    /// Pops an address and pushes the narrow integer stored at `memarg.offset` past it,
+    /// sign-extended to `ty` if `signed` is true and zero-extended otherwise.
+    fn generate_extending_load_code(
+        &mut self,
+        memarg: &MemoryImmediate,
+        narrow_type: IntType,
+        ty: IntType,
+        signed: bool,
+        name: &str,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) {
+        let pointer = self.generate_memory_access_pointer(
+            memarg,
+            narrow_type.ptr_type(&AddressSpace::Global),
+            u64::from(narrow_type.get_bit_width() / 8),
+            function,
+            builder,
+            context,
+            reusables,
+        );
+        let value = builder.build_load(pointer, "");
+        value.as_instruction().unwrap().set_alignment(1);
+
+        let value = if signed {
+            builder.build_int_s_extend(value.into(), ty, name)
+        } else {
+            builder.build_int_z_extend(value.into(), ty, name)
+        };
+
+        self.stack.push(value.into());
+    }
+
+    /// Pops an address and stores `value` at `memarg.offset` past it.
+    fn generate_store_code(
+        &mut self,
+        memarg: &MemoryImmediate,
+        value: BasicValue,
+        pointer_type: PointerType,
+        size: u64,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) {
+        let pointer = self.generate_memory_access_pointer(
+            memarg,
+            pointer_type,
+            size,
+            function,
+            builder,
+            context,
+            reusables,
+        );
+
+        builder.build_store(value, pointer).set_alignment(1);
+    }
+
+    /// Pops the address of a `size` bytes memory access and gets a pointer to the accessed value.
+    ///
+    /// With `MemoryStrategy::BoundsChecks`, traps if any of the accessed bytes is past the end of
+    /// the memory. With `MemoryStrategy::GuardPages`, out of bounds accesses fault instead.
+    fn generate_memory_access_pointer(
+        &mut self,
+        memarg: &MemoryImmediate,
+        pointer_type: PointerType,
+        size: u64,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        // Guarantee: parser already type checked stack values.
+        let address: IntValue = self.stack.pop().unwrap().into();
+        // Guarantee: all functions take the InstanceContext pointer first.
+        let instance_context: PointerValue = function.get_first_param().unwrap().into();
+        let address = MemoryGenerator::generate_effective_address_code(
+            address,
+            memarg.offset,
+            builder,
+            reusables,
+        );
+
+        if self.options.memory_strategy == MemoryStrategy::BoundsChecks {
+            let length = MemoryGenerator::generate_memory_length_code(
+                0,
+                instance_context,
+                builder,
+                reusables,
+            );
+            let end = builder.build_int_add(
+                address,
+                reusables.usize_type.const_int(size, false),
+                "access.end",
+            );
+            let is_out_of_bounds =
+                builder.build_icmp(IntPredicate::UGT, end, length, "is_out_of_bounds");
+            self.generate_trap_check(
+                TrapCode::MemoryOutOfBounds,
+                is_out_of_bounds,
+                function,
+                builder,
+                context,
+                reusables,
+            );
+        }
+
+        let base =
+            MemoryGenerator::generate_memory_base_code(0, instance_context, builder, reusables);
+        let pointer = builder.build_gep(base, &[address], "access");
+
+        builder.build_pointer_cast(pointer, pointer_type, "")
+    }
+
+    /// Pops the operands of an integer division and pushes the quotient or remainder.
+    ///
+    /// Traps if the divisor is zero or, for signed division, if the quotient overflows.
+    fn generate_division_code(
+        &mut self,
+        kind: DivisionKind,
+        ty: IntType,
+        name: &str,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) {
+        let (lhs, rhs) = self.pop_int_operands();
+        let minus_one = ty.const_int(-1i64 as u64, true);
+
+        let is_zero = builder.build_icmp(IntPredicate::EQ, rhs, ty.zero(false), "is_zero");
+        self.generate_trap_check(
+            TrapCode::IntegerDivisionByZero,
+            is_zero,
+            function,
+            builder,
+            context,
+            reusables,
+        );
+
+        let value = match kind {
+            DivisionKind::SignedDiv => {
+                // INT_MIN / -1 is not representable.
+                let int_min = ty.const_int(1 << (ty.get_bit_width() - 1), false);
+                let is_int_min = builder.build_icmp(IntPredicate::EQ, lhs, int_min, "is_int_min");
+                let is_minus_one =
+                    builder.build_icmp(IntPredicate::EQ, rhs, minus_one, "is_minus_one");
+                let is_overflow = builder.build_and(is_int_min, is_minus_one, "is_overflow");
+                self.generate_trap_check(
+                    TrapCode::IntegerOverflow,
+                    is_overflow,
+                    function,
+                    builder,
+                    context,
+                    reusables,
+                );
+
+                builder.build_int_signed_div(lhs, rhs, name)
+            }
+            DivisionKind::UnsignedDiv => builder.build_int_unsigned_div(lhs, rhs, name),
+            DivisionKind::SignedRem => {
+                // INT_MIN % -1 is 0 in wasm but overflows in LLVM. Any value % -1 is the same
+                // as % 1, so the divisor is swapped.
+                let is_minus_one =
+                    builder.build_icmp(IntPredicate::EQ, rhs, minus_one, "is_minus_one");
+                let rhs = builder
+                    .build_select(
+                        is_minus_one,
+                        ty.const_int(1, false).into(),
+                        rhs.into(),
+                        "divisor",
+                    )
+                    .into();
+
+                builder.build_int_signed_rem(lhs, rhs, name)
+            }
+            DivisionKind::UnsignedRem => builder.build_int_unsigned_rem(lhs, rhs, name),
+        };
+
+        self.stack.push(value.into());
+    }
+
+    /// Gets the function's trap block, creating it if this is the first check that needs it. The
+    /// block traps with `code` and the offset of the current operator when the current block
+    /// branches to it.
+    fn get_trap_block(
+        &mut self,
+        code: TrapCode,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> BasicBlock {
+        let current_block = builder.get_insert_block().unwrap();
+
+        if self.trap_block.is_none() {
+            let block = function.append_basic_block("trap", context);
+            builder.position_at_end(&block);
+
+            let code = builder.build_phi(reusables.i32_type.into(), "trap_code");
+            let offset = builder.build_phi(reusables.i32_type.into(), "trap_offset");
+
+            // Guarantee: all functions take the InstanceContext pointer first.
+            let instance_context: PointerValue = function.get_first_param().unwrap().into();
+            let instance_context_type = function.get_type().unwrap().get_param_types()[0];
+            ExceptionGenerator::generate_trap_code(
+                code.as_basic_value().into(),
+                offset.as_basic_value().into(),
+                instance_context,
+                instance_context_type,
+                builder,
+                context,
+                reusables,
+            );
+            builder.position_at_end(&current_block);
+
+            self.trap_block = Some(TrapBlock {
+                block,
+                code,
+                offset,
+            });
+        }
+
+        let code = reusables.i32_type.const_int(code as u64, false);
+        let offset = reusables.i32_type.const_int(u64::from(self.offset), false);
+
+        // Guarantee: the trap block was just created if it didn't exist.
+        let trap_block = self.trap_block.as_ref().unwrap();
+        trap_block
+            .code
+            .add_incoming(&[(code.into(), current_block)]);
+        trap_block
+            .offset
+            .add_incoming(&[(offset.into(), current_block)]);
+
+        trap_block.block
+    }
+
+    /// Branches to the trap block if `condition` is true and continues in a new block otherwise.
+    fn generate_trap_check(
+        &mut self,
+        code: TrapCode,
+        condition: IntValue,
+        function: &FunctionValue,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) {
+        let trap_block = self.get_trap_block(code, function, builder, context, reusables);
+        let continue_block = function.append_basic_block("check.ok", context);
+
+        builder.build_cond_br(condition, &trap_block, &continue_block);
+        builder.position_at_end(&continue_block);
+    }
+
+    /// Pops an i32 condition off the stack and compares it against zero.
+    fn pop_condition(&mut self, builder: &Builder, reusables: &Reusables) -> IntValue {
+        // Guarantee: parser already type checked stack values.
+        let value: IntValue = self.stack.pop().unwrap().into();
+
+        builder.build_icmp(
+            IntPredicate::NE,
+            value,
+            reusables.i32_type.zero(false),
+            "condition",
+        )
+    }
+
+    /// Records the values carried by a branch from `block` to the frame at `relative_depth`
+    /// and returns the block the branch should jump to.
+    fn add_branch_edge(
+        &mut self,
+        relative_depth: u32,
+        block: BasicBlock,
+    ) -> Result<BasicBlock, &'static str> {
+        let frame_index = self
+            .control_frames
+            .len()
+            .checked_sub(relative_depth as usize + 1)
+            .ok_or("Branch depth exceeds control frames")?;
+        let frame = &mut self.control_frames[frame_index];
+        let results = self.stack[self.stack.len() - frame.branch_arity()..].to_vec();
+
+        // Loop headers take no values.
+        if frame.kind != ControlKind::Loop {
+            frame.incoming.push(Incoming { block, results });
+        }
+
+        Ok(frame.branch_block)
+    }
+
+    /// Falls through from the current block to the innermost frame's next block.
+    fn add_fallthrough_edge(&mut self, builder: &Builder) -> Result<(), &'static str> {
+        let current_block = builder.get_insert_block().unwrap();
+        let frame = self
+            .control_frames
+            .last_mut()
+            .ok_or("End without a frame")?;
+        let results = self
+            .stack
+            .split_off(self.stack.len() - frame.result_types.len());
+
+        builder.build_br(&frame.next_block);
+        frame.incoming.push(Incoming {
+            block: current_block,
+            results,
+        });
+
+        Ok(())
+    }
+
+    /// Marks the rest of the innermost frame as unreachable.
+    fn enter_unreachable_code(&mut self) -> Result<(), &'static str> {
+        let frame = self
+            .control_frames
+            .last()
+            .ok_or("No frame to branch out of")?;
+
+        self.stack.truncate(frame.stack_height);
+        self.reachable = false;
+
+        Ok(())
+    }
+
+    /// Closes the innermost frame and merges the results flowing into its next block.
+    fn generate_end_code(&mut self, builder: &Builder) -> Result<(), &'static str> {
+        if self.reachable {
+            self.add_fallthrough_edge(builder)?;
+        }
+
+        let mut frame = self.control_frames.pop().ok_or("End without a frame")?;
+
+        // An `if` without an `else` goes straight to the next block when its condition is false.
+        if let ControlKind::If { else_block } = frame.kind {
+            builder.position_at_end(&else_block);
+            builder.build_br(&frame.next_block);
+            frame.incoming.push(Incoming {
+                block: else_block,
+                results: Vec::new(),
+            });
+        }
+
+        self.stack.truncate(frame.stack_height);
+
+        if let Some(current_block) = builder.get_insert_block() {
+            frame.next_block.move_after(&current_block);
+        }
+        builder.position_at_end(&frame.next_block);
+
+        // Nothing jumps to the next block, so code that follows the frame is unreachable too.
+        if frame.incoming.is_empty() {
+            builder.build_unreachable();
+            self.reachable = false;
+            return Ok(());
+        }
+
+        self.reachable = true;
+
+        // Merge results.
+        for (result_index, ty) in frame.result_types.iter().enumerate() {
+            let incoming = frame
+                .incoming
+                .iter()
+                .map(|edge| (edge.results[result_index], edge.block))
+                .collect::<Vec<_>>();
+
+            let value = FunctionGenerator::merge_values(builder, *ty, &incoming, "result");
+            self.stack.push(value);
+        }
+
+        // The function frame's next block returns what's left on stack.
+        if frame.kind == ControlKind::Function {
+            match *self.stack.as_slice() {
+                [value] => builder.build_return(Some(value)),
+                [] => builder.build_return(None),
+                _ => return Err("Multiple return values not supported yet"),
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Creates a phi node for values coming from different blocks. No phi node is needed if
+    /// every edge carries the same value.
+    fn merge_values(
+        builder: &Builder,
+        ty: BasicType,
+        incoming: &[(BasicValue, BasicBlock)],
+        name: &str,
+    ) -> BasicValue {
+        let first_value = incoming[0].0;
+
+        if incoming.iter().all(|(value, _)| *value == first_value) {
+            return first_value;
+        }
+
+        let phi = builder.build_phi(ty, name);
+        phi.add_incoming(incoming);
+        phi.as_basic_value()
+    }
+
+    /// Generates the empty function that marks where the code of the wasm functions ends. It must be
+    /// generated after them, and before any other function with a body.
+    pub fn generate_code_end_function(
+        &mut self,
+        module: &mut Module,
+        builder: &Builder,
+        context: &Context,
+    ) -> FunctionValue {
+        let function_type = function_type(&[], context.void_type().into(), false);
+        let function = module.add_function(CODE_END_NAME, function_type, None);
+        let basic_block = function.append_basic_block("entry", context);
+        builder.position_at_end(&basic_block);
+        builder.build_return(None);
+
+        function
+    }
+
+    /// Generates the function that initializes an instance's tables and memories from the active
+    /// element and data segments, then calls the start function. It traps if a segment doesn't fit
+    /// in its table or memory.
+    pub fn generate_initialization_function(
+        &mut self,
+        module: &mut Module,
+        runtime_data: &ModuleData,
+        declarations: &Declarations,
+        instance_context_type: &PointerType,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> Result<FunctionValue, &'static str> {
+        let function_type = function_type(
+            &[(*instance_context_type).into()],
+            context.void_type().into(),
+            false,
+        );
+        let function = module.add_function(INITIALIZE_FUNCTION_NAME, function_type, None);
+        let basic_block = function.append_basic_block("entry", context);
+        builder.position_at_end(&basic_block);
+
+        // Guarantee: the function takes the InstanceContext pointer first.
+        let instance_context: PointerValue = function.get_first_param().unwrap().into();
+
+        for element in runtime_data.locals.elements.iter() {
+            // Passive segments are only copied by `table.init`.
+            let offset = match &element.offset {
+                Some(offset) => offset,
+                None => continue,
+            };
+
+            let offset: IntValue = self
+                .generate_init_expression_code(
+                    offset,
+                    &ValueType::I32,
+                    &function,
+                    builder,
+                    reusables,
+                )
+                .into();
+            let offset = builder.build_int_z_extend(offset, reusables.usize_type, "offset");
+            let count = reusables
+                .usize_type
+                .const_int(element.function_indices.len() as u64, false);
+            let end = builder.build_int_add(offset, count, "end");
+            let length = TableGenerator::generate_table_length_code(
+                element.table_index,
+                instance_context,
+                builder,
+                reusables,
+            );
+            let is_out_of_bounds =
+                builder.build_icmp(IntPredicate::UGT, end, length, "is_out_of_bounds");
+            self.generate_trap_check(
+                TrapCode::TableOutOfBounds,
+                is_out_of_bounds,
+                &function,
+                builder,
+                context,
+                reusables,
+            );
+
+            let elements = element
+                .function_indices
+                .iter()
+                .map(|function_index| {
+                    self.generate_func_ref_code(
+                        *function_index,
+                        runtime_data,
+                        declarations,
+                        &function,
+                        builder,
+                        reusables,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            TableGenerator::generate_table_initialization_code(
+                element.table_index,
+                offset,
+                &elements,
+                instance_context,
+                builder,
+                reusables,
+            );
+        }
+
+        for (segment_index, segment) in runtime_data.locals.data_segments.iter().enumerate() {
+            // Passive segments are only copied by `memory.init`.
+            let offset = match &segment.offset {
+                Some(offset) => offset,
+                None => continue,
+            };
+
+            let offset: IntValue = self
+                .generate_init_expression_code(
+                    offset,
+                    &ValueType::I32,
+                    &function,
+                    builder,
+                    reusables,
+                )
+                .into();
+            let offset = builder.build_int_z_extend(offset, reusables.usize_type, "offset");
+            let count = reusables
+                .usize_type
+                .const_int(segment.bytes.len() as u64, false);
+            let end = builder.build_int_add(offset, count, "end");
+            let length = MemoryGenerator::generate_memory_length_code(
+                segment.memory_index,
+                instance_context,
+                builder,
+                reusables,
+            );
+            let is_out_of_bounds =
+                builder.build_icmp(IntPredicate::UGT, end, length, "is_out_of_bounds");
+            self.generate_trap_check(
+                TrapCode::MemoryOutOfBounds,
+                is_out_of_bounds,
+                &function,
+                builder,
+                context,
+                reusables,
+            );
+
+            MemoryGenerator::generate_memory_initialization_code(
+                segment.memory_index,
+                offset,
+                &segment.bytes,
+                &format!("data_segment_{}", segment_index),
+                instance_context,
+                module,
+                builder,
+                context,
+                reusables,
+            );
+        }
+
+        // The start function runs once tables and memories are initialized.
+        if let Some(start_function) = runtime_data.start_function {
+            self.generate_call_code(start_function, declarations, &function, builder, reusables)?;
+        }
+
+        builder.build_return(None);
+
+        Ok(function)
+    }
+
+    /// Evaluates a constant expression of type `ty`.
+    fn generate_init_expression_code(
+        &self,
+        init: &InitExpression,
+        ty: &ValueType,
+        function: &FunctionValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> BasicValue {
+        match init {
+            InitExpression::Const(bits) => {
+                Self::generate_constant_code(*bits, ty, builder, reusables)
+            }
+            InitExpression::GetGlobal(global_index) => {
+                let pointer =
+                    self.generate_global_pointer(*global_index, ty, function, builder, reusables);
+                builder.build_load(pointer, "global.get")
+            }
+        }
+    }
+
+    /// Gets the function pointer, canonical type index and context a table element referring to
+    /// `function_index` holds.
+    fn generate_func_ref_code(
+        &self,
+        function_index: u32,
+        runtime_data: &ModuleData,
+        declarations: &Declarations,
+        function: &FunctionValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> Result<(PointerValue, IntValue, PointerValue), &'static str> {
+        let type_index = runtime_data
+            .function_type_index(function_index)
+            .ok_or("Function index out of bounds")?;
+        let signature_id = declarations.signature_ids[type_index as usize];
+        let type_id = reusables.i32_type.const_int(u64::from(signature_id), false);
+
+        let import_count = declarations.function_imports.len() as u32;
+        let (function_pointer, callee_context) = if function_index < import_count {
+            let function_pointer = self.generate_imported_function_pointer(
+                function_index,
+                declarations.function_types[type_index as usize],
+                function,
+                builder,
+                reusables,
+            );
+            let callee_context = self.generate_imported_function_context(
+                function_index,
+                function,
+                builder,
+                reusables,
+            );
+
+            (function_pointer, callee_context)
+        } else {
+            let function_pointer =
+                declarations.functions[(function_index - import_count) as usize].as_pointer_value();
+            // Guarantee: all functions take the InstanceContext pointer first.
+            let instance_context: PointerValue = function.get_first_param().unwrap().into();
+
+            (function_pointer, instance_context)
+        };
+
+        // Table elements store functions as `*const ()` and contexts as `*mut usize`.
+        let pointer_type = reusables.i8_type.ptr_type(&AddressSpace::Global);
+        let function_pointer = builder.build_pointer_cast(function_pointer, pointer_type, "");
+        let callee_context = builder.build_pointer_cast(callee_context, pointer_type, "");
+
+        Ok((function_pointer, type_id, callee_context))
+    }
+
+    /// Generates the trampoline the runtime calls functions of type `type_index` through.
+    ///
+    /// The trampoline takes the InstanceContext pointer, the function to call, an array of
+    /// argument cells and an array of result cells. Like globals, every value occupies a 64-bit
+    /// cell with narrower values in its low-order bytes.
+    pub fn generate_trampoline_function(
+        &mut self,
+        type_index: u32,
+        module: &mut Module,
+        runtime_data: &ModuleData,
+        declarations: &Declarations,
+        instance_context_type: &PointerType,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> Result<FunctionValue, &'static str> {
+        let func_type = runtime_data
+            .locals
+            .types
+            .get(type_index as usize)
+            .ok_or("Function type index out of bounds")?;
+        let callee_type = declarations.function_types[type_index as usize];
+
+        let cells_type = reusables.i64_type.ptr_type(&AddressSpace::Generic);
+        let function_type = function_type(
+            &[
+                (*instance_context_type).into(),
+                reusables.i8_type.ptr_type(&AddressSpace::Generic).into(),
+                cells_type.into(),
+                cells_type.into(),
+            ],
+            context.void_type().into(),
+            false,
+        );
+        let name = format!("{}{}", TRAMPOLINE_NAME_PREFIX, type_index);
+        let function = module.add_function(&name, function_type, None);
+        let basic_block = function.append_basic_block("entry", context);
+        builder.position_at_end(&basic_block);
+
+        // Guarantee: the function was created with four parameters.
+        let instance_context = function.get_nth_param(0).unwrap();
+        let callee: PointerValue = function.get_nth_param(1).unwrap().into();
+        let arguments: PointerValue = function.get_nth_param(2).unwrap().into();
+        let results: PointerValue = function.get_nth_param(3).unwrap().into();
+
+        let mut args = vec![instance_context];
+        for (index, ty) in func_type.params().iter().enumerate() {
+            let index = reusables.i32_type.const_int(index as u64, false);
+            let cell = builder.build_gep(arguments, &[index], "");
+            let bits: IntValue = builder.build_load(cell, "argument").into();
+            args.push(Self::generate_value_from_bits_code(
+                bits, ty, builder, reusables,
+            ));
+        }
+
+        let callee =
+            builder.build_pointer_cast(callee, callee_type.ptr_type(&AddressSpace::Generic), "");
+        let result = builder.build_indirect_call(callee, &args, "result");
+
+        if let (Some(result), Some(ty)) = (result, func_type.returns().first()) {
+            let bits = Self::generate_bits_from_value_code(result, ty, builder, reusables);
+            let index = reusables.i32_type.const_int(0, false);
+            let cell = builder.build_gep(results, &[index], "");
+            builder.build_store(bits.into(), cell);
+        }
+
+        builder.build_return(None);
+
+        Ok(function)
+    }
+
+    /// Gets a value of type `ty` from the low-order bytes of a 64-bit cell.
+    fn generate_value_from_bits_code(
+        bits: IntValue,
+        ty: &ValueType,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> BasicValue {
+        match ty {
+            ValueType::I32 => builder
+                .build_int_truncate(bits, reusables.i32_type, "")
+                .into(),
+            ValueType::I64 => bits.into(),
+            ValueType::F32 => {
+                let bits = builder.build_int_truncate(bits, reusables.i32_type, "");
+                builder.build_bitcast(bits.into(), reusables.f32_type.into(), "")
+            }
+            ValueType::F64 => builder.build_bitcast(bits.into(), reusables.f64_type.into(), ""),
+        }
+    }
+
+    /// Zero-extends the bits of a value of type `ty` to a 64-bit cell.
+    fn generate_bits_from_value_code(
+        value: BasicValue,
+        ty: &ValueType,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> IntValue {
+        match ty {
+            ValueType::I32 => builder.build_int_z_extend(value.into(), reusables.i64_type, ""),
+            ValueType::I64 => value.into(),
+            ValueType::F32 => {
+                let bits = builder.build_bitcast(value, reusables.i32_type.into(), "");
+                builder.build_int_z_extend(bits.into(), reusables.i64_type, "")
+            }
+            ValueType::F64 => builder
+                .build_bitcast(value, reusables.i64_type.into(), "")
+                .into(),
+        }
+    }
+
+    /// Generates the `main` of an executable. It describes the module to the runtime support
+    /// library, which instantiates it with `initialize` and calls the `_start` export, see
+    /// `wasmo_runtime::executable`. `main` returns what `wasmo_main` returns, the exit status.
     pub fn generate_main_function(
         &mut self,
+        initialize: &FunctionValue,
         module: &mut Module,
+        runtime_data: &ModuleData,
+        declarations: &Declarations,
         builder: &Builder,
         context: &Context,
         reusables: &Reusables,
-    ) -> Result<(), &'static str> {
-        //
+    ) -> Result<FunctionValue, &'static str> {
+        let (start_function, start_returns_status) = match runtime_data.exports.get("_start") {
+            Some(ExportKind::Function(index)) => {
+                // Guarantee: exports are validated against the function index space.
+                let ty = runtime_data.function_type(*index).unwrap();
+                if !ty.params().is_empty() {
+                    return Err("`_start` must not take parameters");
+                }
+
+                match ty.returns() {
+                    [] => (i64::from(*index), false),
+                    [ValueType::I32] => (i64::from(*index), true),
+                    _ => return Err("`_start` must return nothing or an i32"),
+                }
+            }
+            Some(_) => return Err("`_start` must be a function"),
+            None => (NO_START_FUNCTION, false),
+        };
+
+        let pointer_type = reusables.i8_type.ptr_type(&AddressSpace::Generic);
+        let wasmo_main = Self::get_or_add_external_function(
+            MAIN_NAME,
+            function_type(
+                &[
+                    pointer_type.into(),
+                    pointer_type.into(),
+                    reusables.i64_type.into(),
+                    reusables.i32_type.into(),
+                ],
+                reusables.i32_type.into(),
+                false,
+            ),
+            module,
+        );
+
         let function_type = function_type(&[], reusables.i32_type.into(), false);
         let function = module.add_function("main", function_type, None);
-        let basic_block = function.append_basic_block("entry", &context);
+        let basic_block = function.append_basic_block("entry", context);
+        builder.position_at_end(&basic_block);
+
+        let data = Self::generate_module_data_code(
+            module,
+            runtime_data,
+            declarations,
+            builder,
+            context,
+            reusables,
+        )?;
+
+        let initialize =
+            builder.build_pointer_cast(initialize.as_pointer_value(), pointer_type, "");
+        let args = [
+            data,
+            initialize.into(),
+            reusables
+                .i64_type
+                .const_int(start_function as u64, true)
+                .into(),
+            reusables
+                .i32_type
+                .const_int(u64::from(start_returns_status), false)
+                .into(),
+        ];
+
+        // Guarantee: `wasmo_main` returns the exit status.
+        let status = builder.build_call(&wasmo_main, &args, "status").unwrap();
+        builder.build_return(Some(status));
+
+        Ok(function)
+    }
+
+    /// Generates the function a static library instantiates its module with. It describes the
+    /// module to the runtime support library like `main` does and returns the instance
+    /// `wasmo_instantiate` creates, see `wasmo_runtime::library`.
+    pub fn generate_instantiate_function(
+        &mut self,
+        name: &str,
+        initialize: &FunctionValue,
+        module: &mut Module,
+        runtime_data: &ModuleData,
+        declarations: &Declarations,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> Result<FunctionValue, &'static str> {
+        // `*mut InstanceAOT` is opaque to generated code.
+        let pointer_type = reusables.i8_type.ptr_type(&AddressSpace::Generic);
+        let wasmo_instantiate = Self::get_or_add_external_function(
+            INSTANTIATE_NAME,
+            function_type(
+                &[pointer_type.into(), pointer_type.into()],
+                pointer_type.into(),
+                false,
+            ),
+            module,
+        );
 
+        let function_type = function_type(&[], pointer_type.into(), false);
+        let function = module.add_function(name, function_type, None);
+        let basic_block = function.append_basic_block("entry", context);
         builder.position_at_end(&basic_block);
 
-        // TODO: Abstraction ahead!
+        let data = Self::generate_module_data_code(
+            module,
+            runtime_data,
+            declarations,
+            builder,
+            context,
+            reusables,
+        )?;
+
+        let initialize =
+            builder.build_pointer_cast(initialize.as_pointer_value(), pointer_type, "");
 
-        // CREATIONS
-        // Create memories -> call mmap(...) dynamic libc.dylib | call VirtualAlloc(...) dynamic win32.dll
-        // Create global -> build_global
-        // Create tables -> call mmap(...) dynamic libc.dylib | call VirtualAlloc(...) dynamic win32.dll
+        // Guarantee: `wasmo_instantiate` returns a pointer.
+        let instance = builder
+            .build_call(&wasmo_instantiate, &[data, initialize.into()], "instance")
+            .unwrap();
+        builder.build_return(Some(instance));
 
-        // INITIALIZATIONS
-        // initialize memories -> loop and place const values
-        // initialize tables -> loop and place const values
+        Ok(function)
+    }
 
-        // INSTANCECONTEXT
-        // create global -> build_global instancecontext
-        // assign to instancecontext -> assign pointer values from CREATIONS to fields
-        // assign to instancecontext.functions -> get address of each function
+    /// Generates the C function a static library exports a wasm function as. It takes the
+    /// instance and the parameters of the function, and a pointer to store the result at if the
+    /// function returns one. It calls the function through `wasmo_instance_call`, so that traps
+    /// are caught, and returns what that returns.
+    pub fn generate_export_wrapper_function(
+        &mut self,
+        name: &str,
+        function_index: u32,
+        module: &mut Module,
+        runtime_data: &ModuleData,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> Result<FunctionValue, &'static str> {
+        let type_index = runtime_data
+            .function_type_index(function_index)
+            .ok_or("Function index out of bounds")?;
+        // Guarantee: the type index comes from the module.
+        let func_type = runtime_data.function_type(function_index).unwrap();
+        if func_type.returns().len() > 1 {
+            return Err("Exported functions can't return more than one value");
+        }
 
-        // START
-        // Call start -> call start
+        let trampoline_name = format!("{}{}", TRAMPOLINE_NAME_PREFIX, type_index);
+        // Guarantee: a trampoline is generated for every type.
+        let trampoline = module.get_function(&trampoline_name).unwrap();
 
-        builder.build_return(Some(reusables.i32_type.const_int(0, false).into()));
+        let pointer_type = reusables.i8_type.ptr_type(&AddressSpace::Generic);
+        let cells_type = reusables.i64_type.ptr_type(&AddressSpace::Generic);
+        let wasmo_instance_call = Self::get_or_add_external_function(
+            INSTANCE_CALL_NAME,
+            function_type(
+                &[
+                    pointer_type.into(),
+                    reusables.i32_type.into(),
+                    pointer_type.into(),
+                    cells_type.into(),
+                    cells_type.into(),
+                ],
+                reusables.i32_type.into(),
+                false,
+            ),
+            module,
+        );
 
-        Ok(())
+        let mut param_types = vec![pointer_type.into()];
+        for ty in func_type.params() {
+            param_types.push(match ty {
+                ValueType::I32 => reusables.i32_type.into(),
+                ValueType::I64 => reusables.i64_type.into(),
+                ValueType::F32 => reusables.f32_type.into(),
+                ValueType::F64 => reusables.f64_type.into(),
+            });
+        }
+        for ty in func_type.returns() {
+            let result_type = match ty {
+                ValueType::I32 => reusables.i32_type.ptr_type(&AddressSpace::Generic),
+                ValueType::I64 => reusables.i64_type.ptr_type(&AddressSpace::Generic),
+                ValueType::F32 => reusables.f32_type.ptr_type(&AddressSpace::Generic),
+                ValueType::F64 => reusables.f64_type.ptr_type(&AddressSpace::Generic),
+            };
+            param_types.push(result_type.into());
+        }
+
+        let function_type = function_type(&param_types, reusables.i32_type.into(), false);
+        let function = module.add_function(name, function_type, None);
+        let basic_block = function.append_basic_block("entry", context);
+        builder.position_at_end(&basic_block);
+
+        let arguments = Self::generate_cells_code(func_type.params().len(), builder, reusables);
+        let results = Self::generate_cells_code(func_type.returns().len(), builder, reusables);
+
+        for (index, ty) in func_type.params().iter().enumerate() {
+            // Guarantee: the instance comes first, then the parameters.
+            let value = function.get_nth_param(index as u32 + 1).unwrap();
+            let bits = Self::generate_bits_from_value_code(value, ty, builder, reusables);
+            let index = reusables.i32_type.const_int(index as u64, false);
+            let cell = builder.build_gep(arguments, &[index], "");
+            builder.build_store(bits.into(), cell);
+        }
+
+        // Guarantee: the function takes the instance first.
+        let instance = function.get_first_param().unwrap();
+        let trampoline =
+            builder.build_pointer_cast(trampoline.as_pointer_value(), pointer_type, "");
+        let args = [
+            instance,
+            reusables
+                .i32_type
+                .const_int(u64::from(function_index), false)
+                .into(),
+            trampoline.into(),
+            arguments.into(),
+            results.into(),
+        ];
+
+        // Guarantee: `wasmo_instance_call` returns a status.
+        let status = builder
+            .build_call(&wasmo_instance_call, &args, "status")
+            .unwrap();
+
+        // Results are stored even when the call traps, they are just unspecified then.
+        if let Some(ty) = func_type.returns().first() {
+            let index = reusables.i32_type.const_int(0, false);
+            let cell = builder.build_gep(results, &[index], "");
+            let bits: IntValue = builder.build_load(cell, "result").into();
+            let value = Self::generate_value_from_bits_code(bits, ty, builder, reusables);
+            // Guarantee: the pointer to the result comes last.
+            let result: PointerValue = function.get_last_param().unwrap().into();
+            builder.build_store(value, result);
+        }
+
+        builder.build_return(Some(status));
+
+        Ok(function)
+    }
+
+    /// Describes the local memories, tables, globals and functions of the module to the runtime
+    /// support library. Returns the `*mut ModuleData` it creates.
+    fn generate_module_data_code(
+        module: &Module,
+        runtime_data: &ModuleData,
+        declarations: &Declarations,
+        builder: &Builder,
+        context: &Context,
+        reusables: &Reusables,
+    ) -> Result<BasicValue, &'static str> {
+        if !runtime_data.imports.is_empty() {
+            return Err("Modules with imports can't be compiled to executables or libraries");
+        }
+
+        // `*mut ModuleData` is opaque to generated code.
+        let pointer_type = reusables.i8_type.ptr_type(&AddressSpace::Generic);
+        let void_type = context.void_type();
+        let limits_params: [BasicType; 3] = [
+            pointer_type.into(),
+            reusables.i32_type.into(),
+            reusables.i64_type.into(),
+        ];
+
+        let module_data_new = Self::get_or_add_external_function(
+            MODULE_DATA_NEW_NAME,
+            function_type(&[], pointer_type.into(), false),
+            module,
+        );
+        let add_memory = Self::get_or_add_external_function(
+            MODULE_DATA_ADD_MEMORY_NAME,
+            function_type(&limits_params, void_type.into(), false),
+            module,
+        );
+        let add_table = Self::get_or_add_external_function(
+            MODULE_DATA_ADD_TABLE_NAME,
+            function_type(&limits_params, void_type.into(), false),
+            module,
+        );
+        let add_global = Self::get_or_add_external_function(
+            MODULE_DATA_ADD_GLOBAL_NAME,
+            function_type(
+                &[
+                    pointer_type.into(),
+                    reusables.i32_type.into(),
+                    reusables.i32_type.into(),
+                    reusables.i64_type.into(),
+                ],
+                void_type.into(),
+                false,
+            ),
+            module,
+        );
+        let add_function = Self::get_or_add_external_function(
+            MODULE_DATA_ADD_FUNCTION_NAME,
+            function_type(
+                &[
+                    pointer_type.into(),
+                    reusables.i32_type.into(),
+                    pointer_type.into(),
+                    reusables.i32_type.into(),
+                ],
+                void_type.into(),
+                false,
+            ),
+            module,
+        );
+        let set_code_end = Self::get_or_add_external_function(
+            MODULE_DATA_SET_CODE_END_NAME,
+            function_type(
+                &[pointer_type.into(), pointer_type.into()],
+                void_type.into(),
+                false,
+            ),
+            module,
+        );
+
+        // Guarantee: `wasmo_module_data_new` returns a pointer.
+        let data = builder.build_call(&module_data_new, &[], "data").unwrap();
+
+        let limits = |limits: &ResizableLimits| -> [BasicValue; 2] {
+            let maximum = limits.maximum.map_or(NO_MAXIMUM, i64::from);
+            [
+                reusables
+                    .i32_type
+                    .const_int(u64::from(limits.minimum), false)
+                    .into(),
+                reusables.i64_type.const_int(maximum as u64, true).into(),
+            ]
+        };
+
+        for memory in runtime_data.locals.memories.iter() {
+            let [minimum, maximum] = limits(memory.limits());
+            builder.build_call(&add_memory, &[data, minimum, maximum], "");
+        }
+
+        for table in runtime_data.locals.tables.iter() {
+            let [minimum, maximum] = limits(table.limits());
+            builder.build_call(&add_table, &[data, minimum, maximum], "");
+        }
+
+        for global in runtime_data.locals.globals.iter() {
+            // Without imports, init expressions can only be constants.
+            let bits = match global.init() {
+                Some(InitExpression::Const(bits)) => *bits,
+                _ => return Err("Globals must be initialized with constants"),
+            };
+
+            // The type is passed as its `ValueType` discriminant.
+            let args = [
+                data,
+                reusables
+                    .i32_type
+                    .const_int(global.ty() as u64, false)
+                    .into(),
+                reusables
+                    .i32_type
+                    .const_int(u64::from(global.is_mutable()), false)
+                    .into(),
+                reusables.i64_type.const_int(bits, false).into(),
+            ];
+            builder.build_call(&add_global, &args, "");
+        }
+
+        for (function_value, function_data) in declarations
+            .functions
+            .iter()
+            .zip(runtime_data.locals.functions.iter())
+        {
+            let pointer =
+                builder.build_pointer_cast(function_value.as_pointer_value(), pointer_type, "");
+            let args = [
+                data,
+                reusables
+                    .i32_type
+                    .const_int(u64::from(function_data.type_index), false)
+                    .into(),
+                pointer.into(),
+                reusables
+                    .i32_type
+                    .const_int(u64::from(function_data.body_offset), false)
+                    .into(),
+            ];
+            builder.build_call(&add_function, &args, "");
+        }
+
+        // Guarantee: the code end function is generated with the wasm functions.
+        let code_end = module.get_function(CODE_END_NAME).unwrap();
+        let code_end = builder.build_pointer_cast(code_end.as_pointer_value(), pointer_type, "");
+        builder.build_call(&set_code_end, &[data, code_end.into()], "");
+
+        Ok(data)
+    }
+
+    /// Allocates `count` 64-bit cells on the stack and returns a pointer to the first one.
+    fn generate_cells_code(count: usize, builder: &Builder, reusables: &Reusables) -> PointerValue {
+        let cells_type = reusables.i64_type.array_type(count as u32);
+        let cells = builder.build_alloca(cells_type.into(), "cells");
+
+        builder.build_pointer_cast(
+            cells,
+            reusables.i64_type.ptr_type(&AddressSpace::Generic),
+            "",
+        )
+    }
+
+    /// Gets a function the runtime support library defines, declaring it the first time.
+    fn get_or_add_external_function(
+        name: &str,
+        function_type: FunctionType,
+        module: &Module,
+    ) -> FunctionValue {
+        match module.get_function(name) {
+            Some(function) => function,
+            None => module.add_function(name, function_type, Some(Linkage::External)),
+        }
+    }
+}
+
+/// Gets the exclusive bounds a float must lie within to be truncated to an integer of `bit_width`
+/// bits. Every bound is exactly representable in the float type.
+fn truncation_bounds(bit_width: u32, from_f64: bool, signed: bool) -> (f64, f64) {
+    match (bit_width, from_f64, signed) {
+        (32, false, true) => (-2_147_483_904.0, 2_147_483_648.0),
+        (32, true, true) => (-2_147_483_649.0, 2_147_483_648.0),
+        (32, _, false) => (-1.0, 4_294_967_296.0),
+        (64, false, true) => (-9_223_373_136_366_403_584.0, 9_223_372_036_854_775_808.0),
+        (64, true, true) => (-9_223_372_036_854_777_856.0, 9_223_372_036_854_775_808.0),
+        (64, _, false) => (-1.0, 18_446_744_073_709_551_616.0),
+        _ => unreachable!("Wasm only truncates to i32 and i64"),
     }
 }
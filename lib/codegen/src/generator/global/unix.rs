@@ -1,4 +1,27 @@
+use crate::generator::module::Reusables;
+use wasmo_llvm::types::PointerType;
+use wasmo_llvm::values::PointerValue;
+use wasmo_llvm::Builder;
+use wasmo_runtime::context::GLOBALS_FIELD;
+
 pub struct GlobalGenerator();
 
 impl GlobalGenerator {
+    /// Gets a pointer to the cell of a global from the `InstanceContext` globals array.
+    /// `pointer_type` is the type the cell is accessed as.
+    pub fn generate_global_pointer_code(
+        global_index: u32,
+        pointer_type: PointerType,
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        let globals_pointer = builder.build_struct_gep(instance_context, GLOBALS_FIELD, "");
+        let globals: PointerValue = builder.build_load(globals_pointer, "globals").into();
+        let index = reusables.i32_type.const_int(u64::from(global_index), false);
+        let global_pointer = builder.build_gep(globals, &[index], "");
+        let global: PointerValue = builder.build_load(global_pointer, "global").into();
+
+        builder.build_pointer_cast(global, pointer_type, "")
+    }
 }
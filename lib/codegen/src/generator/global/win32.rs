@@ -0,0 +1,4 @@
+pub struct GlobalGenerator();
+
+impl GlobalGenerator {
+}
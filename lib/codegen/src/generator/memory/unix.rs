@@ -1,47 +1,265 @@
-use wasmo_llvm::{Builder, Context};
-use wasmparser::ResizableLimits;
+#![allow(clippy::too_many_arguments)]
+
+use crate::generator::module::Reusables;
+use wasmo_llvm::types::{function_type, BasicType, FunctionType};
+use wasmo_llvm::values::{IntValue, PointerValue};
+use wasmo_llvm::{AddressSpace, Builder, Context, Linkage, Module};
+use wasmo_runtime::context::{INTRINSIC_FUNCTIONS_FIELD, MEMORIES_FIELD};
+use wasmo_runtime::intrinsics::{MEMORY_GROW_INDEX, MEMORY_SIZE_INDEX};
 
 pub struct MemoryGenerator();
 
 impl MemoryGenerator {
-    /// Generates IR for calling mmap and co needed for creating
-    /// memory.
-    pub fn generate_memory_setup_code(
-        limits: &ResizableLimits,
+    // DATA
+    /// Copies `bytes` into a memory, starting at byte `offset`. The bytes are stored in a private
+    /// constant named `name`. The range must already be bounds checked.
+    pub fn generate_memory_initialization_code(
+        memory_index: u32,
+        offset: IntValue,
+        bytes: &[u8],
+        name: &str,
+        instance_context: PointerValue,
+        module: &Module,
+        builder: &Builder,
         context: &Context,
+        reusables: &Reusables,
+    ) {
+        let data = context.const_bytes(bytes);
+        let global = module.add_global(data.get_type(), &AddressSpace::Generic, name);
+        global.set_initializer(&data.into());
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+
+        let base = MemoryGenerator::generate_memory_base_code(
+            memory_index,
+            instance_context,
+            builder,
+            reusables,
+        );
+        let destination = builder.build_gep(base, &[offset], "destination");
+        let source_type = reusables.i8_type.ptr_type(&AddressSpace::Generic);
+        let source = builder.build_pointer_cast(global.as_pointer_value(), source_type, "source");
+        let length = reusables.usize_type.const_int(bytes.len() as u64, false);
+
+        let memcpy_name = format!(
+            "llvm.memcpy.p1i8.p0i8.i{}",
+            reusables.usize_type.get_bit_width()
+        );
+        let memcpy = module.get_or_declare_intrinsic(
+            &memcpy_name,
+            function_type(
+                &[
+                    reusables.i8_type.ptr_type(&AddressSpace::Global).into(),
+                    source_type.into(),
+                    reusables.usize_type.into(),
+                    context.bool_type().into(),
+                ],
+                context.void_type().into(),
+                false,
+            ),
+        );
+
+        let is_volatile = context.bool_type().zero(false);
+        builder.build_call(
+            &memcpy,
+            &[
+                destination.into(),
+                source.into(),
+                length.into(),
+                is_volatile.into(),
+            ],
+            "",
+        );
+    }
+
+    /// Loads the base pointer of a memory from the `InstanceContext` memories array.
+    pub fn generate_memory_base_code(
+        memory_index: u32,
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        let pointer = MemoryGenerator::generate_memory_field_pointer(
+            memory_index,
+            0,
+            instance_context,
+            builder,
+            reusables,
+        );
+
+        builder.build_load(pointer, "memory.base").into()
+    }
+
+    /// Loads the current length in bytes of a memory from the `InstanceContext` memories array.
+    pub fn generate_memory_length_code(
+        memory_index: u32,
+        instance_context: PointerValue,
         builder: &Builder,
-    ) -> () {
-        // TODO
+        reusables: &Reusables,
+    ) -> IntValue {
+        let pointer = MemoryGenerator::generate_memory_field_pointer(
+            memory_index,
+            1,
+            instance_context,
+            builder,
+            reusables,
+        );
+
+        builder.build_load(pointer, "memory.length").into()
     }
 
-    // DATA
-    pub fn generate_memory_initialization_code(values: &[u8]) -> () {}
+    /// Adds the static offset of a load or store to its dynamic address.
+    ///
+    /// Both are zero-extended first so the sum, which can take up to 33 bits, doesn't wrap.
+    pub fn generate_effective_address_code(
+        address: IntValue,
+        offset: u32,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> IntValue {
+        let address = builder.build_int_z_extend(address, reusables.usize_type, "address");
 
-    // MEMORY.GROW
-    pub fn generate_memory_size_code() -> () {}
+        if offset == 0 {
+            return address;
+        }
+
+        let offset = reusables.usize_type.const_int(u64::from(offset), false);
+
+        builder.build_int_add(address, offset, "effective_address")
+    }
+
+    /// Gets a pointer to a field of the `MemoryPtr` struct at `memory_index`.
+    fn generate_memory_field_pointer(
+        memory_index: u32,
+        field_index: u32,
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        let memories_pointer = builder.build_struct_gep(instance_context, MEMORIES_FIELD, "");
+        let memories: PointerValue = builder.build_load(memories_pointer, "memories").into();
+        let index = reusables.i32_type.const_int(u64::from(memory_index), false);
+        let memory_pointer = builder.build_gep(memories, &[index], "");
+        let memory: PointerValue = builder.build_load(memory_pointer, "memory").into();
+
+        builder.build_struct_gep(memory, field_index, "")
+    }
 
     // MEMORY.SIZE
-    pub fn generate_memory_grow_code() -> () {}
+    /// Calls the memory size intrinsic. Returns the size of the memory in pages.
+    pub fn generate_memory_size_code(
+        memory_index: u32,
+        instance_context: PointerValue,
+        instance_context_type: BasicType,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> IntValue {
+        let function_pointer = MemoryGenerator::generate_intrinsic_pointer(
+            MEMORY_SIZE_INDEX,
+            MemoryGenerator::memory_size_function_type(instance_context_type, reusables),
+            instance_context,
+            builder,
+            reusables,
+        );
+        let memory_index = reusables.i32_type.const_int(u64::from(memory_index), false);
 
-    /// This is only generated for AOT mode, for JIT mode an in-process function
-    /// is shared and used accross instances.
-    pub fn generate_memory_grow_function() -> () {}
+        // Guarantee: the intrinsic returns a value.
+        builder
+            .build_indirect_call(
+                function_pointer,
+                &[instance_context.into(), memory_index.into()],
+                "memory.size",
+            )
+            .unwrap()
+            .into()
+    }
 
-    /// This is only generated for AOT mode, for JIT mode an in-process function
-    /// is shared and used accross instances.
-    pub fn generate_memory_size_function() -> () {}
-}
+    // MEMORY.GROW
+    /// Calls the memory grow intrinsic. Returns the previous size of the memory in pages, or -1
+    /// if it can't grow by `delta` pages without exceeding its maximum.
+    ///
+    /// The intrinsic updates the length generated code reads for bounds checks.
+    pub fn generate_memory_grow_code(
+        delta: IntValue,
+        memory_index: u32,
+        instance_context: PointerValue,
+        instance_context_type: BasicType,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> IntValue {
+        let function_pointer = MemoryGenerator::generate_intrinsic_pointer(
+            MEMORY_GROW_INDEX,
+            MemoryGenerator::memory_grow_function_type(instance_context_type, reusables),
+            instance_context,
+            builder,
+            reusables,
+        );
+        let memory_index = reusables.i32_type.const_int(u64::from(memory_index), false);
 
-pub struct TableGenerator();
+        // Guarantee: the intrinsic returns a value.
+        builder
+            .build_indirect_call(
+                function_pointer,
+                &[instance_context.into(), delta.into(), memory_index.into()],
+                "memory.grow",
+            )
+            .unwrap()
+            .into()
+    }
 
-impl TableGenerator {
-    // TABLE ENTRY
-    pub fn generate_table_setup_code() -> () {}
+    /// `fn (*mut InstanceContext, delta: u32, memory_index: u32) -> i32`
+    fn memory_grow_function_type(
+        instance_context_type: BasicType,
+        reusables: &Reusables,
+    ) -> FunctionType {
+        function_type(
+            &[
+                instance_context_type,
+                reusables.i32_type.into(),
+                reusables.i32_type.into(),
+            ],
+            reusables.i32_type.into(),
+            false,
+        )
+    }
 
-    // ELEM
-    pub fn generate_table_initialization_code() -> () {}
-}
+    /// `fn (*mut InstanceContext, memory_index: u32) -> i32`
+    fn memory_size_function_type(
+        instance_context_type: BasicType,
+        reusables: &Reusables,
+    ) -> FunctionType {
+        function_type(
+            &[instance_context_type, reusables.i32_type.into()],
+            reusables.i32_type.into(),
+            false,
+        )
+    }
 
-pub struct GlobalGenerator();
+    /// Loads an intrinsic from the `InstanceContext` intrinsic functions array and casts it to a
+    /// pointer to `function_type`.
+    pub(crate) fn generate_intrinsic_pointer(
+        intrinsic_index: u32,
+        function_type: FunctionType,
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        let intrinsics_pointer =
+            builder.build_struct_gep(instance_context, INTRINSIC_FUNCTIONS_FIELD, "");
+        let intrinsics: PointerValue = builder
+            .build_load(intrinsics_pointer, "intrinsic_functions")
+            .into();
+        let index = reusables
+            .i32_type
+            .const_int(u64::from(intrinsic_index), false);
+        let intrinsic_pointer = builder.build_gep(intrinsics, &[index], "");
+        let intrinsic: PointerValue = builder.build_load(intrinsic_pointer, "intrinsic").into();
 
-impl GlobalGenerator {}
+        // Functions live in the generic address space.
+        builder.build_pointer_cast(
+            intrinsic,
+            function_type.ptr_type(&AddressSpace::Generic),
+            "",
+        )
+    }
+}
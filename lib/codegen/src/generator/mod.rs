@@ -1,8 +1,15 @@
+mod control;
+mod exception;
 mod function;
+mod global;
 mod module;
 mod memory;
+mod table;
 
 
 pub use function::FunctionGenerator;
 pub use module::ModuleGenerator;
 pub use memory::MemoryGenerator;
+pub use exception::ExceptionGenerator;
+pub use global::GlobalGenerator;
+pub use table::TableGenerator;
@@ -5,19 +5,29 @@ use crate::{
     convert::LLVM,
     error::{Offset, ParserError, ParserResult},
     generator::FunctionGenerator,
+    library,
     options::CodegenOptions,
+    validate::validate,
 };
 use std::ptr::null;
-use wasmo_llvm::target::{Target, TargetData};
-use wasmo_llvm::types::{
-    function_type, BasicType, FloatType, FunctionType, IntType, PointerType, StructType,
+use wasmo_llvm::errors::TargetInit;
+use wasmo_llvm::target::{Target, TargetData, TargetMachine};
+use wasmo_llvm::types::{BasicType, FloatType, FunctionType, IntType, PointerType};
+use wasmo_llvm::values::FunctionValue;
+use wasmo_llvm::{
+    AddressSpace, Builder, Context, FunctionPassManager, InitializationConfig, Linkage, Module,
 };
-use wasmo_llvm::values::{BasicValue, FloatValue, FunctionValue, InstructionValue, IntValue};
-use wasmo_llvm::{AddressSpace, BasicBlock, Builder, Context, InitializationConfig, Module};
-use wasmo_llvm::{CodeModel, OptimizationLevel, RelocationModel};
-use wasmo_runtime::data::{FuncData, ModuleData};
+use wasmo_llvm::{CodeModel, CompilerError, CompilerResult, OptimizationLevel, RelocationModel};
+use wasmo_runtime::context::{
+    CODE_END_NAME, FUNCTION_NAME_PREFIX, INITIALIZE_FUNCTION_NAME, TRAMPOLINE_NAME_PREFIX,
+};
+use wasmo_runtime::data::{
+    DataSegmentData, ElementData, FuncData, GlobalData, InitExpression, MemoryData, ModuleData,
+    TableData,
+};
+use wasmo_runtime::types::{ExportKind, ValueType};
 use wasmo_utils::{debug, verbose};
-use wasmparser::{FuncType, Operator, Parser, ParserState, WasmDecoder};
+use wasmparser::{GlobalType, ImportSectionEntryType, Parser, ParserState, WasmDecoder};
 
 ///
 pub struct Reusables {
@@ -26,78 +36,156 @@ pub struct Reusables {
     pub(crate) i64_type: IntType,
     pub(crate) f32_type: FloatType,
     pub(crate) f64_type: FloatType,
+    pub(crate) usize_type: IntType,
 }
 
 impl Reusables {
     ///
-    fn new(context: &Context) -> Self {
+    fn new(context: &Context, target_data: &TargetData) -> Self {
         Self {
             i8_type: context.i8_type(),
             i32_type: context.i32_type(),
             i64_type: context.i64_type(),
             f32_type: context.f32_type(),
             f64_type: context.f64_type(),
+            usize_type: context.machine_int_type(target_data, None),
+        }
+    }
+}
+
+/// Module-level entities function bodies refer to.
+pub struct Declarations {
+    /// LLVM types of the type section entries.
+    pub(crate) function_types: Vec<FunctionType>,
+    /// Canonical index of each type section entry, compared by `call_indirect`.
+    pub(crate) signature_ids: Vec<u32>,
+    /// Type indices of imported functions, which come first in the function index space.
+    pub(crate) function_imports: Vec<u32>,
+    /// Local functions, declared before their bodies are generated.
+    pub(crate) functions: Vec<FunctionValue>,
+    /// Globals in the global index space.
+    pub(crate) globals: Vec<GlobalDeclaration>,
+}
+
+/// A global as seen by function bodies.
+pub struct GlobalDeclaration {
+    pub(crate) ty: ValueType,
+    pub(crate) mutable: bool,
+    /// Bits of the value of an immutable global with a known initializer. Such globals are
+    /// folded to constants instead of being loaded from the `InstanceContext`.
+    pub(crate) constant: Option<u64>,
+}
+
+impl Declarations {
+    /// Creates empty declarations, filled in as the sections are parsed.
+    fn new() -> Self {
+        Self {
+            function_types: Vec::new(),
+            signature_ids: Vec::new(),
+            function_imports: Vec::new(),
+            functions: Vec::new(),
+            globals: Vec::new(),
+        }
+    }
+}
+
+impl GlobalDeclaration {
+    /// `constant` is only known for immutable globals initialized with a constant.
+    fn new(ty: ValueType, mutable: bool, constant: Option<u64>) -> Self {
+        Self {
+            ty,
+            mutable,
+            constant,
         }
     }
 }
 
 ///
 pub struct ModuleGenerator<'a> {
+    wasm_binary: &'a [u8],
     parser: Parser<'a>,
     instance_context_type: PointerType,
     context: Context,
     builder: Builder,
-    function_types: Vec<FunctionType>,
+    declarations: Declarations,
     function_index: u32,
+    global_type: Option<GlobalType>,
+    element_table_index: Option<u32>,
+    data_memory_index: Option<u32>,
+    data_bytes: Vec<u8>,
+    init_expression: Option<InitExpression>,
     options: CodegenOptions,
     reusables: Reusables,
+    target_machine: TargetMachine,
 }
 
 impl<'a> ModuleGenerator<'a> {
-    ///
-    pub fn new(wasm_binary: &'a [u8], options: &CodegenOptions) -> Self {
+    /// Fails if LLVM doesn't support the target of `options`.
+    pub fn new(wasm_binary: &'a [u8], options: &CodegenOptions) -> CompilerResult<Self> {
         let context = Context::create();
-        let llvm_target_data = ModuleGenerator::create_target_data();
+        let target_machine = ModuleGenerator::create_target_machine(options)?;
+        let llvm_target_data = target_machine.get_target_data();
         let builder = context.create_builder();
-        let reusables = Reusables::new(&context);
+        let reusables = Reusables::new(&context, &llvm_target_data);
         let instance_context_type =
-            ModuleGenerator::create_instance_context_type(&context, &reusables, &llvm_target_data, );
+            ModuleGenerator::create_instance_context_type(&context, &reusables, &llvm_target_data);
 
-        Self {
+        Ok(Self {
             context,
+            wasm_binary,
             parser: Parser::new(&wasm_binary),
             instance_context_type,
             builder,
-            function_types: Vec::new(),
+            declarations: Declarations::new(),
             function_index: 0,
-            options: *options,
+            global_type: None,
+            element_table_index: None,
+            data_memory_index: None,
+            data_bytes: Vec::new(),
+            init_expression: None,
+            options: options.clone(),
             reusables,
-        }
+            target_machine,
+        })
     }
 
-    ///
-    fn create_target_data() -> TargetData {
-        Target::initialize_native(&InitializationConfig::default())
-            .expect("Unsuccessful initilalization of native target");
+    /// Gets the target machine generated modules are laid out for. It emits them as objects.
+    pub fn target_machine(&self) -> &TargetMachine {
+        &self.target_machine
+    }
 
-        let target_triple = Target::get_default_triple().to_string();
+    /// Creates a target machine for the target of `options`, the host by default. The
+    /// InstanceContext type is laid out with its target data. Code is position independent, so
+    /// objects it emits can be linked into any executable or library.
+    fn create_target_machine(options: &CodegenOptions) -> CompilerResult<TargetMachine> {
+        let target_triple = match &options.target_triple {
+            Some(target_triple) => {
+                // Cross-compiling can target any architecture LLVM is built with.
+                Target::initialize_all(&InitializationConfig::default());
+                target_triple.clone()
+            }
+            None => {
+                Target::initialize_native(&InitializationConfig::default())?;
+                Target::get_default_triple().to_string()
+            }
+        };
 
         let normalized_target_triple = Target::normalize_target_triple(&target_triple).to_string();
 
-        let target = Target::from_triple(&normalized_target_triple)
-            .expect("Unsuccessful creation of target from triple");
+        let target = Target::from_triple(&normalized_target_triple)?;
 
-        let target_machine = target
+        target
             .create_target_machine(
-                &target_triple,
-                "",
-                "",
+                &normalized_target_triple,
+                &options.cpu,
+                &options.features,
                 OptimizationLevel::None,
-                RelocationModel::Default,
+                RelocationModel::PIC,
                 CodeModel::Default,
             )
-            .expect("Unable to create target machine from target");
-        target_machine.get_target_data()
+            .ok_or(CompilerError::TargetInit(
+                TargetInit::CantCreateTargetMachine,
+            ))
     }
 
     /// This function creates an LLVM IR representing `*mut InstanceContext`.
@@ -118,28 +206,50 @@ impl<'a> ModuleGenerator<'a> {
     ///     globals_offset: usize,
     ///     functions_offset: usize,
     ///     intrinsic_function_offset: usize,
-    ///     memories: dyn [*mut u8; memory_count],
-    ///     tables: dyn [*mut u32; table_count],
+    ///     function_contexts_offset: usize,
+    ///     memories: dyn [*mut Memory; memory_count],
+    ///     tables: dyn [TablePtr; table_count],
     ///     globals: dyn [*mut u64; global_count],
     ///     functions: dyn [*const (); function_count],
     ///     intrinsic_functions: dyn [*const (); intrinsic_function_count],
+    ///     function_contexts: dyn [*mut usize; function_count],
     /// }
     /// ```
-    fn create_instance_context_type(context: &Context, reusables: &Reusables, target_data: &TargetData) -> PointerType {
+    fn create_instance_context_type(
+        context: &Context,
+        reusables: &Reusables,
+        target_data: &TargetData,
+    ) -> PointerType {
         let address_space = &AddressSpace::Global;
+        let func_ref_ty = context.struct_type_with_name(
+            "FuncRef",
+            &[
+                reusables.i8_type.ptr_type(address_space).into(), // *const ()
+                reusables.i32_type.into(),                        // u32
+                reusables.i8_type.ptr_type(address_space).into(), // *mut usize
+            ],
+            false,
+        );
         let bound_ptr_ty = context.struct_type_with_name(
             "BoundPtr",
             &[
-                context.i32_type().ptr_type(address_space).into(), // *mut u32
+                func_ref_ty.ptr_type(address_space).into(), // *mut FuncRef
                 context.machine_int_type(target_data, None).into(), // usize
             ],
             false,
         );
-        let memories_ty: BasicType = reusables
-            .i8_type
+        let memory_ptr_ty = context.struct_type_with_name(
+            "MemoryPtr",
+            &[
+                reusables.i8_type.ptr_type(address_space).into(), // *mut u8
+                reusables.usize_type.into(),                      // usize
+            ],
+            false,
+        );
+        let memories_ty: BasicType = memory_ptr_ty
             .ptr_type(address_space)
             .ptr_type(address_space)
-            .into(); // *mut *mut u8
+            .into(); // *mut *mut struct MemoryPtr
         let tables_ty: BasicType = bound_ptr_ty.ptr_type(address_space).into(); // *mut struct BoundPtr
         let globals_ty: BasicType = reusables
             .i64_type
@@ -151,22 +261,97 @@ impl<'a> ModuleGenerator<'a> {
             .ptr_type(address_space)
             .ptr_type(address_space)
             .into(); // *mut *const i8 // LLVM doesn't like void pointers
+        let intrinsic_functions_ty = functions_ty; // *mut *const i8
+        let function_contexts_ty = functions_ty; // *mut *mut i8
 
         context
             .struct_type_with_name(
                 "InstanceContext",
-                &[memories_ty, tables_ty, globals_ty, functions_ty],
+                &[
+                    memories_ty,
+                    tables_ty,
+                    globals_ty,
+                    functions_ty,
+                    intrinsic_functions_ty,
+                    function_contexts_ty,
+                ],
                 false,
             )
             .ptr_type(address_space)
     }
 
-    ///
+    /// Generates the module of a static library: the module with the C API `library` describes,
+    /// named after `prefix`. Everything else gets internal linkage, so that several libraries can
+    /// be linked into a program.
+    pub fn generate_library_module(&mut self, prefix: &str) -> ParserResult<(Module, ModuleData)> {
+        let (mut module, runtime_data) = self.generate_module()?;
+        library::check_symbols(prefix, &runtime_data)?;
+
+        // Guarantee: `generate_module` generates the initialization function.
+        let initialize = module.get_function(INITIALIZE_FUNCTION_NAME).unwrap();
+        let mut function_codegen = FunctionGenerator::new(&self.options);
+        function_codegen.generate_instantiate_function(
+            &library::instantiate_symbol(prefix),
+            &initialize,
+            &mut module,
+            &runtime_data,
+            &self.declarations,
+            &self.builder,
+            &self.context,
+            &self.reusables,
+        )?;
+
+        for (field, export) in library::sorted_exports(&runtime_data) {
+            if let ExportKind::Function(function_index) = export {
+                let mut function_codegen = FunctionGenerator::new(&self.options);
+                function_codegen.generate_export_wrapper_function(
+                    &library::export_symbol(prefix, field),
+                    *function_index,
+                    &mut module,
+                    &runtime_data,
+                    &self.builder,
+                    &self.context,
+                    &self.reusables,
+                )?;
+            }
+        }
+
+        initialize.set_linkage(Linkage::Internal);
+        // Guarantee: `generate_module` generates the code end function.
+        module
+            .get_function(CODE_END_NAME)
+            .unwrap()
+            .set_linkage(Linkage::Internal);
+        for function in self.declarations.functions.iter() {
+            function.set_linkage(Linkage::Internal);
+        }
+        for type_index in 0..runtime_data.locals.types.len() {
+            let name = format!("{}{}", TRAMPOLINE_NAME_PREFIX, type_index);
+            // Guarantee: a trampoline is generated for every type.
+            module
+                .get_function(&name)
+                .unwrap()
+                .set_linkage(Linkage::Internal);
+        }
+
+        Ok((module, runtime_data))
+    }
+
+    /// Generates the module of a wasm binary and its runtime data. The binary is validated first,
+    /// so that no code is generated for an invalid module.
     pub fn generate_module(&mut self) -> ParserResult<(Module, ModuleData)> {
-        //
+        validate(self.wasm_binary)?;
+
         let mut module = self.context.create_module("wasm");
+        module.set_triple(&self.target_machine.get_triple().to_string());
+        module.set_data_layout(&self.target_machine.get_target_data());
         let mut runtime_data = ModuleData::new();
 
+        // Locals are generated as stack slots that mem2reg turns into SSA values.
+        let pass_manager = FunctionPassManager::create(&module);
+        pass_manager.add_promote_memory_to_register_pass();
+        pass_manager.initialize();
+
         loop {
             let state = self.parser.read();
 
@@ -174,15 +359,55 @@ impl<'a> ModuleGenerator<'a> {
                 // END
                 ParserState::EndWasm => {
                     verbose!("Parser ended!");
-                    // Generate `main` function.
-                    let mut function_codegen = FunctionGenerator::new();
-                    function_codegen.generate_main_function(
+                    // Mark where the code of the wasm functions ends, before generating others.
+                    let mut function_codegen = FunctionGenerator::new(&self.options);
+                    function_codegen.generate_code_end_function(
+                        &mut module,
+                        &self.builder,
+                        &self.context,
+                    );
+
+                    // Generate the instance initialization function.
+                    let mut function_codegen = FunctionGenerator::new(&self.options);
+                    let initialize = function_codegen.generate_initialization_function(
                         &mut module,
+                        &runtime_data,
+                        &self.declarations,
+                        &self.instance_context_type,
                         &self.builder,
                         &self.context,
                         &self.reusables,
                     )?;
 
+                    // Generate the trampolines the runtime calls functions through.
+                    for type_index in 0..self.declarations.function_types.len() as u32 {
+                        let mut function_codegen = FunctionGenerator::new(&self.options);
+                        function_codegen.generate_trampoline_function(
+                            type_index,
+                            &mut module,
+                            &runtime_data,
+                            &self.declarations,
+                            &self.instance_context_type,
+                            &self.builder,
+                            &self.context,
+                            &self.reusables,
+                        )?;
+                    }
+
+                    // Generate `main` function.
+                    if self.options.generate_main {
+                        let mut function_codegen = FunctionGenerator::new(&self.options);
+                        function_codegen.generate_main_function(
+                            &initialize,
+                            &mut module,
+                            &runtime_data,
+                            &self.declarations,
+                            &self.builder,
+                            &self.context,
+                            &self.reusables,
+                        )?;
+                    }
+
                     break;
                 }
                 // ERRORS
@@ -196,15 +421,41 @@ impl<'a> ModuleGenerator<'a> {
                 ParserState::TypeSectionEntry(ty) => {
                     verbose!("type entry => {:?}", ty);
                     runtime_data.add_type(Runtime::func_type(ty)?);
-                    self.function_types.push(LLVM::func_type(
+                    let type_index = self.declarations.function_types.len() as u32;
+                    self.declarations.function_types.push(LLVM::func_type(
                         &self.context,
                         &self.instance_context_type,
                         ty,
                     )?);
+                    self.declarations
+                        .signature_ids
+                        .push(runtime_data.canonical_type_index(type_index));
                 }
                 // IMPORT
                 ParserState::ImportSectionEntry { module, field, ty } => {
                     debug!("import entry type => {:?}, {:?}, {:?}", module, field, ty);
+                    match ty {
+                        ImportSectionEntryType::Function(type_index) => {
+                            self.declarations
+                                .function_types
+                                .get(*type_index as usize)
+                                .ok_or("Function type index out of bounds")?;
+                            self.declarations.function_imports.push(*type_index);
+                        }
+                        ImportSectionEntryType::Global(ty) => {
+                            self.declarations.globals.push(GlobalDeclaration::new(
+                                Runtime::value_type(&ty.content_type)?,
+                                ty.mutable,
+                                None,
+                            ));
+                        }
+                        _ => (),
+                    }
+                    runtime_data.add_import(
+                        module.to_string(),
+                        field.to_string(),
+                        Runtime::import(ty)?,
+                    );
                 }
                 // EXPORT
                 ParserState::ExportSectionEntry { field, kind, index } => {
@@ -214,17 +465,48 @@ impl<'a> ModuleGenerator<'a> {
                 // MEMORY
                 ParserState::MemorySectionEntry(ty) => {
                     debug!("memory entry type => {:?}", ty);
+                    runtime_data.add_memory(MemoryData::new(Runtime::resizable_limits(&ty.limits)));
                 }
                 // TABLE
                 ParserState::TableSectionEntry(ty) => {
                     debug!("table entry type => {:?}", ty);
+                    runtime_data.add_table(TableData::new(Runtime::resizable_limits(&ty.limits)));
                 }
                 // GLOBAL
                 ParserState::BeginGlobalSectionEntry(ty) => {
                     verbose!("global section started!");
+                    self.global_type = Some(*ty);
                 }
                 ParserState::EndGlobalSectionEntry => {
-                    verbose!("global section started!");
+                    verbose!("global section concluded!");
+                    // Guarantee: a global entry always has a type and an init expression.
+                    let global_type = self.global_type.take().unwrap();
+                    let init = self.init_expression.take().unwrap();
+                    let ty = Runtime::value_type(&global_type.content_type)?;
+
+                    // Only imported globals can be referenced by init expressions, and their values
+                    // are unknown until instantiation.
+                    let constant = if global_type.mutable {
+                        None
+                    } else {
+                        match init {
+                            InitExpression::Const(bits) => Some(bits),
+                            InitExpression::GetGlobal(index) => {
+                                self.declarations
+                                    .globals
+                                    .get(index as usize)
+                                    .ok_or("Global index out of bounds")?
+                                    .constant
+                            }
+                        }
+                    };
+
+                    runtime_data.add_global(GlobalData::new(ty, global_type.mutable, init));
+                    self.declarations.globals.push(GlobalDeclaration::new(
+                        ty,
+                        global_type.mutable,
+                        constant,
+                    ));
                 }
                 // INIT EXPRESSION
                 ParserState::BeginInitExpressionBody => {
@@ -235,61 +517,109 @@ impl<'a> ModuleGenerator<'a> {
                 }
                 ParserState::InitExpressionOperator(operator) => {
                     debug!("init expression operator => {:?}", operator);
+                    self.init_expression = Some(Runtime::init_expression(operator)?);
                 }
                 // ELEMENT
                 ParserState::BeginPassiveElementSectionEntry(ty) => {
                     verbose!("element section (passive) started! => {:?}", ty);
+                    self.element_table_index = None;
+                    self.init_expression = None;
                 }
                 ParserState::BeginActiveElementSectionEntry(table_index) => {
                     verbose!("element section (active) started! => {:?}", table_index);
+                    self.element_table_index = Some(*table_index);
                 }
                 ParserState::EndElementSectionEntry => {
                     verbose!("element section concluded!");
                 }
                 ParserState::ElementSectionEntryBody(func_indices) => {
                     debug!("element function indices => {:?}", func_indices);
+                    // Passive segments have no table until `table.init` names one.
+                    runtime_data.add_element(ElementData {
+                        table_index: self.element_table_index.take().unwrap_or(0),
+                        offset: self.init_expression.take(),
+                        function_indices: func_indices.to_vec(),
+                    });
                 }
                 // DATA
                 ParserState::BeginPassiveDataSectionEntry => {
                     verbose!("data section (passive) started!");
+                    self.data_memory_index = None;
+                    self.init_expression = None;
                 }
                 ParserState::BeginActiveDataSectionEntry(mem_index) => {
                     verbose!("data section (active) started! => {:?}", mem_index);
+                    self.data_memory_index = Some(*mem_index);
                 }
                 ParserState::EndDataSectionEntryBody => {
                     verbose!("data section concluded!");
+                    // Passive segments have no memory until `memory.init` names one.
+                    runtime_data.add_data_segment(DataSegmentData {
+                        memory_index: self.data_memory_index.take().unwrap_or(0),
+                        offset: self.init_expression.take(),
+                        bytes: std::mem::take(&mut self.data_bytes),
+                    });
                 }
                 ParserState::DataSectionEntryBodyChunk(bytes) => {
                     debug!("data bytes => {:?}", bytes);
+                    self.data_bytes.extend_from_slice(bytes);
                 }
                 // START
                 ParserState::StartSectionEntry(func_index) => {
                     debug!("start function index => {:?}", func_index);
+                    runtime_data.start_function = Some(*func_index);
                 }
                 // FUNCTION
                 ParserState::FunctionSectionEntry(type_index) => {
                     runtime_data.add_function(FuncData::new(null() as _, *type_index));
+
+                    // Declared upfront so calls can refer to functions defined later.
+                    let name = format!(
+                        "{}{}",
+                        FUNCTION_NAME_PREFIX,
+                        self.declarations.function_imports.len()
+                            + self.declarations.functions.len()
+                    );
+                    let function_type = *self
+                        .declarations
+                        .function_types
+                        .get(*type_index as usize)
+                        .ok_or("Function type index out of bounds")?;
+                    let function = module.add_function(&name, function_type, None);
+                    self.declarations.functions.push(function);
                 }
                 // FUNCTION BODY | CODE
-                ParserState::BeginFunctionBody { .. } => {
+                ParserState::BeginFunctionBody { range } => {
+                    // Faults in the function report the offset of its body.
+                    runtime_data
+                        .locals
+                        .functions
+                        .get_mut(self.function_index as usize)
+                        .ok_or("Function body without a function")?
+                        .body_offset = range.start as u32;
+
                     // Generate function.
-                    let mut function_codegen = FunctionGenerator::new();
-                    function_codegen.generate_function(
+                    let mut function_codegen = FunctionGenerator::new(&self.options);
+                    let function = function_codegen.generate_function(
                         &mut module,
                         &mut self.parser,
-                        &self.function_types,
+                        &self.declarations,
                         &self.builder,
                         &self.context,
                         &self.reusables,
                         self.function_index,
                     )?;
 
+                    pass_manager.run_on(&function);
+
                     self.function_index += 1;
                 }
                 _ => (),
             }
         }
 
+        pass_manager.finalize();
+
         if cfg!(feature = "verbose") {
             let delim = std::iter::repeat("+").take(70).collect::<String>();
             println!("●{}●\n{}\n●{}●", delim, module, delim);
@@ -298,3 +628,79 @@ impl<'a> ModuleGenerator<'a> {
         Ok((module, runtime_data))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::size_of;
+    use wasmo_runtime::context::{
+        FUNCTIONS_FIELD, FUNCTION_CONTEXTS_FIELD, GLOBALS_FIELD, HEADER_FIELD_COUNT,
+        INTRINSIC_FUNCTIONS_FIELD, MEMORIES_FIELD, TABLES_FIELD,
+    };
+    use wasmo_runtime::types::{FuncRef, MemoryPtr, TablePtr};
+
+    #[test]
+    fn instance_context_layout_matches_runtime() {
+        let generator = ModuleGenerator::new(&[], &CodegenOptions::default()).unwrap();
+        let target_data = generator.target_machine().get_target_data();
+        let module = generator.context.create_module("layout");
+        let struct_type = |name| module.get_struct_type(name).unwrap();
+        let word_size = size_of::<usize>() as u64;
+
+        // Header fields are pointers at the indices the runtime uses.
+        let instance_context = struct_type("InstanceContext");
+        assert_eq!(
+            target_data.get_abi_size(&instance_context.into()),
+            HEADER_FIELD_COUNT as u64 * word_size
+        );
+        for field in &[
+            MEMORIES_FIELD,
+            TABLES_FIELD,
+            GLOBALS_FIELD,
+            FUNCTIONS_FIELD,
+            INTRINSIC_FUNCTIONS_FIELD,
+            FUNCTION_CONTEXTS_FIELD,
+        ] {
+            assert_eq!(
+                target_data.offset_of_element(&instance_context, *field),
+                u64::from(*field) * word_size
+            );
+        }
+
+        // Tables array elements.
+        let bound_ptr = struct_type("BoundPtr");
+        assert_eq!(
+            target_data.get_abi_size(&bound_ptr.into()),
+            size_of::<TablePtr>() as u64
+        );
+        assert_eq!(target_data.offset_of_element(&bound_ptr, 1), word_size);
+
+        // Table elements.
+        let func_ref = struct_type("FuncRef");
+        let null = FuncRef::null();
+        let type_id_offset =
+            &null.type_id as *const u32 as usize - &null as *const FuncRef as usize;
+        let context_offset =
+            &null.context as *const *mut usize as usize - &null as *const FuncRef as usize;
+        assert_eq!(
+            target_data.get_abi_size(&func_ref.into()),
+            size_of::<FuncRef>() as u64
+        );
+        assert_eq!(
+            target_data.offset_of_element(&func_ref, 1),
+            type_id_offset as u64
+        );
+        assert_eq!(
+            target_data.offset_of_element(&func_ref, 2),
+            context_offset as u64
+        );
+
+        // Memories are read through a pointer to the start of `Memory`.
+        let memory_ptr = struct_type("MemoryPtr");
+        assert_eq!(
+            target_data.get_abi_size(&memory_ptr.into()),
+            size_of::<MemoryPtr>() as u64
+        );
+        assert_eq!(target_data.offset_of_element(&memory_ptr, 1), word_size);
+    }
+}
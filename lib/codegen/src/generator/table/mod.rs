@@ -0,0 +1,13 @@
+#[cfg(unix)]
+#[path = "unix.rs"]
+mod unix;
+
+#[cfg(windows)]
+#[path = "win32.rs"]
+mod win32;
+
+#[cfg(unix)]
+pub use unix::TableGenerator;
+
+#[cfg(windows)]
+pub use win32::TableGenerator;
@@ -1,7 +1,108 @@
-
+use crate::generator::module::Reusables;
+use wasmo_llvm::values::{IntValue, PointerValue};
+use wasmo_llvm::Builder;
+use wasmo_runtime::context::TABLES_FIELD;
 
 pub struct TableGenerator();
 
 impl TableGenerator {
-}
+    // ELEM
+    /// Stores function references into a table, starting at element `offset`. The range must
+    /// already be bounds checked.
+    pub fn generate_table_initialization_code(
+        table_index: u32,
+        offset: IntValue,
+        elements: &[(PointerValue, IntValue, PointerValue)],
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) {
+        let table = TableGenerator::generate_table_pointer(
+            table_index,
+            instance_context,
+            builder,
+            reusables,
+        );
+        let base_pointer = builder.build_struct_gep(table, 0, "");
+        let base: PointerValue = builder.build_load(base_pointer, "table.base").into();
+
+        for (position, (function_pointer, type_id, context)) in elements.iter().enumerate() {
+            let position = reusables.usize_type.const_int(position as u64, false);
+            let index = builder.build_int_add(offset, position, "");
+            let element = builder.build_gep(base, &[index], "element");
+
+            let function_pointer_field = builder.build_struct_gep(element, 0, "");
+            builder.build_store((*function_pointer).into(), function_pointer_field);
+            let type_id_field = builder.build_struct_gep(element, 1, "");
+            builder.build_store((*type_id).into(), type_id_field);
+            let context_field = builder.build_struct_gep(element, 2, "");
+            builder.build_store((*context).into(), context_field);
+        }
+    }
+
+    /// Loads the current number of elements of a table from the `InstanceContext` tables array.
+    pub fn generate_table_length_code(
+        table_index: u32,
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> IntValue {
+        let table = TableGenerator::generate_table_pointer(
+            table_index,
+            instance_context,
+            builder,
+            reusables,
+        );
+        let length_pointer = builder.build_struct_gep(table, 1, "");
+
+        builder.build_load(length_pointer, "table.length").into()
+    }
 
+    /// Loads the function pointer, type id and context of a table element. `element_index` must
+    /// already be bounds checked.
+    pub fn generate_table_element_code(
+        table_index: u32,
+        element_index: IntValue,
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> (PointerValue, IntValue, PointerValue) {
+        let table = TableGenerator::generate_table_pointer(
+            table_index,
+            instance_context,
+            builder,
+            reusables,
+        );
+        let base_pointer = builder.build_struct_gep(table, 0, "");
+        let base: PointerValue = builder.build_load(base_pointer, "table.base").into();
+        let element = builder.build_gep(base, &[element_index], "element");
+
+        let function_pointer = builder.build_struct_gep(element, 0, "");
+        let function_pointer: PointerValue =
+            builder.build_load(function_pointer, "element.func").into();
+        let type_id_pointer = builder.build_struct_gep(element, 1, "");
+        let type_id: IntValue = builder
+            .build_load(type_id_pointer, "element.type_id")
+            .into();
+        let context_pointer = builder.build_struct_gep(element, 2, "");
+        let context: PointerValue = builder
+            .build_load(context_pointer, "element.context")
+            .into();
+
+        (function_pointer, type_id, context)
+    }
+
+    /// Gets a pointer to the `BoundPtr` at `table_index` in the `InstanceContext` tables array.
+    fn generate_table_pointer(
+        table_index: u32,
+        instance_context: PointerValue,
+        builder: &Builder,
+        reusables: &Reusables,
+    ) -> PointerValue {
+        let tables_pointer = builder.build_struct_gep(instance_context, TABLES_FIELD, "");
+        let tables: PointerValue = builder.build_load(tables_pointer, "tables").into();
+        let index = reusables.i32_type.const_int(u64::from(table_index), false);
+
+        builder.build_gep(tables, &[index], "table")
+    }
+}
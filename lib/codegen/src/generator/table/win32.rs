@@ -0,0 +1,4 @@
+pub struct TableGenerator();
+
+impl TableGenerator {
+}
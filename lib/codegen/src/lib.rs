@@ -1,5 +1,8 @@
+pub mod compile;
 pub mod convert;
 pub mod generator;
 pub mod error;
+pub mod library;
 pub mod options;
+pub mod validate;
 pub mod intrinsic;
@@ -0,0 +1,305 @@
+//! The C API of modules compiled to static libraries, see `ModuleGenerator::generate_library_module`.
+//!
+//! A library exports a function that instantiates its module and a wrapper of each function the
+//! module exports, all named after the prefix of the library. Its header declares them, along with
+//! accessors of the memories and globals the module exports.
+use wasmo_runtime::data::ModuleData;
+use wasmo_runtime::library::{CALL_ERROR, CALL_OK};
+use wasmo_runtime::trap::TrapCode;
+use wasmo_runtime::types::{ExportKind, ValueType};
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Replaces what can't be in a C identifier with underscores.
+pub fn c_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if matches!(identifier.chars().next(), None | Some('0'..='9')) {
+        identifier.insert(0, '_');
+    }
+
+    identifier
+}
+
+/// Symbol of the function that instantiates the module.
+pub fn instantiate_symbol(prefix: &str) -> String {
+    format!("{}_instantiate", prefix)
+}
+
+/// Symbol of the wrapper of an exported function, or of the getter of an exported memory or
+/// global.
+pub fn export_symbol(prefix: &str, field: &str) -> String {
+    format!("{}_{}", prefix, c_identifier(field))
+}
+
+/// Symbol of the setter of an exported mutable global.
+pub fn global_setter_symbol(prefix: &str, field: &str) -> String {
+    format!("{}_set_{}", prefix, c_identifier(field))
+}
+
+/// Exports sorted by name, so that libraries and headers don't depend on hash order.
+pub fn sorted_exports(data: &ModuleData) -> Vec<(&String, &ExportKind)> {
+    let mut exports = data.exports.iter().collect::<Vec<_>>();
+    exports.sort_by_key(|(name, _)| *name);
+
+    exports
+}
+
+/// Checks that no two parts of the API of a library get the same symbol, e.g. because of
+/// characters `c_identifier` replaces.
+pub fn check_symbols(prefix: &str, data: &ModuleData) -> Result<(), &'static str> {
+    let mut symbols = HashSet::new();
+    symbols.insert(instantiate_symbol(prefix));
+
+    for (field, export) in data.exports.iter() {
+        if !symbols.insert(export_symbol(prefix, field)) {
+            return Err("Exports of the module get the same C symbol");
+        }
+
+        if let ExportKind::Global(index) = export {
+            let global = data.locals.globals.get(*index as usize);
+            if matches!(global, Some(global) if global.is_mutable())
+                && !symbols.insert(global_setter_symbol(prefix, field))
+            {
+                return Err("Exports of the module get the same C symbol");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates the C header of a library compiled from the module `data` describes.
+///
+/// Exported functions return `WASMO_OK`, the trap they trapped with or `WASMO_ERROR`, see
+/// `wasmo_runtime::library::wasmo_instance_call`. Accessors of memories and globals are inline
+/// functions calling into the runtime support library. Exported tables are not accessible.
+pub fn generate_header(prefix: &str, data: &ModuleData) -> Result<String, &'static str> {
+    let guard = format!("{}_H", c_identifier(prefix).to_uppercase());
+    let mut header = String::new();
+
+    // Writing to a String can't fail.
+    let mut line = |text: &str| writeln!(header, "{}", text).unwrap();
+
+    line(&format!(
+        "/* Generated by wasmo. Link against lib{}.a and libwasmo_support.a. */",
+        prefix
+    ));
+    line(&format!("#ifndef {}\n#define {}\n", guard, guard));
+    line("#include <stddef.h>\n#include <stdint.h>\n#include <string.h>\n");
+    line("#ifdef __cplusplus\nextern \"C\" {\n#endif\n");
+
+    // What libraries share is declared once.
+    line("#ifndef WASMO_SUPPORT_H\n#define WASMO_SUPPORT_H\n");
+    line("typedef struct wasmo_instance wasmo_instance;\n");
+    line(&format!("#define WASMO_OK {}", CALL_OK));
+    line(&format!("#define WASMO_ERROR {}", CALL_ERROR));
+    let mut code = 0;
+    while let Some(trap_code) = TrapCode::from_u32(code) {
+        line(&format!(
+            "#define WASMO_TRAP_{} {}",
+            screaming_snake_case(&format!("{:?}", trap_code)),
+            trap_code as i32 + 1
+        ));
+        code += 1;
+    }
+    line("");
+    line("void wasmo_instance_free(wasmo_instance *instance);");
+    line("uint8_t *wasmo_instance_memory(wasmo_instance *instance, uint32_t memory_index, size_t *size);");
+    line("uint64_t *wasmo_instance_global(wasmo_instance *instance, uint32_t global_index);");
+    line("\n#endif\n");
+
+    line("/* Returns NULL if instantiating the module fails. */");
+    line(&format!(
+        "wasmo_instance *{}(void);",
+        instantiate_symbol(prefix)
+    ));
+
+    for (field, export) in sorted_exports(data) {
+        line("");
+        match export {
+            ExportKind::Function(index) => {
+                let ty = data
+                    .function_type(*index)
+                    .ok_or("Function index out of bounds")?;
+                let mut params = vec!["wasmo_instance *instance".to_string()];
+                for (index, ty) in ty.params().iter().enumerate() {
+                    params.push(format!("{} p{}", c_type(ty), index));
+                }
+                for ty in ty.returns() {
+                    params.push(format!("{} *result", c_type(ty)));
+                }
+
+                line(&format!("/* Function `{}`. */", field));
+                line(&format!(
+                    "int32_t {}({});",
+                    export_symbol(prefix, field),
+                    params.join(", ")
+                ));
+            }
+            ExportKind::Memory(index) => {
+                line(&format!(
+                    "/* Memory `{}`. The bytes stay put when it grows, only the size changes. */",
+                    field
+                ));
+                line(&format!(
+                    "static inline uint8_t *{}(wasmo_instance *instance, size_t *size) {{",
+                    export_symbol(prefix, field)
+                ));
+                line(&format!(
+                    "    return wasmo_instance_memory(instance, {}, size);\n}}",
+                    index
+                ));
+            }
+            ExportKind::Global(index) => {
+                let global = data
+                    .locals
+                    .globals
+                    .get(*index as usize)
+                    .ok_or("Global index out of bounds")?;
+                let (ty, bits_type) = (c_type(&global.ty()), bits_type(&global.ty()));
+
+                // Values are in the low-order bits of the 64-bit cell of the global.
+                line(&format!("/* Global `{}`. */", field));
+                line(&format!(
+                    "static inline {} {}(wasmo_instance *instance) {{",
+                    ty,
+                    export_symbol(prefix, field)
+                ));
+                line(&format!(
+                    "    {} bits = ({})*wasmo_instance_global(instance, {});",
+                    bits_type, bits_type, index
+                ));
+                line(&format!("    {} value;", ty));
+                line("    memcpy(&value, &bits, sizeof value);\n    return value;\n}");
+
+                if global.is_mutable() {
+                    line(&format!(
+                        "static inline void {}(wasmo_instance *instance, {} value) {{",
+                        global_setter_symbol(prefix, field),
+                        ty
+                    ));
+                    line(&format!("    {} bits;", bits_type));
+                    line("    memcpy(&bits, &value, sizeof bits);");
+                    line(&format!(
+                        "    *wasmo_instance_global(instance, {}) = bits;\n}}",
+                        index
+                    ));
+                }
+            }
+            ExportKind::Table(_) => continue,
+        }
+    }
+
+    line("\n#ifdef __cplusplus\n}\n#endif\n");
+    line(&format!("#endif /* {} */", guard));
+
+    Ok(header)
+}
+
+fn c_type(ty: &ValueType) -> &'static str {
+    match ty {
+        ValueType::I32 => "int32_t",
+        ValueType::I64 => "int64_t",
+        ValueType::F32 => "float",
+        ValueType::F64 => "double",
+    }
+}
+
+/// Unsigned integer type of the bits of a value.
+fn bits_type(ty: &ValueType) -> &'static str {
+    match ty {
+        ValueType::I32 | ValueType::F32 => "uint32_t",
+        ValueType::I64 | ValueType::F64 => "uint64_t",
+    }
+}
+
+/// `MemoryOutOfBounds` to `MEMORY_OUT_OF_BOUNDS`.
+fn screaming_snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && index != 0 {
+            result.push('_');
+        }
+        result.push(c.to_ascii_uppercase());
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr::null;
+    use wasmo_runtime::data::{FuncData, GlobalData, InitExpression, MemoryData, ResizableLimits};
+    use wasmo_runtime::types::FuncType;
+
+    fn module_data() -> ModuleData {
+        let mut data = ModuleData::new();
+        data.add_type(FuncType::new(
+            vec![ValueType::I32, ValueType::F64],
+            vec![ValueType::I64],
+        ));
+        data.add_function(FuncData::new(null(), 0));
+        data.add_memory(MemoryData::new(ResizableLimits::new(1, None)));
+        data.add_global(GlobalData::new(
+            ValueType::F32,
+            true,
+            InitExpression::Const(0),
+        ));
+        data.add_export("memory".to_string(), ExportKind::Memory(0));
+        data.add_export("counter".to_string(), ExportKind::Global(0));
+        data.add_export("add-one".to_string(), ExportKind::Function(0));
+
+        data
+    }
+
+    #[test]
+    fn header_declares_exports_in_name_order() {
+        let header = generate_header("mylib", &module_data()).unwrap();
+
+        let expected = [
+            "#ifndef MYLIB_H",
+            "#define WASMO_OK 0",
+            "#define WASMO_ERROR -1",
+            "#define WASMO_TRAP_UNREACHABLE 1",
+            "#define WASMO_TRAP_STACK_OVERFLOW 9",
+            "wasmo_instance *mylib_instantiate(void);",
+            "int32_t mylib_add_one(wasmo_instance *instance, int32_t p0, double p1, int64_t *result);",
+            "static inline float mylib_counter(wasmo_instance *instance) {",
+            "    uint32_t bits = (uint32_t)*wasmo_instance_global(instance, 0);",
+            "static inline void mylib_set_counter(wasmo_instance *instance, float value) {",
+            "static inline uint8_t *mylib_memory(wasmo_instance *instance, size_t *size) {",
+            "    return wasmo_instance_memory(instance, 0, size);",
+            "#endif /* MYLIB_H */",
+        ];
+
+        let mut position = 0;
+        for text in expected.iter() {
+            let found = header[position..]
+                .find(text)
+                .unwrap_or_else(|| panic!("Missing or out of order: {}\n{}", text, header));
+            position += found + text.len();
+        }
+    }
+
+    #[test]
+    fn colliding_symbols_are_rejected() {
+        let mut data = module_data();
+        assert!(check_symbols("mylib", &data).is_ok());
+
+        data.add_export("add_one".to_string(), ExportKind::Function(0));
+        assert!(check_symbols("mylib", &data).is_err());
+    }
+
+    #[test]
+    fn c_identifiers() {
+        assert_eq!(c_identifier("add-one"), "add_one");
+        assert_eq!(c_identifier("1st"), "_1st");
+        assert_eq!(c_identifier(""), "_");
+    }
+}
@@ -1,11 +1,36 @@
+/// How generated code keeps memory accesses within the bounds of a memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryStrategy {
+    /// Every access compares its end address against the current length of the memory and traps
+    /// if it is out of bounds.
+    BoundsChecks,
+    /// Accesses are not checked. The runtime reserves enough address space for a memory that any
+    /// effective address falls within the reservation, and accesses to uncommitted pages fault.
+    GuardPages,
+}
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct CodegenOptions {
-
+    pub memory_strategy: MemoryStrategy,
+    /// Generates a `main` that instantiates the module and runs it, for linking the module into an
+    /// executable. Modules with imports can't be compiled that way.
+    pub generate_main: bool,
+    /// Target triple to generate code for, e.g. `aarch64-unknown-linux-gnu`. The host if `None`.
+    pub target_triple: Option<String>,
+    /// CPU to generate code for, e.g. `cortex-a72`. The generic CPU of the target if empty.
+    pub cpu: String,
+    /// Target features to enable or disable, e.g. `+neon,-crypto`.
+    pub features: String,
 }
 
 impl CodegenOptions {
     pub fn default() -> Self {
-        Self { }
+        Self {
+            memory_strategy: MemoryStrategy::BoundsChecks,
+            generate_main: false,
+            target_triple: None,
+            cpu: String::new(),
+            features: String::new(),
+        }
     }
 }
@@ -0,0 +1,90 @@
+//! Validation of wasm binaries. Modules are validated before any code is generated for them, so
+//! that the generator only ever sees well-typed code.
+use crate::error::ParserResult;
+use wasmparser::{
+    BinaryReader, OperatorValidatorConfig, ParserState, Range, SectionCode, ValidatingParser,
+    ValidatingParserConfig, WasmDecoder, WasmModuleResources,
+};
+
+/// Only MVP operators are supported. Mutable globals can be imported from the linker.
+const CONFIG: ValidatingParserConfig = ValidatingParserConfig {
+    operator_config: OperatorValidatorConfig {
+        enable_threads: false,
+        enable_reference_types: false,
+        enable_simd: false,
+        enable_bulk_memory: false,
+        enable_multi_value: false,
+    },
+    mutable_global_imports: true,
+};
+
+/// Checks that `wasm_binary` is a valid module, structurally and type-wise.
+pub fn validate(wasm_binary: &[u8]) -> ParserResult<()> {
+    let mut parser = ValidatingParser::new(wasm_binary, Some(CONFIG));
+
+    loop {
+        let start_section = match parser.read() {
+            ParserState::EndWasm => return Ok(()),
+            ParserState::Error(error) => return Err((*error).into()),
+            ParserState::BeginSection {
+                code: SectionCode::Start,
+                range,
+            } => Some(*range),
+            _ => None,
+        };
+
+        // The validator rejects all invalid start functions with the same message.
+        if let Some(range) = start_section {
+            check_start(wasm_binary, range, parser.get_resources())?;
+        }
+    }
+}
+
+/// Checks the start function of the start section in `range` against the types of `resources`.
+fn check_start(
+    wasm_binary: &[u8],
+    range: Range,
+    resources: &dyn WasmModuleResources,
+) -> ParserResult<()> {
+    let mut reader = BinaryReader::new_with_offset(range.slice(wasm_binary), range.start);
+    let func_index = reader.read_var_u32()?;
+
+    let type_index = resources
+        .func_type_indices()
+        .get(func_index as usize)
+        .ok_or("Start function index out of bounds")?;
+    let func_type = &resources.types()[*type_index as usize];
+
+    if !func_type.params.is_empty() {
+        return Err("Start function must not take parameters".into());
+    }
+
+    if !func_type.returns.is_empty() {
+        return Err("Start function must not return values".into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmo_utils::file::convert_wat_source_to_wasm;
+
+    fn validate_wat(source: &str) -> ParserResult<()> {
+        validate(&convert_wat_source_to_wasm(source).unwrap())
+    }
+
+    #[test]
+    fn valid_modules_pass() {
+        validate_wat(r#"(module (func (export "f") (param i32) (result i32) (local.get 0)))"#)
+            .unwrap();
+    }
+
+    #[test]
+    fn ill_typed_functions_fail() {
+        let source = "(module (func (drop (i32.add (i32.const 0) (i64.const 0)))))";
+        let error = format!("{:?}", validate_wat(source).unwrap_err());
+        assert!(error.contains("stack operand type mismatch"), "{}", error);
+    }
+}
@@ -1,6 +1,10 @@
+use llvm_sys::core::{LLVMGetBasicBlockTerminator, LLVMMoveBasicBlockAfter};
 use llvm_sys::prelude::{LLVMBasicBlockRef, LLVMValueRef};
 
+use crate::values::InstructionValue;
+
 ///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct BasicBlock {
     pub(crate) basic_block: LLVMBasicBlockRef,
 }
@@ -11,4 +15,20 @@ impl BasicBlock {
 
         Self { basic_block }
     }
+
+    /// Returns the terminator instruction of the block if it has one.
+    pub fn get_terminator(&self) -> Option<InstructionValue> {
+        let value = unsafe { LLVMGetBasicBlockTerminator(self.basic_block) };
+
+        if value.is_null() {
+            return None;
+        }
+
+        Some(InstructionValue::new(value))
+    }
+
+    /// Moves the block so it comes right after `basic_block` in its function's block list.
+    pub fn move_after(&self, basic_block: &BasicBlock) {
+        unsafe { LLVMMoveBasicBlockAfter(self.basic_block, basic_block.basic_block) }
+    }
 }
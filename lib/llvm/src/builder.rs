@@ -2,20 +2,36 @@ use std::rc::Rc;
 
 use std::ffi::CString;
 
-use llvm_sys::prelude::LLVMBuilderRef;
+use llvm_sys::prelude::{LLVMBuilderRef, LLVMValueRef};
+
+use llvm_sys::LLVMTypeKind;
 
 use llvm_sys::core::{
-    LLVMBuildAdd, LLVMBuildAlloca, LLVMBuildFAdd, LLVMBuildFMul, LLVMBuildFSub, LLVMBuildMul,
-    LLVMBuildRet, LLVMBuildRetVoid, LLVMBuildSub, LLVMCreateBuilder, LLVMDisposeBuilder,
+    LLVMAddCase, LLVMBuildAShr, LLVMBuildAdd, LLVMBuildAlloca, LLVMBuildAnd, LLVMBuildBitCast,
+    LLVMBuildBr, LLVMBuildCall, LLVMBuildCondBr, LLVMBuildFAdd, LLVMBuildFCmp, LLVMBuildFDiv,
+    LLVMBuildFMul, LLVMBuildFPExt, LLVMBuildFPToSI, LLVMBuildFPToUI, LLVMBuildFPTrunc,
+    LLVMBuildFSub, LLVMBuildGEP, LLVMBuildICmp, LLVMBuildIsNull, LLVMBuildLShr, LLVMBuildLoad,
+    LLVMBuildMul, LLVMBuildOr, LLVMBuildPhi, LLVMBuildPointerCast, LLVMBuildRet, LLVMBuildRetVoid,
+    LLVMBuildSDiv, LLVMBuildSExt, LLVMBuildSIToFP, LLVMBuildSRem, LLVMBuildSelect, LLVMBuildShl,
+    LLVMBuildStore, LLVMBuildStructGEP, LLVMBuildSub, LLVMBuildSwitch, LLVMBuildTrunc,
+    LLVMBuildUDiv, LLVMBuildUIToFP, LLVMBuildURem, LLVMBuildUnreachable, LLVMBuildXor,
+    LLVMBuildZExt, LLVMCreateBuilder, LLVMDisposeBuilder, LLVMGetInsertBlock, LLVMGetTypeKind,
     LLVMPositionBuilder, LLVMPositionBuilderAtEnd, LLVMPositionBuilderBefore,
 };
 
 use wasmo_utils::debug;
 
-use crate::{BasicBlock, Context};
+use crate::{BasicBlock, Context, FloatPredicate, IntPredicate, Module};
+
+use crate::types::{
+    function_type, AsTypeRef, BasicType, FloatType, FunctionType, IntType, PointerType,
+};
+
+use crate::values::FunctionValue;
 
 use crate::values::{
-    AsValueRef, BasicValue, FloatMathValue, InstructionValue, IntMathValue, PointerMathValue,
+    AsValueRef, BasicValue, FloatMathValue, FloatValue, InstructionValue, IntMathValue, IntValue,
+    PhiValue, PointerMathValue, PointerValue,
 };
 
 ///
@@ -63,8 +79,19 @@ impl Builder {
         }
     }
 
+    /// Returns the basic block the builder is currently positioned in.
+    pub fn get_insert_block(&self) -> Option<BasicBlock> {
+        let basic_block = unsafe { LLVMGetInsertBlock(self.builder) };
+
+        if basic_block.is_null() {
+            return None;
+        }
+
+        Some(BasicBlock::new(basic_block))
+    }
+
     ///
-    pub fn build_int_add<T: IntMathValue>(&self, rhs: T, lhs: T, name: &str) -> T {
+    pub fn build_int_add<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
         let name = CString::new(name).expect("Conversion of name string to c_string failed");
 
         let value =
@@ -123,6 +150,197 @@ impl Builder {
         T::new(value)
     }
 
+    ///
+    pub fn build_float_div<T: FloatMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildFDiv(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_int_signed_div<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildSDiv(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_int_unsigned_div<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildUDiv(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_int_signed_rem<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildSRem(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_int_unsigned_rem<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildURem(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_and<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildAnd(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_or<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildOr(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_xor<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildXor(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_left_shift<T: IntMathValue>(&self, lhs: T, rhs: T, name: &str) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildShl(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr()) };
+
+        T::new(value)
+    }
+
+    /// Builds an arithmetic shift if `sign_extend` is true, a logical shift otherwise.
+    pub fn build_right_shift<T: IntMathValue>(
+        &self,
+        lhs: T,
+        rhs: T,
+        sign_extend: bool,
+        name: &str,
+    ) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            if sign_extend {
+                LLVMBuildAShr(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr())
+            } else {
+                LLVMBuildLShr(self.builder, lhs.as_ref(), rhs.as_ref(), c_string.as_ptr())
+            }
+        };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_int_z_extend(&self, value: IntValue, ty: IntType, name: &str) -> IntValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildZExt(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr()) };
+
+        IntValue::new(value)
+    }
+
+    /// Returns `None` if the function doesn't return a value.
+    pub fn build_call(
+        &self,
+        function: &FunctionValue,
+        args: &[BasicValue],
+        name: &str,
+    ) -> Option<BasicValue> {
+        // Guarantee: functions always have a function type.
+        let function_type = function.get_type().unwrap();
+
+        self.build_call_value(function.as_ref(), function_type, args, name)
+    }
+
+    /// Calls the function `function_pointer` points to.
+    ///
+    /// Returns `None` if the function doesn't return a value.
+    pub fn build_indirect_call(
+        &self,
+        function_pointer: PointerValue,
+        args: &[BasicValue],
+        name: &str,
+    ) -> Option<BasicValue> {
+        let function_type = match function_pointer.val.get_type() {
+            BasicType::PointerType(ty) => match ty.ty.get_element_type() {
+                BasicType::FunctionType(ty) => ty,
+                _ => panic!("Expected a pointer to a function"),
+            },
+            _ => panic!("Expected a pointer to a function"),
+        };
+
+        self.build_call_value(function_pointer.as_ref(), function_type, args, name)
+    }
+
+    ///
+    fn build_call_value(
+        &self,
+        callee: LLVMValueRef,
+        function_type: FunctionType,
+        args: &[BasicValue],
+        name: &str,
+    ) -> Option<BasicValue> {
+        // Void values can't be named.
+        let returns_void = match function_type.get_return_type() {
+            BasicType::VoidType(_) => true,
+            _ => false,
+        };
+
+        let name = if returns_void { "" } else { name };
+
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let mut args: Vec<LLVMValueRef> = args.iter().map(|arg| arg.as_ref()).collect();
+
+        let value = unsafe {
+            LLVMBuildCall(
+                self.builder,
+                callee,
+                args.as_mut_ptr(),
+                args.len() as _,
+                c_string.as_ptr(),
+            )
+        };
+
+        if returns_void {
+            return None;
+        }
+
+        Some(BasicValue::new(value))
+    }
+
     ///
     pub fn build_return(&self, value: Option<BasicValue>) -> InstructionValue {
         let value = unsafe {
@@ -134,6 +352,421 @@ impl Builder {
 
         InstructionValue::new(value)
     }
+
+    ///
+    pub fn build_alloca(&self, ty: BasicType, name: &str) -> PointerValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe { LLVMBuildAlloca(self.builder, ty.as_ref(), c_string.as_ptr()) };
+
+        PointerValue::new(value)
+    }
+
+    ///
+    pub fn build_load(&self, pointer: PointerValue, name: &str) -> BasicValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe { LLVMBuildLoad(self.builder, pointer.as_ref(), c_string.as_ptr()) };
+
+        BasicValue::new(value)
+    }
+
+    ///
+    pub fn build_store(&self, value: BasicValue, pointer: PointerValue) -> InstructionValue {
+        let value = unsafe { LLVMBuildStore(self.builder, value.as_ref(), pointer.as_ref()) };
+
+        InstructionValue::new(value)
+    }
+
+    ///
+    pub fn build_gep(
+        &self,
+        pointer: PointerValue,
+        indices: &[IntValue],
+        name: &str,
+    ) -> PointerValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let mut indices: Vec<LLVMValueRef> = indices.iter().map(|index| index.as_ref()).collect();
+
+        let value = unsafe {
+            LLVMBuildGEP(
+                self.builder,
+                pointer.as_ref(),
+                indices.as_mut_ptr(),
+                indices.len() as _,
+                c_string.as_ptr(),
+            )
+        };
+
+        PointerValue::new(value)
+    }
+
+    ///
+    pub fn build_struct_gep(&self, pointer: PointerValue, index: u32, name: &str) -> PointerValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildStructGEP(self.builder, pointer.as_ref(), index, c_string.as_ptr()) };
+
+        PointerValue::new(value)
+    }
+
+    ///
+    pub fn build_pointer_cast(
+        &self,
+        pointer: PointerValue,
+        ty: PointerType,
+        name: &str,
+    ) -> PointerValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildPointerCast(
+                self.builder,
+                pointer.as_ref(),
+                ty.as_ref(),
+                c_string.as_ptr(),
+            )
+        };
+
+        PointerValue::new(value)
+    }
+
+    ///
+    pub fn build_is_null(&self, pointer: PointerValue, name: &str) -> IntValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe { LLVMBuildIsNull(self.builder, pointer.as_ref(), c_string.as_ptr()) };
+
+        IntValue::new(value)
+    }
+
+    ///
+    pub fn build_br(&self, destination: &BasicBlock) -> InstructionValue {
+        let value = unsafe { LLVMBuildBr(self.builder, destination.basic_block) };
+
+        InstructionValue::new(value)
+    }
+
+    ///
+    pub fn build_cond_br(
+        &self,
+        condition: IntValue,
+        then_block: &BasicBlock,
+        else_block: &BasicBlock,
+    ) -> InstructionValue {
+        let value = unsafe {
+            LLVMBuildCondBr(
+                self.builder,
+                condition.as_ref(),
+                then_block.basic_block,
+                else_block.basic_block,
+            )
+        };
+
+        InstructionValue::new(value)
+    }
+
+    /// Cases whose value doesn't match any of `cases` go to `else_block`.
+    pub fn build_switch(
+        &self,
+        value: IntValue,
+        else_block: &BasicBlock,
+        cases: &[(IntValue, BasicBlock)],
+    ) -> InstructionValue {
+        let switch = unsafe {
+            LLVMBuildSwitch(
+                self.builder,
+                value.as_ref(),
+                else_block.basic_block,
+                cases.len() as _,
+            )
+        };
+
+        for (case_value, case_block) in cases {
+            unsafe { LLVMAddCase(switch, case_value.as_ref(), case_block.basic_block) }
+        }
+
+        InstructionValue::new(switch)
+    }
+
+    ///
+    pub fn build_unreachable(&self) -> InstructionValue {
+        let value = unsafe { LLVMBuildUnreachable(self.builder) };
+
+        InstructionValue::new(value)
+    }
+
+    ///
+    pub fn build_phi(&self, ty: BasicType, name: &str) -> PhiValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe { LLVMBuildPhi(self.builder, ty.as_ref(), c_string.as_ptr()) };
+
+        PhiValue::new(value)
+    }
+
+    ///
+    pub fn build_icmp<T: IntMathValue>(
+        &self,
+        predicate: IntPredicate,
+        lhs: T,
+        rhs: T,
+        name: &str,
+    ) -> T {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildICmp(
+                self.builder,
+                predicate.into(),
+                lhs.as_ref(),
+                rhs.as_ref(),
+                c_string.as_ptr(),
+            )
+        };
+
+        T::new(value)
+    }
+
+    ///
+    pub fn build_fcmp<T: FloatMathValue>(
+        &self,
+        predicate: FloatPredicate,
+        lhs: T,
+        rhs: T,
+        name: &str,
+    ) -> IntValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildFCmp(
+                self.builder,
+                predicate.into(),
+                lhs.as_ref(),
+                rhs.as_ref(),
+                c_string.as_ptr(),
+            )
+        };
+
+        IntValue::new(value)
+    }
+
+    ///
+    pub fn build_int_truncate(&self, value: IntValue, ty: IntType, name: &str) -> IntValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildTrunc(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr()) };
+
+        IntValue::new(value)
+    }
+
+    ///
+    pub fn build_int_s_extend(&self, value: IntValue, ty: IntType, name: &str) -> IntValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildSExt(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr()) };
+
+        IntValue::new(value)
+    }
+
+    ///
+    pub fn build_float_to_signed_int(
+        &self,
+        value: FloatValue,
+        ty: IntType,
+        name: &str,
+    ) -> IntValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildFPToSI(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr())
+        };
+
+        IntValue::new(value)
+    }
+
+    ///
+    pub fn build_float_to_unsigned_int(
+        &self,
+        value: FloatValue,
+        ty: IntType,
+        name: &str,
+    ) -> IntValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildFPToUI(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr())
+        };
+
+        IntValue::new(value)
+    }
+
+    ///
+    pub fn build_signed_int_to_float(
+        &self,
+        value: IntValue,
+        ty: FloatType,
+        name: &str,
+    ) -> FloatValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildSIToFP(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr())
+        };
+
+        FloatValue::new(value)
+    }
+
+    ///
+    pub fn build_unsigned_int_to_float(
+        &self,
+        value: IntValue,
+        ty: FloatType,
+        name: &str,
+    ) -> FloatValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildUIToFP(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr())
+        };
+
+        FloatValue::new(value)
+    }
+
+    ///
+    pub fn build_float_truncate(&self, value: FloatValue, ty: FloatType, name: &str) -> FloatValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildFPTrunc(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr())
+        };
+
+        FloatValue::new(value)
+    }
+
+    ///
+    pub fn build_float_extend(&self, value: FloatValue, ty: FloatType, name: &str) -> FloatValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value =
+            unsafe { LLVMBuildFPExt(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr()) };
+
+        FloatValue::new(value)
+    }
+
+    /// Reinterprets the bits of `value` as `ty`. Constants are folded.
+    pub fn build_bitcast(&self, value: BasicValue, ty: BasicType, name: &str) -> BasicValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildBitCast(self.builder, value.as_ref(), ty.as_ref(), c_string.as_ptr())
+        };
+
+        BasicValue::new(value)
+    }
+
+    /// Builds a call to `llvm.fabs`.
+    pub fn build_float_abs(&self, module: &Module, value: FloatValue, name: &str) -> FloatValue {
+        self.build_float_intrinsic_call(module, "fabs", &[value], name)
+    }
+
+    /// Builds a call to `llvm.copysign`.
+    pub fn build_float_copysign(
+        &self,
+        module: &Module,
+        magnitude: FloatValue,
+        sign: FloatValue,
+        name: &str,
+    ) -> FloatValue {
+        self.build_float_intrinsic_call(module, "copysign", &[magnitude, sign], name)
+    }
+
+    /// Builds a call to `llvm.ceil`.
+    pub fn build_float_ceil(&self, module: &Module, value: FloatValue, name: &str) -> FloatValue {
+        self.build_float_intrinsic_call(module, "ceil", &[value], name)
+    }
+
+    /// Builds a call to `llvm.floor`.
+    pub fn build_float_floor(&self, module: &Module, value: FloatValue, name: &str) -> FloatValue {
+        self.build_float_intrinsic_call(module, "floor", &[value], name)
+    }
+
+    /// Builds a call to `llvm.trunc`.
+    pub fn build_float_trunc(&self, module: &Module, value: FloatValue, name: &str) -> FloatValue {
+        self.build_float_intrinsic_call(module, "trunc", &[value], name)
+    }
+
+    /// Builds a call to `llvm.nearbyint`, which rounds half to even under the default rounding
+    /// mode and doesn't raise an inexact exception.
+    pub fn build_float_nearest(
+        &self,
+        module: &Module,
+        value: FloatValue,
+        name: &str,
+    ) -> FloatValue {
+        self.build_float_intrinsic_call(module, "nearbyint", &[value], name)
+    }
+
+    /// Builds a call to `llvm.sqrt`.
+    pub fn build_float_sqrt(&self, module: &Module, value: FloatValue, name: &str) -> FloatValue {
+        self.build_float_intrinsic_call(module, "sqrt", &[value], name)
+    }
+
+    /// Calls an intrinsic overloaded on the float type of its arguments, declaring it if needed.
+    fn build_float_intrinsic_call(
+        &self,
+        module: &Module,
+        intrinsic: &str,
+        args: &[FloatValue],
+        name: &str,
+    ) -> FloatValue {
+        let ty = args[0].val.get_type();
+
+        let suffix = match unsafe { LLVMGetTypeKind(ty.as_ref()) } {
+            LLVMTypeKind::LLVMFloatTypeKind => "f32",
+            LLVMTypeKind::LLVMDoubleTypeKind => "f64",
+            _ => unreachable!("Float intrinsics only support f32 and f64"),
+        };
+
+        let param_types: Vec<BasicType> = args.iter().map(|_| ty).collect();
+
+        let function = module.get_or_declare_intrinsic(
+            &format!("llvm.{}.{}", intrinsic, suffix),
+            function_type(&param_types, ty, false),
+        );
+
+        let args: Vec<BasicValue> = args.iter().map(|arg| (*arg).into()).collect();
+
+        // Guarantee: float intrinsics return a value.
+        self.build_call(&function, &args, name).unwrap().into()
+    }
+
+    ///
+    pub fn build_select(
+        &self,
+        condition: IntValue,
+        then_value: BasicValue,
+        else_value: BasicValue,
+        name: &str,
+    ) -> BasicValue {
+        let c_string = CString::new(name).expect("Conversion of name string to c_string failed");
+
+        let value = unsafe {
+            LLVMBuildSelect(
+                self.builder,
+                condition.as_ref(),
+                then_value.as_ref(),
+                else_value.as_ref(),
+                c_string.as_ptr(),
+            )
+        };
+
+        BasicValue::new(value)
+    }
 }
 
 ///
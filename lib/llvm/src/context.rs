@@ -3,8 +3,9 @@ use std::rc::Rc;
 use std::ffi::CString;
 
 use llvm_sys::core::{
-    LLVMContextCreate, LLVMContextDispose, LLVMCreateBuilderInContext, LLVMDoubleTypeInContext,
-    LLVMFloatTypeInContext, LLVMInt32TypeInContext, LLVMInt64TypeInContext, LLVMInt8TypeInContext,
+    LLVMConstStringInContext, LLVMContextCreate, LLVMContextDispose, LLVMCreateBuilderInContext,
+    LLVMDoubleTypeInContext, LLVMFloatTypeInContext, LLVMInt16TypeInContext, LLVMInt1TypeInContext,
+    LLVMInt32TypeInContext, LLVMInt64TypeInContext, LLVMInt8TypeInContext,
     LLVMModuleCreateWithNameInContext, LLVMStructCreateNamed, LLVMStructSetBody,
     LLVMStructTypeInContext, LLVMVoidTypeInContext,
 };
@@ -17,6 +18,7 @@ use wasmo_utils::debug;
 
 use crate::{
     types::{BasicType, FloatType, IntType, StructType, VoidType},
+    values::ArrayValue,
     AddressSpace, Builder, Module,
 };
 
@@ -54,12 +56,38 @@ impl Context {
         Builder::new(builder, Some(self))
     }
 
+    /// Creates a constant i8 array holding `bytes`, without a null terminator.
+    pub fn const_bytes(&self, bytes: &[u8]) -> ArrayValue {
+        let value = unsafe {
+            LLVMConstStringInContext(
+                *self.context,
+                bytes.as_ptr() as *const _,
+                bytes.len() as u32,
+                1,
+            )
+        };
+
+        ArrayValue::new(value)
+    }
+
+    pub fn bool_type(&self) -> IntType {
+        let ty = unsafe { LLVMInt1TypeInContext(*self.context) };
+
+        IntType::new(ty)
+    }
+
     pub fn i8_type(&self) -> IntType {
         let ty = unsafe { LLVMInt8TypeInContext(*self.context) };
 
         IntType::new(ty)
     }
 
+    pub fn i16_type(&self) -> IntType {
+        let ty = unsafe { LLVMInt16TypeInContext(*self.context) };
+
+        IntType::new(ty)
+    }
+
     pub fn i32_type(&self) -> IntType {
         let ty = unsafe { LLVMInt32TypeInContext(*self.context) };
 
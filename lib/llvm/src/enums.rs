@@ -1,5 +1,5 @@
 #[macro_use]
-use llvm_sys::LLVMLinkage;
+use llvm_sys::{LLVMIntPredicate, LLVMLinkage, LLVMRealPredicate};
 use llvm_sys::target_machine::{LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMCodeModel, LLVMRelocMode};
 
 #[derive(Debug, PartialEq, Eq)]
@@ -79,6 +79,67 @@ impl RelocationModel {
 }
 
 
+enum_rename! {
+    /// Predicates used by integer comparison instructions.
+    LLVMIntPredicate >> IntPredicate {
+        /// Equal.
+        LLVMIntEQ >> EQ,
+        /// Not equal.
+        LLVMIntNE >> NE,
+        /// Unsigned greater than.
+        LLVMIntUGT >> UGT,
+        /// Unsigned greater or equal.
+        LLVMIntUGE >> UGE,
+        /// Unsigned less than.
+        LLVMIntULT >> ULT,
+        /// Unsigned less or equal.
+        LLVMIntULE >> ULE,
+        /// Signed greater than.
+        LLVMIntSGT >> SGT,
+        /// Signed greater or equal.
+        LLVMIntSGE >> SGE,
+        /// Signed less than.
+        LLVMIntSLT >> SLT,
+        /// Signed less or equal.
+        LLVMIntSLE >> SLE
+    }
+}
+
+enum_rename! {
+    /// Predicates used by float comparison instructions. Ordered predicates are false if either
+    /// operand is NaN, unordered predicates are true.
+    LLVMRealPredicate >> FloatPredicate {
+        /// Ordered and equal.
+        LLVMRealOEQ >> OEQ,
+        /// Ordered and greater than.
+        LLVMRealOGT >> OGT,
+        /// Ordered and greater or equal.
+        LLVMRealOGE >> OGE,
+        /// Ordered and less than.
+        LLVMRealOLT >> OLT,
+        /// Ordered and less or equal.
+        LLVMRealOLE >> OLE,
+        /// Ordered and not equal.
+        LLVMRealONE >> ONE,
+        /// Neither operand is NaN.
+        LLVMRealORD >> ORD,
+        /// Either operand is NaN.
+        LLVMRealUNO >> UNO,
+        /// Unordered or equal.
+        LLVMRealUEQ >> UEQ,
+        /// Unordered or greater than.
+        LLVMRealUGT >> UGT,
+        /// Unordered or greater or equal.
+        LLVMRealUGE >> UGE,
+        /// Unordered or less than.
+        LLVMRealULT >> ULT,
+        /// Unordered or less or equal.
+        LLVMRealULE >> ULE,
+        /// Unordered or not equal.
+        LLVMRealUNE >> UNE
+    }
+}
+
 enum_rename! {
     LLVMCodeGenFileType >> CodeGenFileType {
         LLVMAssemblyFile >> AssemblyFile,
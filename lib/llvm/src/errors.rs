@@ -5,7 +5,9 @@ pub type CompilerResult<T> = Result<T, CompilerError>;
 pub enum CompilerError {
     FunctionLookUp(FunctionLookUp),
     TargetInit(TargetInit),
-    ExecutionEngine(String),
+    /// An error the ORC JIT reported.
+    JIT(String),
+    Emit(String),
     GetValue(GetValue),
     GetType(GetType),
 }
@@ -15,7 +17,6 @@ pub enum CompilerError {
 ///
 #[derive(Debug, PartialEq, Eq)]
 pub enum FunctionLookUp {
-    JITNotEnabled,
     FunctionNotFound,
 }
 
@@ -29,6 +30,8 @@ pub enum TargetInit {
     CantInitializeNativeASMParser,
     CantInitializeNativeDisassembler,
     CantCreateTargetFromTriple(&'static str),
+    /// The target doesn't support the CPU, features or code model asked for.
+    CantCreateTargetMachine,
 }
 
 //-------------- VALUES --------------------//
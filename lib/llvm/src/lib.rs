@@ -5,8 +5,9 @@ mod builder;
 mod context;
 mod enums;
 pub mod errors;
-mod execution_engine;
 mod module;
+mod orc;
+mod pass_manager;
 pub mod support;
 pub mod target;
 pub mod types;
@@ -15,8 +16,9 @@ pub mod values;
 pub use basic_block::BasicBlock;
 pub use builder::Builder;
 pub use context::Context;
-pub use enums::{Linkage, OptimizationLevel, AddressSpace, RelocationModel, CodeModel};
+pub use enums::{Linkage, OptimizationLevel, AddressSpace, RelocationModel, CodeModel, IntPredicate, FloatPredicate, CodeGenFileType};
 pub use errors::{CompilerError, CompilerResult};
-pub use execution_engine::{ExecutionEngine, Func};
 pub use module::Module;
+pub use orc::{Func, ModuleHandle, ORCJIT};
+pub use pass_manager::FunctionPassManager;
 pub use target::InitializationConfig;
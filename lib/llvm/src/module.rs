@@ -1,42 +1,38 @@
-use std::mem::zeroed;
+use std::ffi::CString;
 
-use std::cell::RefCell;
+use std::mem::ManuallyDrop;
 
-use std::ffi::CString;
+use std::ptr;
 
 use std::fmt::{Display, Formatter, Result};
 
 use llvm_sys::core::{
-    LLVMAddFunction, LLVMDisposeModule, LLVMModuleCreateWithName, LLVMPrintModuleToString,
-};
-
-use llvm_sys::execution_engine::{
-    LLVMCreateExecutionEngineForModule, LLVMCreateInterpreterForModule,
-    LLVMCreateJITCompilerForModule, LLVMExecutionEngineRef,
+    LLVMAddFunction, LLVMAddGlobalInAddressSpace, LLVMDisposeModule, LLVMGetNamedFunction,
+    LLVMGetTypeByName, LLVMModuleCreateWithName, LLVMPrintModuleToString, LLVMSetTarget,
 };
 
 use llvm_sys::prelude::{LLVMContextRef, LLVMModuleRef};
 
+use llvm_sys::target::LLVMSetModuleDataLayout;
+
 use wasmo_utils::debug;
 
 use crate::support::LLVMString;
 
 use super::{
-    types::{AsTypeRef, FunctionType},
-    values::FunctionValue,
-    CompilerError, CompilerResult, Context, ExecutionEngine, InitializationConfig, Linkage,
-    OptimizationLevel,
+    types::{AsTypeRef, BasicType, FunctionType, StructType},
+    values::{FunctionValue, GlobalValue},
+    AddressSpace, Context, Linkage,
 };
 
-use crate::target::Target;
+use crate::target::TargetData;
 
-///
-/// TODO:IMPORTANT: Can the Rc be gotten rid of. Does EE own module?
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// An LLVM module. It disposes the module pointer, so it can't be cloned. Adding it to an
+/// `ORCJIT` moves it into the JIT, which disposes it instead.
+#[derive(Debug, PartialEq, Eq)]
 pub struct Module {
     pub(crate) module: LLVMModuleRef,
     context_ref: Option<Context>,
-    owned: RefCell<bool>, // RefCell is a workaround for mutating `owned` field without passing module mutably during execution engine creation.
 }
 
 impl Module {
@@ -47,13 +43,18 @@ impl Module {
         Self {
             module,
             context_ref: context.cloned(), // Increments Context.context ref count
-            owned: RefCell::new(false),
         }
     }
 
-    ///
-    pub(crate) fn is_owned(&self) -> bool {
-        *self.owned.borrow_mut()
+    /// Gives up ownership of the module pointer. The context it was created in is returned so
+    /// that the new owner can keep it alive.
+    pub(crate) fn into_raw(self) -> (LLVMModuleRef, Option<Context>) {
+        let module = ManuallyDrop::new(self);
+
+        // Moves the context out, `module` is not dropped.
+        let context_ref = unsafe { ptr::read(&module.context_ref) };
+
+        (module.module, context_ref)
     }
 
     ///
@@ -65,100 +66,97 @@ impl Module {
         Module::new(module, None)
     }
 
-    ///
-    pub fn create_interpreter_execution_engine(&self) -> CompilerResult<ExecutionEngine> {
-        Target::initialize_native(&InitializationConfig::default())?;
-
-        // Check if module is owned by another execution engine.
-        if self.is_owned() {
-            return Err(CompilerError::ExecutionEngine(
-                "Module is owned by another Execution Engine".into(),
-            ));
-        }
+    /// Consumes type
+    pub fn add_function(
+        &self,
+        function_name: &str,
+        func_type: FunctionType,
+        linkage: Option<Linkage>,
+    ) -> FunctionValue {
+        let name = CString::new(function_name).expect("CString conversion failed");
+
+        let value = unsafe { LLVMAddFunction(self.module, name.as_ptr(), func_type.as_ref()) };
+
+        let fn_value = FunctionValue::new(value);
 
-        // Set module as owned so it will be disposed by this execution engine.
-        *self.owned.borrow_mut() = true;
+        if let Some(linkage) = linkage {
+            fn_value.set_linkage(linkage)
+        }
 
-        let mut execution_engine = unsafe { zeroed() };
-        let mut error_string = unsafe { zeroed() };
+        fn_value
+    }
 
-        let code = unsafe {
-            LLVMCreateInterpreterForModule(
-                &mut execution_engine,
-                self.module, // Takes ownership of module
-                &mut error_string,
+    ///
+    pub fn add_global(
+        &self,
+        ty: BasicType,
+        address_space: &AddressSpace,
+        global_name: &str,
+    ) -> GlobalValue {
+        let name = CString::new(global_name).expect("CString conversion failed");
+
+        let value = unsafe {
+            LLVMAddGlobalInAddressSpace(
+                self.module,
+                ty.as_ref(),
+                name.as_ptr(),
+                *address_space as _,
             )
         };
 
-        if code == 1 {
-            return Err(CompilerError::ExecutionEngine(unsafe {
-                CString::from_raw(error_string)
-                    .into_string()
-                    .expect("Conversion of error string to CString failed")
-            }));
-        }
-
-        Ok(ExecutionEngine::new(execution_engine, false))
+        GlobalValue::new(value)
     }
 
+    /// Gets the declaration of an LLVM intrinsic, declaring it in the module if it is not there yet.
     ///
-    pub fn create_jit_execution_engine(
+    /// Overloaded intrinsics must be named with their type suffix, e.g. `llvm.ctpop.i32`.
+    pub fn get_or_declare_intrinsic(
         &self,
-        opt_level: OptimizationLevel,
-    ) -> CompilerResult<ExecutionEngine> {
-        Target::initialize_native(&InitializationConfig::default())?;
-
-        // Check if module is owned by another execution engine.
-        if self.is_owned() {
-            return Err(CompilerError::ExecutionEngine(
-                "Module is owned by another Execution Engine".into(),
-            ));
+        intrinsic_name: &str,
+        func_type: FunctionType,
+    ) -> FunctionValue {
+        match self.get_function(intrinsic_name) {
+            Some(function) => function,
+            None => self.add_function(intrinsic_name, func_type, None),
         }
+    }
 
-        // Set module as owned so it will be disposed by this execution engine.
-        *self.owned.borrow_mut() = true;
-
-        let mut execution_engine = unsafe { zeroed() };
-        let mut error_string = unsafe { zeroed() };
+    /// Gets a named struct type of the module's context.
+    pub fn get_struct_type(&self, struct_name: &str) -> Option<StructType> {
+        let name = CString::new(struct_name).expect("CString conversion failed");
 
-        let code = unsafe {
-            LLVMCreateJITCompilerForModule(
-                &mut execution_engine,
-                self.module, // Takes ownership of module
-                opt_level as u32,
-                &mut error_string,
-            )
-        };
+        let ty = unsafe { LLVMGetTypeByName(self.module, name.as_ptr()) };
 
-        if code == 1 {
-            return Err(CompilerError::ExecutionEngine(unsafe {
-                CString::from_raw(error_string)
-                    .into_string()
-                    .expect("Conversion of error string to CString failed")
-            }));
+        if ty.is_null() {
+            return None;
         }
 
-        Ok(ExecutionEngine::new(execution_engine, true))
+        Some(StructType::new(ty))
     }
 
-    /// Consumes type
-    pub fn add_function(
-        &self,
-        function_name: &str,
-        func_type: FunctionType,
-        linkage: Option<Linkage>,
-    ) -> FunctionValue {
-        let name = CString::new(function_name).expect("CString conversion failed");
+    /// Sets the target triple the module is compiled for.
+    pub fn set_triple(&self, triple: &str) {
+        let triple = CString::new(triple).expect("CString conversion failed");
 
-        let value = unsafe { LLVMAddFunction(self.module, name.as_ptr(), func_type.as_ref()) };
+        unsafe { LLVMSetTarget(self.module, triple.as_ptr()) }
+    }
 
-        let fn_value = FunctionValue::new(value);
+    /// Sets the data layout the module is compiled with. It must match the target machine's.
+    pub fn set_data_layout(&self, target_data: &TargetData) {
+        unsafe { LLVMSetModuleDataLayout(self.module, target_data.data) }
+    }
 
-        if let Some(linkage) = linkage {
-            fn_value.set_linkage(linkage)
+    ///
+    pub fn get_function(&self, function_name: &str) -> Option<FunctionValue> {
+        let name = CString::new(function_name).expect("CString conversion failed");
+
+        let value = unsafe { LLVMGetNamedFunction(self.module, name.as_ptr()) };
+
+        if value.is_null() {
+            return None;
         }
 
-        fn_value
+        Some(FunctionValue::new(value))
     }
 }
 
@@ -174,12 +172,9 @@ impl Display for Module {
 ///
 impl Drop for Module {
     fn drop(&mut self) {
-        // NOTE: ExecutionEngine disposes its associated Module.
-        // Dispose Module pointer explicitly if not owned by an ExecutionEngine.
-        if !*self.owned.borrow_mut() {
-            unsafe {
-                LLVMDisposeModule(self.module);
-            }
+        // NOTE: Modules added to an ORCJIT are disposed by it, see `into_raw`.
+        unsafe {
+            LLVMDisposeModule(self.module);
         }
 
         debug!("Module drop!");
@@ -1,4 +1,313 @@
-//! Module for generating ORC Module.
+//! Module for jit-compiling LLVM modules with ORC.
 
+use std::collections::HashMap;
+
+use std::ffi::{CStr, CString};
+
+use std::ptr::{self, null};
+
+use libc::{c_char, c_void};
+
+use llvm_sys::error::{LLVMDisposeErrorMessage, LLVMErrorRef, LLVMGetErrorMessage};
+
+use llvm_sys::orc::{
+    LLVMOrcAddEagerlyCompiledIR, LLVMOrcCreateInstance, LLVMOrcDisposeInstance,
+    LLVMOrcDisposeMangledSymbol, LLVMOrcGetMangledSymbol, LLVMOrcGetSymbolAddress,
+    LLVMOrcJITStackRef, LLVMOrcModuleHandle, LLVMOrcRemoveModule, LLVMOrcTargetAddress,
+};
+
+use llvm_sys::support::{LLVMLoadLibraryPermanently, LLVMSearchForAddressOfSymbol};
+
+use wasmo_utils::debug;
+
+use crate::{
+    errors::{FunctionLookUp, TargetInit},
+    target::{Target, TargetMachine},
+    CodeModel, CompilerError, CompilerResult, Context, InitializationConfig, Module,
+    OptimizationLevel, RelocationModel,
+};
+
+/// A JIT compiler that modules can be added to and removed from.
+///
+/// Added modules are compiled when a symbol is first looked up. Their undefined symbols resolve
+/// against the symbols of other added modules, then the host symbols registered with
+/// `add_host_symbol`, then the symbols of the process.
 pub struct ORCJIT {
+    jit_stack: LLVMOrcJITStackRef,
+    // Boxed, the JIT stack holds a pointer to it.
+    resolver: Box<SymbolResolver>,
+    // Contexts of the added modules, by module handle. The JIT stack owns the modules, which must
+    // not outlive their context.
+    contexts: HashMap<LLVMOrcModuleHandle, Option<Context>>,
+}
+
+/// Identifies a module added to an `ORCJIT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModuleHandle(LLVMOrcModuleHandle);
+
+/// What undefined symbols of added modules resolve against, by mangled name.
+struct SymbolResolver {
+    host_symbols: HashMap<String, u64>,
+    global_prefix: String,
+}
+
+impl ORCJIT {
+    /// Creates a JIT compiling for `target_machine`, which it takes.
+    pub fn new(target_machine: TargetMachine) -> Self {
+        let jit_stack = unsafe { LLVMOrcCreateInstance(target_machine.into_raw()) };
+
+        assert!(!jit_stack.is_null());
+
+        // Loads the symbols of the process, for lookups of symbols no module defines.
+        unsafe { LLVMLoadLibraryPermanently(null()) };
+
+        let mut jit = Self {
+            jit_stack,
+            resolver: Box::new(SymbolResolver {
+                host_symbols: HashMap::new(),
+                global_prefix: String::new(),
+            }),
+            contexts: HashMap::new(),
+        };
+
+        // Mangling adds the global prefix of the target, e.g. `_` on macOS.
+        jit.resolver.global_prefix = jit.get_mangled_symbol("");
+
+        jit
+    }
+
+    /// Creates a JIT compiling for the host.
+    pub fn create_native(opt_level: OptimizationLevel) -> CompilerResult<Self> {
+        Target::initialize_native(&InitializationConfig::default())?;
+
+        let target_triple = Target::get_default_triple().to_string();
+
+        let target = Target::from_triple(&target_triple)?;
+
+        let target_machine = target
+            .create_target_machine(
+                &target_triple,
+                "",
+                "",
+                opt_level,
+                RelocationModel::Default,
+                CodeModel::JITDefault,
+            )
+            .ok_or(CompilerError::TargetInit(
+                TargetInit::CantCreateTargetMachine,
+            ))?;
+
+        Ok(ORCJIT::new(target_machine))
+    }
+
+    /// Adds `module`, which the JIT takes. It is compiled when one of its symbols is first looked
+    /// up.
+    pub fn add_module(&mut self, module: Module) -> CompilerResult<ModuleHandle> {
+        let (module, context) = module.into_raw();
+        let mut handle = 0;
+
+        let error = unsafe {
+            LLVMOrcAddEagerlyCompiledIR(
+                self.jit_stack,
+                &mut handle,
+                module, // Takes ownership of module
+                Some(resolve_symbol),
+                &*self.resolver as *const SymbolResolver as *mut c_void,
+            )
+        };
+
+        check_error(error)?;
+
+        self.contexts.insert(handle, context);
+
+        Ok(ModuleHandle(handle))
+    }
+
+    /// Removes the module of `handle` and frees its code. Addresses looked up in it dangle.
+    pub fn remove_module(&mut self, handle: ModuleHandle) -> CompilerResult<()> {
+        let error = unsafe { LLVMOrcRemoveModule(self.jit_stack, handle.0) };
+
+        check_error(error)?;
+
+        self.contexts.remove(&handle.0);
+
+        Ok(())
+    }
+
+    /// Makes added modules resolve the undefined symbol `name` to `address`, e.g. a host function
+    /// a module imports. Applies to modules that haven't been compiled yet.
+    pub fn add_host_symbol(&mut self, name: &str, address: u64) {
+        let mangled_name = self.get_mangled_symbol(name);
+
+        self.resolver.host_symbols.insert(mangled_name, address);
+    }
+
+    /// Gets the address of a symbol of the added modules. Compiles the module that defines it if
+    /// it hasn't been compiled yet.
+    pub fn get_symbol_address(&self, name: &str) -> CompilerResult<u64> {
+        let name = CString::new(name).expect("Conversion to CString failed");
+
+        let mut address: LLVMOrcTargetAddress = 0;
+
+        let error = unsafe { LLVMOrcGetSymbolAddress(self.jit_stack, &mut address, name.as_ptr()) };
+
+        check_error(error)?;
+
+        if address == 0 {
+            return Err(CompilerError::FunctionLookUp(
+                FunctionLookUp::FunctionNotFound,
+            ));
+        }
+
+        Ok(address)
+    }
+
+    /// Gets a function of the added modules as a callable `Func`. Compiles the module that defines
+    /// it if it hasn't been compiled yet.
+    ///
+    /// ###### Safety
+    /// `F` must be the `extern "C"` function type the function is compiled with. The function
+    /// dangles once its module is removed or the JIT is dropped.
+    pub unsafe fn get_function<F>(&self, name: &str) -> CompilerResult<Func<F>> {
+        let address = self.get_symbol_address(name)?;
+
+        Ok(Func::create(address))
+    }
+
+    /// Gets the name `name` has in the object code of the target.
+    fn get_mangled_symbol(&self, name: &str) -> String {
+        let name = CString::new(name).expect("Conversion to CString failed");
+
+        let mut mangled_name = ptr::null_mut();
+
+        unsafe {
+            LLVMOrcGetMangledSymbol(self.jit_stack, &mut mangled_name, name.as_ptr());
+
+            let result = CStr::from_ptr(mangled_name).to_string_lossy().into_owned();
+
+            LLVMOrcDisposeMangledSymbol(mangled_name);
+
+            result
+        }
+    }
+}
+
+/// Frees the code of the added modules, then their contexts.
+impl Drop for ORCJIT {
+    fn drop(&mut self) {
+        debug!("ORCJIT drop!");
+        // NOTE: Disposes the added modules before their contexts are dropped.
+        unsafe {
+            LLVMOrcDisposeInstance(self.jit_stack);
+        }
+    }
+}
+
+/// Resolves the undefined symbols of added modules that other added modules don't define.
+extern "C" fn resolve_symbol(name: *const c_char, context: *mut c_void) -> u64 {
+    // Guarantee: `context` is the resolver of the JIT, which outlives its modules.
+    let resolver = unsafe { &*(context as *const SymbolResolver) };
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+
+    if let Some(address) = resolver.host_symbols.get(name.as_ref()) {
+        return *address;
+    }
+
+    // Symbols of the process are not mangled.
+    let name = if name.starts_with(resolver.global_prefix.as_str()) {
+        &name[resolver.global_prefix.len()..]
+    } else {
+        name.as_ref()
+    };
+    let name = CString::new(name).expect("Conversion to CString failed");
+
+    unsafe { LLVMSearchForAddressOfSymbol(name.as_ptr()) as u64 }
+}
+
+fn check_error(error: LLVMErrorRef) -> CompilerResult<()> {
+    if error.is_null() {
+        return Ok(());
+    }
+
+    // Consumes the error.
+    unsafe {
+        let message = LLVMGetErrorMessage(error);
+
+        let result = CStr::from_ptr(message).to_string_lossy().into_owned();
+
+        LLVMDisposeErrorMessage(message);
+
+        Err(CompilerError::JIT(result))
+    }
+}
+
+/// Represents a jitted function that can be called.
+pub struct Func<F> {
+    address: F,
+}
+
+impl<F> Func<F> {
+    pub unsafe fn create(address: u64) -> Func<F> {
+        Func {
+            address: std::mem::transmute_copy(&address),
+        }
+    }
+}
+
+// Support for 15 arguments for now.
+recurse_vararg_impl!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{function_type, BasicType};
+    use crate::values::IntValue;
+
+    type AnswerFunc = unsafe extern "C" fn(u64) -> u64;
+
+    extern "C" fn host_offset() -> u64 {
+        40
+    }
+
+    /// `answer(x) = x + host_offset()`, with `host_offset` left undefined.
+    fn build_answer(context: &Context) -> Module {
+        let module = context.create_module("answer");
+        let builder = context.create_builder();
+        let i64_type: BasicType = context.i64_type().into();
+
+        let host_offset =
+            module.add_function("host_offset", function_type(&[], i64_type, false), None);
+        let function =
+            module.add_function("answer", function_type(&[i64_type], i64_type, false), None);
+        let basic_block = function.append_basic_block("entry", context);
+        builder.position_at_end(&basic_block);
+
+        let x: IntValue = function.get_nth_param(0).unwrap().into();
+        let offset: IntValue = builder
+            .build_call(&host_offset, &[], "offset")
+            .unwrap()
+            .into();
+        let answer = builder.build_int_add(x, offset, "answer");
+        builder.build_return(Some(answer.into()));
+
+        module
+    }
+
+    #[test]
+    fn add_look_up_and_remove_modules() {
+        let context = Context::create();
+        let mut jit = ORCJIT::create_native(OptimizationLevel::None).unwrap();
+        jit.add_host_symbol("host_offset", host_offset as usize as u64);
+
+        let handle = jit.add_module(build_answer(&context)).unwrap();
+
+        assert_ne!(jit.get_symbol_address("answer").unwrap(), 0);
+        assert!(jit.get_symbol_address("missing").is_err());
+
+        let answer: Func<AnswerFunc> = unsafe { jit.get_function("answer").unwrap() };
+        assert_eq!(unsafe { answer.call(2) }, 42);
+
+        jit.remove_module(handle).unwrap();
+        assert!(jit.get_symbol_address("answer").is_err());
+    }
 }
@@ -0,0 +1,63 @@
+use llvm_sys::core::{
+    LLVMCreateFunctionPassManagerForModule, LLVMDisposePassManager,
+    LLVMFinalizeFunctionPassManager, LLVMInitializeFunctionPassManager, LLVMRunFunctionPassManager,
+};
+
+use llvm_sys::prelude::LLVMPassManagerRef;
+
+use llvm_sys::transforms::util::LLVMAddPromoteMemoryToRegisterPass;
+
+use wasmo_utils::debug;
+
+use crate::values::{AsValueRef, FunctionValue};
+
+use crate::Module;
+
+/// Runs optimization passes on individual functions of a module.
+///
+/// The module must outlive the pass manager.
+#[derive(Debug)]
+pub struct FunctionPassManager {
+    pass_manager: LLVMPassManagerRef,
+}
+
+impl FunctionPassManager {
+    ///
+    pub fn create(module: &Module) -> Self {
+        let pass_manager = unsafe { LLVMCreateFunctionPassManagerForModule(module.module) };
+
+        assert!(!pass_manager.is_null());
+
+        Self { pass_manager }
+    }
+
+    /// Adds the pass that promotes allocas to SSA registers, inserting phi nodes where needed.
+    pub fn add_promote_memory_to_register_pass(&self) {
+        unsafe { LLVMAddPromoteMemoryToRegisterPass(self.pass_manager) }
+    }
+
+    /// Must be called after all passes have been added and before any function is run.
+    pub fn initialize(&self) -> bool {
+        unsafe { LLVMInitializeFunctionPassManager(self.pass_manager) == 1 }
+    }
+
+    /// Returns true if any of the passes modified the function.
+    pub fn run_on(&self, function: &FunctionValue) -> bool {
+        unsafe { LLVMRunFunctionPassManager(self.pass_manager, function.as_ref()) == 1 }
+    }
+
+    ///
+    pub fn finalize(&self) -> bool {
+        unsafe { LLVMFinalizeFunctionPassManager(self.pass_manager) == 1 }
+    }
+}
+
+///
+impl Drop for FunctionPassManager {
+    fn drop(&mut self) {
+        debug!("FunctionPassManager drop!");
+        unsafe {
+            LLVMDisposePassManager(self.pass_manager);
+        }
+    }
+}
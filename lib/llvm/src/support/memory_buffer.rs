@@ -0,0 +1,47 @@
+use llvm_sys::core::{LLVMDisposeMemoryBuffer, LLVMGetBufferSize, LLVMGetBufferStart};
+use llvm_sys::prelude::LLVMMemoryBufferRef;
+use std::fmt::{self, Debug, Formatter};
+use std::slice;
+use wasmo_utils::debug;
+
+/// A buffer of bytes created by the LLVM library, like the object code a `TargetMachine` emits.
+pub struct MemoryBuffer {
+    memory_buffer: LLVMMemoryBufferRef,
+}
+
+impl MemoryBuffer {
+    pub(crate) fn new(memory_buffer: LLVMMemoryBufferRef) -> Self {
+        assert!(!memory_buffer.is_null());
+
+        Self { memory_buffer }
+    }
+
+    ///
+    pub fn get_size(&self) -> usize {
+        unsafe { LLVMGetBufferSize(self.memory_buffer) }
+    }
+
+    /// Borrows the content of the buffer.
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe {
+            let start = LLVMGetBufferStart(self.memory_buffer) as *const u8;
+
+            slice::from_raw_parts(start, self.get_size())
+        }
+    }
+}
+
+impl Drop for MemoryBuffer {
+    fn drop(&mut self) {
+        debug!("MemoryBuffer drop!");
+        unsafe {
+            LLVMDisposeMemoryBuffer(self.memory_buffer);
+        }
+    }
+}
+
+impl Debug for MemoryBuffer {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "MemoryBuffer({} bytes)", self.get_size())
+    }
+}
@@ -1,4 +1,6 @@
+mod memory_buffer;
 mod string;
 
 
+pub use memory_buffer::MemoryBuffer;
 pub(crate) use string::LLVMString;
@@ -22,6 +22,7 @@ impl LLVMString {
     }
 
     ///
+    #[allow(clippy::inherent_to_string_shadow_display)] // Display quotes and escapes the string
     pub fn to_string(&self) -> String {
         // Clones the content buffer_ptr points to using `to_string_lossy`.
         unsafe {
@@ -1,26 +1,31 @@
 use llvm_sys::target_machine::{
     LLVMCreateTargetDataLayout, LLVMCreateTargetMachine, LLVMDisposeTargetMachine,
     LLVMGetDefaultTargetTriple, LLVMGetTargetDescription, LLVMGetTargetFromTriple,
-    LLVMNormalizeTargetTriple, LLVMTargetMachineRef, LLVMTargetRef,
+    LLVMGetTargetMachineTriple, LLVMNormalizeTargetTriple, LLVMTargetMachineEmitToFile,
+    LLVMTargetMachineEmitToMemoryBuffer, LLVMTargetMachineRef, LLVMTargetRef,
 };
 
 use llvm_sys::target::{
-    LLVMCreateTargetData, LLVMDisposeTargetData, LLVMIntPtrType, LLVMIntPtrTypeForAS,
-    LLVMTargetDataRef,
+    LLVMABISizeOfType, LLVMCreateTargetData, LLVMDisposeTargetData, LLVMIntPtrType,
+    LLVMIntPtrTypeForAS, LLVMOffsetOfElement, LLVMTargetDataRef,
 };
 
 
 use super::{errors::TargetInit, CompilerError, CompilerResult};
 
-use crate::types::IntType;
+use crate::types::{AsTypeRef, BasicType, IntType, StructType};
 
-use crate::enums::{CodeModel, OptimizationLevel, RelocationModel};
+use crate::enums::{CodeGenFileType, CodeModel, OptimizationLevel, RelocationModel};
+
+use crate::Module;
 
 use crate::AddressSpace;
 
-use crate::support::LLVMString;
+use crate::support::{LLVMString, MemoryBuffer};
 
 use std::ffi::{CStr, CString};
+use std::mem::{self, zeroed};
+use std::path::Path;
 
 ///
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -213,10 +218,13 @@ impl Target {
     }
 
     pub fn normalize_target_triple(triple: &str) -> LLVMString {
+        let c_string = CString::new(triple)
+            .expect("Conversion of triple string to CString failed");
+
         // Borrowing triple pointer.
         // Creating an LLVMString from returned pointer.
         unsafe {
-            LLVMString::new(LLVMNormalizeTargetTriple(triple.as_ptr() as _))
+            LLVMString::new(LLVMNormalizeTargetTriple(c_string.as_ptr()))
         }
     }
 
@@ -307,11 +315,89 @@ impl TargetMachine {
         }
     }
 
+    /// Gives up ownership of the target machine, e.g. to an ORC JIT stack which disposes it.
+    pub(crate) fn into_raw(self) -> LLVMTargetMachineRef {
+        let machine = self.machine;
+        mem::forget(self);
+
+        machine
+    }
+
     pub fn get_target_data(&self) -> TargetData {
         let data_layout = unsafe { LLVMCreateTargetDataLayout(self.machine) };
 
         TargetData::new(data_layout)
     }
+
+    ///
+    pub fn get_triple(&self) -> LLVMString {
+        // Creating an LLVMString from returned pointer.
+        unsafe { LLVMString::new(LLVMGetTargetMachineTriple(self.machine)) }
+    }
+
+    /// Compiles `module` to an object or assembly file at `path`.
+    ///
+    /// The module should have the triple and data layout of this target machine.
+    pub fn emit_to_file(
+        &self,
+        module: &Module,
+        file_type: CodeGenFileType,
+        path: &Path,
+    ) -> CompilerResult<()> {
+        let path = CString::new(path.to_string_lossy().as_bytes())
+            .expect("Conversion of path string to CString failed");
+
+        let mut error_string = unsafe { zeroed() };
+
+        // LLVM takes a mutable filename but doesn't write to it.
+        let code = unsafe {
+            LLVMTargetMachineEmitToFile(
+                self.machine,
+                module.module,
+                path.as_ptr() as *mut _,
+                file_type.into(),
+                &mut error_string,
+            )
+        };
+
+        if code == 1 {
+            return Err(CompilerError::Emit(
+                unsafe { LLVMString::new(error_string) }.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Compiles `module` to an object or assembly file in memory.
+    ///
+    /// The module should have the triple and data layout of this target machine.
+    pub fn emit_to_memory_buffer(
+        &self,
+        module: &Module,
+        file_type: CodeGenFileType,
+    ) -> CompilerResult<MemoryBuffer> {
+        let mut memory_buffer = unsafe { zeroed() };
+        let mut error_string = unsafe { zeroed() };
+
+        let code = unsafe {
+            LLVMTargetMachineEmitToMemoryBuffer(
+                self.machine,
+                module.module,
+                file_type.into(),
+                &mut error_string,
+                &mut memory_buffer,
+            )
+        };
+
+        if code == 1 {
+            return Err(CompilerError::Emit(
+                unsafe { LLVMString::new(error_string) }.to_string(),
+            ));
+        }
+
+        Ok(MemoryBuffer::new(memory_buffer))
+    }
 }
 
 impl Drop for TargetMachine {
@@ -354,6 +440,16 @@ impl TargetData {
 
         IntType::new(ty)
     }
+
+    /// Gets the number of bytes between successive values of type `ty`, padding included.
+    pub fn get_abi_size(&self, ty: &BasicType) -> u64 {
+        unsafe { LLVMABISizeOfType(self.data, ty.as_ref()) }
+    }
+
+    /// Gets the byte offset of a field of `ty`.
+    pub fn offset_of_element(&self, ty: &StructType, index: u32) -> u64 {
+        unsafe { LLVMOffsetOfElement(self.data, ty.as_ref(), index) }
+    }
 }
 
 impl Drop for TargetData {
@@ -2,11 +2,11 @@ use super::Type;
 
 use llvm_sys::prelude::LLVMTypeRef;
 
-use llvm_sys::core::LLVMConstInt;
+use llvm_sys::core::{LLVMArrayType, LLVMConstInt, LLVMGetIntTypeWidth};
 
 use crate::values::IntValue;
 
-use crate::types::PointerType;
+use crate::types::{ArrayType, PointerType};
 
 use crate::AddressSpace;
 
@@ -33,9 +33,17 @@ impl IntType {
         self.ty.ptr_type(address_space)
     }
 
+    pub fn array_type(&self, size: u32) -> ArrayType {
+        unsafe { ArrayType::new(LLVMArrayType(self.ty.ty, size)) }
+    }
+
     pub fn zero(&self, sign_extend: bool) -> IntValue {
         self.const_int(0, sign_extend)
     }
+
+    pub fn get_bit_width(&self) -> u32 {
+        unsafe { LLVMGetIntTypeWidth(self.ty.ty) }
+    }
 }
 
 impl AsTypeRef for IntType {
@@ -4,6 +4,8 @@ use llvm_sys::prelude::LLVMValueRef;
 
 use super::AsValueRef;
 
+use crate::types::BasicType;
+
 ///
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct ArrayValue {
@@ -18,6 +20,11 @@ impl ArrayValue {
             val: Value::new(val),
         }
     }
+
+    ///
+    pub fn get_type(&self) -> BasicType {
+        self.val.get_type()
+    }
 }
 
 impl AsValueRef for ArrayValue {
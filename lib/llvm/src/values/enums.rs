@@ -3,11 +3,11 @@ use llvm_sys::prelude::LLVMValueRef;
 
 use llvm_sys::LLVMTypeKind;
 
-use llvm_sys::core::{LLVMGetTypeKind, LLVMTypeOf};
+use llvm_sys::core::{LLVMGetTypeKind, LLVMIsAInstruction, LLVMTypeOf};
 
 use super::{
-    ArrayValue, AsValueRef, FloatValue, FunctionValue, IntValue, PointerValue, StructValue,
-    VectorValue,
+    ArrayValue, AsValueRef, FloatValue, FunctionValue, InstructionValue, IntValue, PointerValue,
+    StructValue, VectorValue,
 };
 
 enum_impl_def! {
@@ -27,3 +27,15 @@ enum_impl_def! {
     }
 }
 
+impl BasicValue {
+    /// Returns `None` if the value isn't produced by an instruction, e.g. a constant.
+    pub fn as_instruction(self) -> Option<InstructionValue> {
+        let value = self.as_ref();
+
+        if unsafe { LLVMIsAInstruction(value).is_null() } {
+            return None;
+        }
+
+        Some(InstructionValue::new(value))
+    }
+}
@@ -13,7 +13,7 @@ use super::{
         errors::{GetValue, GetType}, CompilerError, CompilerResult,
         types::{FunctionType, BasicType},
     },
-    AsValueRef, BasicValue,
+    AsValueRef, BasicValue, PointerValue,
 };
 
 use std::ffi::CString;
@@ -41,6 +41,11 @@ impl FunctionValue {
         }
     }
 
+    /// Gets the address of the function.
+    pub fn as_pointer_value(&self) -> PointerValue {
+        PointerValue::new(self.val.val)
+    }
+
     ///
     pub fn count_params(&self) -> u32 {
         unsafe { LLVMCountParams(self.val.val) }
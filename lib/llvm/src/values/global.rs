@@ -0,0 +1,50 @@
+use super::Value;
+
+use llvm_sys::core::{LLVMSetGlobalConstant, LLVMSetInitializer, LLVMSetLinkage};
+use llvm_sys::prelude::LLVMValueRef;
+
+use crate::Linkage;
+
+use super::{AsValueRef, BasicValue, PointerValue};
+
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GlobalValue {
+    pub(crate) val: Value,
+}
+
+impl GlobalValue {
+    pub(crate) fn new(val: LLVMValueRef) -> Self {
+        assert!(!val.is_null());
+
+        Self {
+            val: Value::new(val),
+        }
+    }
+
+    ///
+    pub fn set_initializer(&self, value: &BasicValue) {
+        unsafe { LLVMSetInitializer(self.val.val, value.as_ref()) }
+    }
+
+    ///
+    pub fn set_constant(&self, is_constant: bool) {
+        unsafe { LLVMSetGlobalConstant(self.val.val, is_constant as _) }
+    }
+
+    ///
+    pub fn set_linkage(&self, linkage: Linkage) {
+        unsafe { LLVMSetLinkage(self.val.val, linkage.into()) }
+    }
+
+    /// Gets the address of the global.
+    pub fn as_pointer_value(&self) -> PointerValue {
+        PointerValue::new(self.val.val)
+    }
+}
+
+impl AsValueRef for GlobalValue {
+    fn as_ref(&self) -> LLVMValueRef {
+        self.val.val
+    }
+}
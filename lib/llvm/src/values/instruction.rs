@@ -1,6 +1,6 @@
 use super::Value;
 
-use llvm_sys::core::LLVMIsAInstruction;
+use llvm_sys::core::{LLVMIsAInstruction, LLVMSetAlignment};
 use llvm_sys::prelude::LLVMValueRef;
 
 use super::AsValueRef;
@@ -20,6 +20,11 @@ impl InstructionValue {
             val: Value::new(instruction_value),
         }
     }
+
+    /// Sets the alignment in bytes a load or store can assume for its pointer.
+    pub fn set_alignment(&self, alignment: u32) {
+        unsafe { LLVMSetAlignment(self.val.val, alignment) }
+    }
 }
 
 impl AsValueRef for InstructionValue {
@@ -2,8 +2,10 @@ mod array;
 mod enums;
 mod float;
 mod function;
+mod global;
 mod instruction;
 mod int;
+mod phi;
 mod pointer;
 mod struct_;
 mod traits;
@@ -14,8 +16,10 @@ pub use array::ArrayValue;
 pub use enums::BasicValue;
 pub use float::FloatValue;
 pub use function::FunctionValue;
+pub use global::GlobalValue;
 pub use instruction::{InstructionOpcode, InstructionValue};
 pub use int::IntValue;
+pub use phi::PhiValue;
 pub use pointer::PointerValue;
 pub use struct_::StructValue;
 pub(crate) use traits::{AsValueRef, FloatMathValue, IntMathValue, PointerMathValue};
@@ -0,0 +1,54 @@
+use super::Value;
+
+use llvm_sys::core::LLVMAddIncoming;
+use llvm_sys::prelude::{LLVMBasicBlockRef, LLVMValueRef};
+
+use crate::BasicBlock;
+
+use super::{AsValueRef, BasicValue};
+
+///
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PhiValue {
+    pub(crate) val: Value,
+}
+
+impl PhiValue {
+    pub(crate) fn new(val: LLVMValueRef) -> Self {
+        assert!(!val.is_null());
+
+        Self {
+            val: Value::new(val),
+        }
+    }
+
+    /// Adds a value for each predecessor block specified.
+    pub fn add_incoming(&self, incoming: &[(BasicValue, BasicBlock)]) {
+        let mut values: Vec<LLVMValueRef> =
+            incoming.iter().map(|(value, _)| value.as_ref()).collect();
+        let mut blocks: Vec<LLVMBasicBlockRef> = incoming
+            .iter()
+            .map(|(_, block)| block.basic_block)
+            .collect();
+
+        unsafe {
+            LLVMAddIncoming(
+                self.val.val,
+                values.as_mut_ptr(),
+                blocks.as_mut_ptr(),
+                incoming.len() as _,
+            );
+        }
+    }
+
+    /// Gets the phi node as a value that can be used by other instructions.
+    pub fn as_basic_value(&self) -> BasicValue {
+        BasicValue::new(self.val.val)
+    }
+}
+
+impl AsValueRef for PhiValue {
+    fn as_ref(&self) -> LLVMValueRef {
+        self.val.val
+    }
+}
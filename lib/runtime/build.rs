@@ -0,0 +1,10 @@
+//! Compiles the C parts of trap handling, see `src/trap/unix.c`.
+fn main() {
+    if std::env::var_os("CARGO_CFG_UNIX").is_some() {
+        println!("cargo:rerun-if-changed=src/trap/unix.c");
+
+        cc::Build::new()
+            .file("src/trap/unix.c")
+            .compile("wasmo_trap");
+    }
+}
@@ -1,80 +1,557 @@
-use crate::data::ModuleData;
-use crate::func::Func;
-use wasmo_llvm::Module as LLVMModule;
 use crate::context::InstanceContext;
-use crate::options::Options;
+use crate::data::{Data, InitExpression, ModuleData, ResizableLimits};
+use crate::errors::{InstantiationError, LinkError, RuntimeError};
+use crate::func::{Trampoline, TypedFunc, WasmParams, WasmResults};
+use crate::imports::{Extern, HostContext, Linker};
+use crate::memory::Memory;
+use crate::trap::{catch_traps, register_code, CodeRegistration};
+use crate::types::{GlobalPtr, TablePtr};
 
-use std::sync::{Arc, RwLock};
+use std::fs;
+use std::io;
 use std::marker::PhantomData;
+use std::path::Path;
+
+#[cfg(feature = "jit")]
+use crate::context::{
+    CODE_END_NAME, FUNCTION_NAME_PREFIX, INITIALIZE_FUNCTION_NAME, TRAMPOLINE_NAME_PREFIX,
+};
+#[cfg(feature = "jit")]
+use crate::func::Func;
+#[cfg(feature = "jit")]
+use crate::types::{ExportKind, FuncPtr};
+#[cfg(feature = "jit")]
+use std::{
+    fmt,
+    mem::transmute,
+    rc::Rc,
+    sync::{Arc, RwLock},
+};
+#[cfg(feature = "jit")]
+use wasmo_llvm::{CompilerResult, Module as LLVMModule, OptimizationLevel, ORCJIT};
 
 /// Container<T> is a type that can either be Instance or Module. Container<T> represents a shared structural
 /// representation between these two types. With traits and generics, a specific type of container,
 /// e.g. Container<Instance<AOT>>, can have it's own specific implementation.
 ///
 /// #### NOTE
-/// `context`'s InstanceContext is not optional (i.e. Option<InstanceContext>) here, even though it is not
-/// needed at codegen phase, because we may need a stable way of determining the offsets of `ctx` and `data`
-/// in the Module<T>.
+/// `context` is only set for instances. Modules don't need an InstanceContext, each instance builds its
+/// own from `data` when it is instantiated.
 ///
 /// `module` is optional because it is not always needed. JIT Eager and AOT discards it after instantiation.
 /// `module` is Arc because LLVMModule is shared between instances and since they can read/write to it RwLock is
 /// required. Instances live on seperate execution threads BTW.
+///
+/// `code` is the machine code of a jitted module. A module shares it with its instances.
+/// `object` is the relocatable object code of an AOT module.
+/// `host_contexts` are what the host functions an instance imports are called with.
+/// `externs` are what an instance imports. The InstanceContext points into them, so the instance
+/// keeps them alive.
+/// `code_registration` keeps the code of an instance registered, so faults in it are traps.
+///
+/// `code` and `module` only exist with the `jit` feature, executables link the runtime without it.
 #[repr(C)]
 #[derive(Debug)]
 pub struct Container<T> {
-    context: InstanceContext,
+    context: Option<InstanceContext>,
     data: ModuleData,
+    #[cfg(feature = "jit")]
+    code: Option<Rc<Code>>,
+    object: Option<Vec<u8>>,
+    #[allow(clippy::vec_box)] // The InstanceContext points to the boxes
+    host_contexts: Vec<Box<HostContext>>,
+    externs: Vec<Extern>,
+    code_registration: Option<CodeRegistration>,
+    #[cfg(feature = "jit")]
     module: Option<Arc<RwLock<LLVMModule>>>,
     phantom: PhantomData<T>,
 }
 
-/// Implementation for all Container<T>'s where T is a ContainerType
-impl<T: ContainerType> Container<T> {
-    pub fn new() -> Self {
+/// Signature of the generated initialization function, see `INITIALIZE_FUNCTION_NAME`.
+pub type Initialize = unsafe extern "C" fn(*mut usize);
+
+/// What `resolve_imports` returns: the numbers of imported memories, tables, globals and
+/// functions, the contexts of imported host functions, and the imported entities.
+type ResolvedImports = ([usize; 4], Vec<Box<HostContext>>, Vec<Extern>);
+
+/// The JIT owns the module the code is compiled from, and frees the code when it is dropped.
+#[cfg(feature = "jit")]
+struct Code {
+    #[allow(dead_code)] // Only kept for its drop
+    jit: ORCJIT,
+    initialize: Initialize,
+    trampolines: Vec<Trampoline>, // Indexed by type index
+}
+
+/// Implementation for all Container<Module<T>>'s where T is a CompileType. Only instantiation creates
+/// instances, as it sets their InstanceContext.
+#[cfg(feature = "jit")]
+impl<T: CompileType> Container<Module<T>> {
+    pub fn from_llvm_module(module: LLVMModule, data: ModuleData) -> Self {
         Self {
-            context: InstanceContext::new(),
-            data: ModuleData::new(),
-            module: None,
+            context: None,
+            data,
+            code: None,
+            object: None,
+            host_contexts: Vec::new(),
+            externs: Vec::new(),
+            code_registration: None,
+            module: Some(Arc::new(RwLock::new(module))),
             phantom: PhantomData,
         }
     }
+}
 
-    pub fn from_llvm_module(module: LLVMModule) -> Self {
+impl Container<Module<AOT>> {
+    /// Creates a module from the object code emitted for an LLVM module and the runtime data
+    /// generated along with it.
+    pub fn from_object(object: Vec<u8>, data: ModuleData) -> Self {
         Self {
-            context: InstanceContext::new(),
-            data: ModuleData::new(),
-            module: Some(Arc::new(RwLock::new(module))),
+            context: None,
+            data,
+            #[cfg(feature = "jit")]
+            code: None,
+            object: Some(object),
+            host_contexts: Vec::new(),
+            externs: Vec::new(),
+            code_registration: None,
+            #[cfg(feature = "jit")]
+            module: None,
             phantom: PhantomData,
         }
     }
-}
 
+    /// Gets the relocatable object code of the module. It exports the wasm functions, the
+    /// trampolines and the initialization function, see `FUNCTION_NAME_PREFIX` and co. Modules
+    /// compiled with `CodegenOptions::generate_main` also export `main`.
+    pub fn object(&self) -> &[u8] {
+        // Guarantee: AOT modules are only created by `from_object`.
+        self.object.as_ref().unwrap()
+    }
 
-impl Container<Module<AOT>> {
-    pub fn instantiate() -> Module<AOT> {
-        unimplemented!()
+    /// Writes the object code to `path`, e.g. for a system linker.
+    pub fn write_object(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.object())
+    }
+}
+
+impl<T: CompileType> Container<Instance<T>> {
+    /// Gets the InstanceContext generated code of the instance is called with.
+    fn context(&self) -> &InstanceContext {
+        // Guarantee: instances are only created by `instantiate_with`, which sets the context.
+        self.context.as_ref().unwrap()
     }
 }
 
 impl Container<Instance<AOT>> {
-    pub fn instantiate() -> Module<AOT> {
-        unimplemented!()
+    /// Creates an instance of the module an executable is compiled from, see
+    /// `executable::wasmo_main`. `data` must hold the function pointers of the module and
+    /// `initialize` must be its initialization function.
+    pub fn instantiate(
+        data: ModuleData,
+        initialize: Initialize,
+        linker: &Linker,
+    ) -> Result<Self, InstantiationError> {
+        Self::instantiate_with(data, initialize, linker)
+    }
+
+    /// Gets a function by its index in the function index space, typed as taking `Params` and
+    /// returning `Results`.
+    ///
+    /// ###### Safety
+    /// The function must have that signature. Executables don't carry the types of their
+    /// functions, it is checked when they are compiled.
+    pub unsafe fn get_typed_func_unchecked<Params, Results>(
+        &self,
+        function_index: u32,
+    ) -> Option<TypedFunc<'_, Params, Results>>
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+    {
+        let addr = *self.context().functions().get(function_index as usize)?;
+        let context = self.context().function_contexts()[function_index as usize];
+
+        Some(TypedFunc::new(addr, context))
+    }
+
+    /// Calls a function by its index in the function index space through `trampoline`, with
+    /// arguments and results in 64-bit cells, see `Func::call`.
+    ///
+    /// Traps are returned as `RuntimeError::Trap`, indices out of bounds as
+    /// `RuntimeError::FunctionNotFound`.
+    ///
+    /// ###### Safety
+    /// `trampoline` must be the trampoline of the type of the function. `args` and `results` must
+    /// have as many cells as it takes and returns.
+    pub unsafe fn call_with_trampoline(
+        &self,
+        function_index: u32,
+        trampoline: Trampoline,
+        args: *const u64,
+        results: *mut u64,
+    ) -> Result<(), RuntimeError> {
+        let addr = *self
+            .context()
+            .functions()
+            .get(function_index as usize)
+            .ok_or(RuntimeError::FunctionNotFound(function_index))?;
+        let context = self.context().function_contexts()[function_index as usize];
+
+        catch_traps(|| trampoline(context, addr, args, results))
+    }
+
+    /// Gets a memory by its index in the memory index space. Memories are owned by the data of
+    /// the instance, or shared with the linker and the other instances that import them.
+    ///
+    /// ###### Safety
+    /// No other reference to the memory may be alive while the returned one is, e.g. one from
+    /// another instance importing it, or from `Caller::memory`.
+    pub unsafe fn get_memory_mut(&mut self, memory_index: u32) -> Option<&mut Memory> {
+        let memory = *self.context().memories().get(memory_index as usize)?;
+
+        // Guarantee: the instance keeps the memories it imports alive.
+        Some(&mut *memory)
+    }
+
+    /// Gets the cell of a global by its index in the global index space.
+    pub fn get_global(&self, global_index: u32) -> Option<GlobalPtr> {
+        self.context().globals().get(global_index as usize).cloned()
     }
 }
 
+#[cfg(feature = "jit")]
 impl Container<Module<JITEager>> {
-    pub fn create_aot_with_llvm_module(module: LLVMModule, options: &Options) -> Container<Module<AOT>> {
-        Container::from_llvm_module(module)
+    /// Compiles `module` to machine code and fills the function pointers of `data`. `data` must
+    /// be the runtime data generated along with `module`.
+    pub fn compile(module: LLVMModule, mut data: ModuleData) -> CompilerResult<Self> {
+        let mut jit = ORCJIT::create_native(OptimizationLevel::Default)?;
+        jit.add_module(module)?;
+
+        let imported_function_count = data.imported_function_count() as usize;
+        for (index, function) in data.locals.functions.iter_mut().enumerate() {
+            let name = format!(
+                "{}{}",
+                FUNCTION_NAME_PREFIX,
+                imported_function_count + index
+            );
+            function.ptr = jit.get_symbol_address(&name)? as FuncPtr;
+        }
+        data.code_end = jit.get_symbol_address(CODE_END_NAME)? as FuncPtr;
+
+        let trampolines = (0..data.locals.types.len())
+            .map(|type_index| {
+                let name = format!("{}{}", TRAMPOLINE_NAME_PREFIX, type_index);
+                let address = jit.get_symbol_address(&name)? as usize;
+
+                // Guarantee: trampolines are generated with the `Trampoline` signature.
+                Ok(unsafe { transmute::<usize, Trampoline>(address) })
+            })
+            .collect::<CompilerResult<Vec<_>>>()?;
+
+        let address = jit.get_symbol_address(INITIALIZE_FUNCTION_NAME)? as usize;
+        // Guarantee: the initialization function only takes the InstanceContext buffer.
+        let initialize = unsafe { transmute::<usize, Initialize>(address) };
+
+        let code = Code {
+            jit,
+            initialize,
+            trampolines,
+        };
+
+        Ok(Self {
+            context: None,
+            data,
+            code: Some(Rc::new(code)),
+            object: None,
+            host_contexts: Vec::new(),
+            externs: Vec::new(),
+            code_registration: None,
+            module: None,
+            phantom: PhantomData,
+        })
     }
 
-    pub fn instantiate() -> Module<AOT> {
-        unimplemented!()
+    /// Creates an instance with its own memories, tables and globals, then initializes them and
+    /// runs the start function. Imports are resolved against what `linker` defines.
+    pub fn instantiate(
+        &self,
+        linker: &Linker,
+    ) -> Result<Container<Instance<JITEager>>, InstantiationError> {
+        // Guarantee: jitted modules are only created by `compile`.
+        let code = Rc::clone(self.code.as_ref().unwrap());
+
+        let mut instance =
+            Container::instantiate_with(self.data.template(), code.initialize, linker)?;
+        instance.code = Some(code);
+
+        Ok(instance)
     }
 }
 
+impl<T: ContainerType> Container<T> {
+    /// Resolves the imports of `data` against what `linker` defines, allocates its memories,
+    /// tables and globals, then runs `initialize` to initialize them and run the start function.
+    fn instantiate_with(
+        mut data: ModuleData,
+        initialize: Initialize,
+        linker: &Linker,
+    ) -> Result<Self, InstantiationError> {
+        let mut context = InstanceContext::new(&data);
+
+        let (imported_counts, host_contexts, externs) =
+            Self::resolve_imports(&data, linker, &mut context)?;
+        let [memory_offset, table_offset, global_offset, function_offset] = imported_counts;
+
+        for (index, memory) in data.locals.memories.iter_mut().enumerate() {
+            let memory = memory.allocate().map_err(InstantiationError::Memory)?;
+            context.memories_mut()[memory_offset + index] = memory as *mut _;
+        }
+
+        for (index, table) in data.locals.tables.iter_mut().enumerate() {
+            let table = table.allocate();
+            context.tables_mut()[table_offset + index] = TablePtr::new(table.base_ptr, table.size);
+        }
+
+        for (index, global) in data.locals.globals.iter_mut().enumerate() {
+            // Guarantee: local globals always have an init expression.
+            let value = match global.init().unwrap() {
+                InitExpression::Const(bits) => *bits,
+                // Init expressions can only get imported globals, which are resolved by now.
+                InitExpression::GetGlobal(index) => unsafe { *context.globals()[*index as usize] },
+            };
+            context.globals_mut()[global_offset + index] = global.allocate(value);
+        }
+
+        for (index, function) in data.locals.functions.iter().enumerate() {
+            context.functions_mut()[function_offset + index] = function.ptr;
+        }
+
+        // Registered before the start function runs.
+        let functions = data.locals.functions.iter();
+        let code_registration = register_code(
+            functions.map(|function| (function.ptr as usize, function.body_offset)),
+            data.code_end as usize,
+        );
+
+        catch_traps(|| unsafe { initialize(context.as_ptr()) }).map_err(|error| match error {
+            RuntimeError::Trap { code, wasm_offset } => {
+                InstantiationError::Trap { code, wasm_offset }
+            }
+            // Guarantee: `catch_traps` only returns traps.
+            _ => unreachable!(),
+        })?;
+
+        Ok(Container {
+            context: Some(context),
+            data,
+            #[cfg(feature = "jit")]
+            code: None,
+            object: None,
+            host_contexts,
+            externs,
+            code_registration: Some(code_registration),
+            #[cfg(feature = "jit")]
+            module: None,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Type checks the imports of `data` against what `linker` defines and stores the imported
+    /// entities in the InstanceContext. Returns the number of imported memories, tables, globals
+    /// and functions, the contexts of imported host functions, and the imported entities.
+    fn resolve_imports(
+        data: &ModuleData,
+        linker: &Linker,
+        context: &mut InstanceContext,
+    ) -> Result<ResolvedImports, InstantiationError> {
+        let [mut memories, mut tables, mut globals, mut functions] = [0; 4];
+        let mut host_contexts = Vec::new();
+        let mut externs = Vec::new();
+        let mut errors = Vec::new();
+
+        for import in data.imports.iter() {
+            let (module, field) = (import.module.clone(), import.field.clone());
+            let defined = match linker.get(&import.module, &import.field) {
+                Some(defined) => defined,
+                None => {
+                    errors.push(LinkError::UnknownImport { module, field });
+                    continue;
+                }
+            };
+
+            // Guarantee: no wasm code runs while imports are resolved, so nothing writes to the
+            // shared entities.
+            let matches = match (&import.data, defined) {
+                (Data::Memory(expected), Extern::Memory(shared)) => {
+                    let defined = unsafe { &mut *shared.as_ptr() };
+                    let limits = defined.limits().maximum;
+                    // Guarantee: the linker allocates memories when it defines them.
+                    let memory = defined.memory_mut().unwrap();
+                    let found = ResizableLimits::new(memory.size(), limits);
+                    let index = memories;
+                    memories += 1;
+
+                    if Self::limits_match(expected.limits(), &found) {
+                        context.memories_mut()[index] = memory as *mut Memory;
+                        true
+                    } else {
+                        let expected = *expected.limits();
+                        errors.push(LinkError::IncompatibleLimits {
+                            module,
+                            field,
+                            expected,
+                            found,
+                        });
+                        false
+                    }
+                }
+                (Data::Table(expected), Extern::Table(shared)) => {
+                    let defined = unsafe { &*shared.as_ptr() };
+                    let found = ResizableLimits::new(
+                        defined.elements().len() as u32,
+                        defined.limits().maximum,
+                    );
+                    let index = tables;
+                    tables += 1;
+
+                    if Self::limits_match(expected.limits(), &found) {
+                        let table = defined.ptr();
+                        context.tables_mut()[index] = TablePtr::new(table.base_ptr, table.size);
+                        true
+                    } else {
+                        let expected = *expected.limits();
+                        errors.push(LinkError::IncompatibleLimits {
+                            module,
+                            field,
+                            expected,
+                            found,
+                        });
+                        false
+                    }
+                }
+                (Data::Global(expected), Extern::Global(shared)) => {
+                    let defined = unsafe { &*shared.as_ptr() };
+                    let expected = (expected.ty(), expected.is_mutable());
+                    let found = (defined.ty(), defined.is_mutable());
+                    let index = globals;
+                    globals += 1;
+
+                    if expected == found {
+                        context.globals_mut()[index] = defined.ptr();
+                        true
+                    } else {
+                        errors.push(LinkError::IncompatibleGlobalType {
+                            module,
+                            field,
+                            expected,
+                            found,
+                        });
+                        false
+                    }
+                }
+                (Data::Func(expected), Extern::Func(defined)) => {
+                    let expected = &data.locals.types[expected.type_index as usize];
+                    let index = functions;
+                    functions += 1;
+
+                    if expected == defined.ty() {
+                        let mut host_context = Box::new(
+                            defined.context(context.as_ptr(), data.memory_count() as usize),
+                        );
+                        context.functions_mut()[index] = defined.entry();
+                        context.function_contexts_mut()[index] =
+                            &mut *host_context as *mut HostContext as *mut usize;
+                        host_contexts.push(host_context);
+                        true
+                    } else {
+                        let (expected, found) = (expected.clone(), defined.ty().clone());
+                        errors.push(LinkError::IncompatibleFuncType {
+                            module,
+                            field,
+                            expected,
+                            found,
+                        });
+                        false
+                    }
+                }
+                _ => {
+                    errors.push(LinkError::IncompatibleKind { module, field });
+                    false
+                }
+            };
+
+            if matches {
+                externs.push(defined.clone());
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(InstantiationError::Link(errors));
+        }
+
+        Ok((
+            [memories, tables, globals, functions],
+            host_contexts,
+            externs,
+        ))
+    }
+
+    /// Checks that a memory or table with `found` limits can be imported as one with `expected`
+    /// limits.
+    fn limits_match(expected: &ResizableLimits, found: &ResizableLimits) -> bool {
+        let maximum_matches = match (expected.maximum, found.maximum) {
+            (None, _) => true,
+            (Some(expected), Some(found)) => found <= expected,
+            (Some(_), None) => false,
+        };
+
+        found.minimum >= expected.minimum && maximum_matches
+    }
+}
+
+#[cfg(feature = "jit")]
 impl Container<Instance<JITEager>> {
-    pub fn get_func<'a>() -> Func<'a> {
-        unimplemented!()
+    /// Gets an exported function.
+    pub fn get_func(&self, name: &str) -> Option<Func<'_>> {
+        let function_index = match self.data.exports.get(name)? {
+            ExportKind::Function(index) => *index,
+            _ => return None,
+        };
+
+        let type_index = self.data.function_type_index(function_index)?;
+        let ty = self.data.function_type(function_index)?;
+        // Guarantee: instances of jitted modules always have code.
+        let trampoline = self.code.as_ref().unwrap().trampolines[type_index as usize];
+        let addr = self.context().functions()[function_index as usize];
+        let context = self.context().function_contexts()[function_index as usize];
+
+        Some(Func::new(addr, trampoline, context, ty))
+    }
+
+    /// Gets an exported function and checks that it takes `Params` and returns `Results`.
+    pub fn get_typed_func<Params, Results>(
+        &self,
+        name: &str,
+    ) -> Result<TypedFunc<'_, Params, Results>, RuntimeError>
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+    {
+        let func = self
+            .get_func(name)
+            .ok_or_else(|| RuntimeError::ExportNotFound(name.to_string()))?;
+
+        func.typed()
+    }
+}
+
+#[cfg(feature = "jit")]
+impl fmt::Debug for Code {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Code")
+            .field("trampolines", &self.trampolines.len())
+            .finish()
     }
 }
 
@@ -98,13 +575,177 @@ pub struct Instance<T>(PhantomData<T>);
 impl<T: CompileType> ContainerType for Module<T> {}
 impl<T: CompileType> ContainerType for Instance<T> {}
 
-
 /// This module is the public interface of this file.
 pub mod module {
-    pub use super::{JITEager, AOT, Container};
+    pub use super::{Container, Initialize, JITEager, AOT};
 
     pub type ModuleAOT = Container<super::Module<AOT>>;
     pub type InstanceAOT = Container<super::Instance<AOT>>;
     pub type Module = Container<super::Module<JITEager>>;
     pub type Instance = Container<super::Instance<JITEager>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{FuncData, GlobalData, MemoryData, TableData};
+    use crate::types::{FuncType, Value, ValueType};
+    use std::ptr::{null, null_mut};
+
+    unsafe extern "C" fn initialize(_: *mut usize) {}
+
+    fn add_import(data: &mut ModuleData, field: &str, import: Data) {
+        data.add_import("env".to_string(), field.to_string(), import);
+    }
+
+    fn resolve_imports(data: &ModuleData, linker: &Linker) -> InstanceContext {
+        let mut context = InstanceContext::new(data);
+        Container::<Module<JITEager>>::resolve_imports(data, linker, &mut context).unwrap();
+        context
+    }
+
+    fn link_errors(data: ModuleData, linker: &Linker) -> Vec<LinkError> {
+        let mut context = InstanceContext::new(&data);
+        match Container::<Module<JITEager>>::resolve_imports(&data, linker, &mut context) {
+            Err(InstantiationError::Link(errors)) => errors,
+            Err(error) => panic!("Expected link errors, got {:?}", error),
+            Ok(_) => panic!("Expected link errors"),
+        }
+    }
+
+    #[test]
+    fn missing_imports_fail_to_link() {
+        let mut data = ModuleData::new();
+        data.add_type(FuncType::new(vec![], vec![]));
+        add_import(&mut data, "missing", Data::Func(FuncData::new(null(), 0)));
+
+        match &link_errors(data, &Linker::new())[..] {
+            [LinkError::UnknownImport { module, field }] => {
+                assert_eq!((module.as_str(), field.as_str()), ("env", "missing"));
+            }
+            errors => panic!("Unexpected link errors: {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn mismatched_imports_fail_to_link() {
+        let mut data = ModuleData::new();
+        data.add_type(FuncType::new(vec![ValueType::I32], vec![]));
+        add_import(&mut data, "log", Data::Func(FuncData::new(null(), 0)));
+        let limits = ResizableLimits::new(2, None);
+        add_import(&mut data, "memory", Data::Memory(MemoryData::new(limits)));
+        let global = GlobalData::import(ValueType::I64, true);
+        add_import(&mut data, "counter", Data::Global(global));
+        let limits = ResizableLimits::new(1, None);
+        add_import(&mut data, "table", Data::Table(TableData::new(limits)));
+
+        let mut linker = Linker::new();
+        linker.func("env", "log", |_: i64| {});
+        linker
+            .memory("env", "memory", ResizableLimits::new(1, None))
+            .unwrap();
+        linker.global("env", "counter", Value::I64(0), false);
+        linker.global("env", "table", Value::I32(0), false);
+
+        match &link_errors(data, &linker)[..] {
+            [LinkError::IncompatibleFuncType {
+                expected: expected_func,
+                found: found_func,
+                ..
+            }, LinkError::IncompatibleLimits {
+                expected: expected_limits,
+                found: found_limits,
+                ..
+            }, LinkError::IncompatibleGlobalType {
+                expected: expected_global,
+                found: found_global,
+                ..
+            }, LinkError::IncompatibleKind { field, .. }] => {
+                assert_eq!(expected_func.params(), &[ValueType::I32][..]);
+                assert_eq!(found_func.params(), &[ValueType::I64][..]);
+                assert_eq!((expected_limits.minimum, found_limits.minimum), (2, 1));
+                assert_eq!(*expected_global, (ValueType::I64, true));
+                assert_eq!(*found_global, (ValueType::I64, false));
+                assert_eq!(field, "table");
+            }
+            errors => panic!("Unexpected link errors: {:?}", errors),
+        }
+    }
+
+    #[test]
+    fn imports_are_stored_in_the_instance_context() {
+        let mut data = ModuleData::new();
+        let limits = ResizableLimits::new(1, None);
+        add_import(&mut data, "memory", Data::Memory(MemoryData::new(limits)));
+        let global = GlobalData::import(ValueType::I32, true);
+        add_import(&mut data, "counter", Data::Global(global));
+
+        let mut linker = Linker::new();
+        linker
+            .memory("env", "memory", ResizableLimits::new(1, Some(2)))
+            .unwrap()
+            .write(0, &[42])
+            .unwrap();
+        linker.global("env", "counter", Value::I32(7), true);
+
+        let context = resolve_imports(&data, &linker);
+        let mut byte = [0];
+        unsafe { &*context.memories()[0] }.read(0, &mut byte).unwrap();
+        assert_eq!(byte, [42]);
+        assert_eq!(unsafe { *context.globals()[0] }, 7);
+    }
+
+    #[test]
+    fn instances_keep_their_imports_alive() {
+        let mut data = ModuleData::new();
+        let limits = ResizableLimits::new(1, None);
+        add_import(&mut data, "memory", Data::Memory(MemoryData::new(limits)));
+        let global = GlobalData::import(ValueType::I32, true);
+        add_import(&mut data, "counter", Data::Global(global));
+
+        let mut linker = Linker::new();
+        linker
+            .memory("env", "memory", ResizableLimits::new(1, Some(2)))
+            .unwrap()
+            .write(0, &[42])
+            .unwrap();
+        linker.global("env", "counter", Value::I32(7), true);
+
+        let mut instance =
+            Container::<Instance<AOT>>::instantiate(data, initialize, &linker).unwrap();
+        drop(linker);
+
+        let memory = unsafe { instance.get_memory_mut(0) }.unwrap();
+        let mut byte = [0];
+        memory.read(0, &mut byte).unwrap();
+        assert_eq!(byte, [42]);
+        assert_eq!(memory.grow(1), Some(1));
+        assert_eq!(unsafe { *instance.get_global(0).unwrap() }, 7);
+    }
+
+    #[test]
+    fn only_instances_have_an_instance_context() {
+        let module = Container::<Module<AOT>>::from_object(Vec::new(), ModuleData::new());
+        assert!(module.context.is_none());
+
+        let instance =
+            Container::<Instance<AOT>>::instantiate(ModuleData::new(), initialize, &Linker::new())
+                .unwrap();
+        assert!(instance.context.is_some());
+    }
+
+    #[test]
+    fn calls_of_missing_functions_fail() {
+        unsafe extern "C" fn trampoline(_: *mut usize, _: FuncPtr, _: *const u64, _: *mut u64) {}
+
+        let instance =
+            Container::<Instance<AOT>>::instantiate(ModuleData::new(), initialize, &Linker::new())
+                .unwrap();
+
+        let result = unsafe { instance.call_with_trampoline(0, trampoline, null(), null_mut()) };
+        match result {
+            Err(RuntimeError::FunctionNotFound(0)) => {}
+            result => panic!("Expected FunctionNotFound, found {:?}", result),
+        }
+    }
+}
@@ -1,5 +1,52 @@
+use crate::data::ModuleData;
+use crate::intrinsics::{
+    wasmo_memory_grow, wasmo_memory_size, wasmo_trap, INTRINSIC_COUNT, MEMORY_GROW_INDEX,
+    MEMORY_SIZE_INDEX, TRAP_INDEX,
+};
+use crate::memory::Memory;
+use crate::types::{FuncPtr, GlobalPtr, TablePtr};
 
-use crate::types::{FuncPtr, GlobalPtr, MemoryPtr, TablePtr};
+use std::mem::size_of;
+use std::ptr;
+use std::slice;
+
+/// Symbol of the generated function that initializes the tables and memories of an instance. It
+/// takes the InstanceContext buffer and traps if a segment doesn't fit.
+pub const INITIALIZE_FUNCTION_NAME: &str = "wasmo_initialize";
+
+/// Symbol of an empty function generated after the wasm functions, and before any other function
+/// with a body. It marks where their code ends, see `ModuleData::code_end`.
+pub const CODE_END_NAME: &str = "wasmo_code_end";
+
+/// Prefix of the symbols of generated wasm functions. The suffix is the index of the function in
+/// the function index space.
+pub const FUNCTION_NAME_PREFIX: &str = "wasm_function_";
+
+/// Prefix of the symbols of the generated trampolines the runtime calls wasm functions through.
+/// The suffix is the index of the function type. A trampoline takes the InstanceContext buffer,
+/// the function, and arrays of 64-bit argument and result cells.
+pub const TRAMPOLINE_NAME_PREFIX: &str = "wasmo_trampoline_";
+
+/// Index of the memories array pointer in the InstanceContext header.
+pub const MEMORIES_FIELD: u32 = 0;
+
+/// Index of the tables array pointer in the InstanceContext header.
+pub const TABLES_FIELD: u32 = 1;
+
+/// Index of the globals array pointer in the InstanceContext header.
+pub const GLOBALS_FIELD: u32 = 2;
+
+/// Index of the functions array pointer in the InstanceContext header.
+pub const FUNCTIONS_FIELD: u32 = 3;
+
+/// Index of the intrinsic functions array pointer in the InstanceContext header.
+pub const INTRINSIC_FUNCTIONS_FIELD: u32 = 4;
+
+/// Index of the function contexts array pointer in the InstanceContext header.
+pub const FUNCTION_CONTEXTS_FIELD: u32 = 5;
+
+/// Number of fields in the InstanceContext header.
+pub const HEADER_FIELD_COUNT: usize = 6;
 
 /// InstanceContext holds pointers for accessing all the memories, tables, functions,
 /// and globals specified for an instance. It contains both local and imported elements
@@ -13,19 +60,29 @@ use crate::types::{FuncPtr, GlobalPtr, MemoryPtr, TablePtr};
 ///
 /// ```rust
 /// struct InstanceContext {
-///     memories_offset: usize,
-///     tables_offset: usize,
-///     globals_offset: usize,
-///     functions_offset: usize,
-///     intrinsic_function_offset: usize,
-///     memories: dyn [*mut u8; memory_count],
-///     tables: dyn [*mut u32; table_count],
-///     globals: dyn [*mut u64; global_count],
-///     functions: dyn [*const (); function_count],
-///     intrinsic_functions: dyn [*const (); intrinsic_function_count],
+///     memories: *mut *mut Memory,
+///     tables: *mut TablePtr,
+///     globals: *mut *mut u64,
+///     functions: *mut *const (),
+///     intrinsic_functions: *mut *const (),
+///     function_contexts: *mut *mut usize,
+///     memories_array: dyn [*mut Memory; memory_count],
+///     tables_array: dyn [TablePtr; table_count],
+///     globals_array: dyn [*mut u64; global_count],
+///     functions_array: dyn [*const (); function_count],
+///     intrinsic_functions_array: dyn [*const (); intrinsic_function_count],
+///     function_contexts_array: dyn [*mut usize; function_count],
 /// }
 /// ```
 ///
+/// A function is called with its entry in the function contexts array first. That is the
+/// InstanceContext buffer of the instance defining the function, or the context of a host
+/// function.
+///
+/// The header fields point into the arrays that follow them in the same buffer. Generated code
+/// sees the header as the LLVM struct built by `ModuleGenerator::create_instance_context_type`,
+/// with the fields at `MEMORIES_FIELD`, `TABLES_FIELD` and so on.
+///
 /// With the structure above, loading a value from InstanceContext is matter of a single lea instruction.
 /// It effectively improves cache hit and reduces pointer indirections. Offsets are statically known,
 /// so the lea operands can are mostly immediate values.
@@ -55,14 +112,166 @@ use crate::types::{FuncPtr, GlobalPtr, MemoryPtr, TablePtr};
 #[derive(Debug)]
 pub struct InstanceContext {
     buffer: *mut usize,
+    buffer_length: usize,                 // In words
+    lengths: [usize; HEADER_FIELD_COUNT], // Number of elements in each array
 }
 
-
 /// Because of its nature of type unsafety, InstanceContext only exposes type safe
 /// interface to the public. You cannot meddle with the buffer pointer directly.
 impl InstanceContext {
+    /// Allocates a buffer with room for all the memories, tables, globals and functions of
+    /// `data`, imported ones included. Array entries start out null, except for the intrinsic
+    /// functions and the contexts of local functions, which are the buffer itself.
+    pub fn new(data: &ModuleData) -> Self {
+        let mut lengths = [0; HEADER_FIELD_COUNT];
+        lengths[MEMORIES_FIELD as usize] = data.memory_count() as usize;
+        lengths[TABLES_FIELD as usize] = data.table_count() as usize;
+        lengths[GLOBALS_FIELD as usize] = data.global_count() as usize;
+        lengths[FUNCTIONS_FIELD as usize] = data.function_count() as usize;
+        lengths[INTRINSIC_FUNCTIONS_FIELD as usize] = INTRINSIC_COUNT;
+        lengths[FUNCTION_CONTEXTS_FIELD as usize] = data.function_count() as usize;
+
+        // Tables hold `TablePtr`s, every other array holds pointers.
+        let word_counts = lengths
+            .iter()
+            .enumerate()
+            .map(|(field, length)| match field as u32 {
+                TABLES_FIELD => length * size_of::<TablePtr>() / size_of::<usize>(),
+                _ => *length,
+            })
+            .collect::<Vec<_>>();
+
+        let buffer_length = HEADER_FIELD_COUNT + word_counts.iter().sum::<usize>();
+        let buffer = Box::into_raw(vec![0_usize; buffer_length].into_boxed_slice()) as *mut usize;
+
+        // Point the header fields at their arrays.
+        let mut offset = HEADER_FIELD_COUNT;
+        for (field, word_count) in word_counts.iter().enumerate() {
+            unsafe { *buffer.add(field) = buffer.add(offset) as usize };
+            offset += word_count;
+        }
+
+        let mut context = Self {
+            buffer,
+            buffer_length,
+            lengths,
+        };
+
+        let intrinsic_functions = context.intrinsic_functions_mut();
+        intrinsic_functions[MEMORY_GROW_INDEX as usize] = wasmo_memory_grow as FuncPtr;
+        intrinsic_functions[MEMORY_SIZE_INDEX as usize] = wasmo_memory_size as FuncPtr;
+        intrinsic_functions[TRAP_INDEX as usize] = wasmo_trap as FuncPtr;
+
+        let imported_function_count = data.imported_function_count() as usize;
+        for function_context in &mut context.function_contexts_mut()[imported_function_count..] {
+            *function_context = buffer;
+        }
+
+        context
+    }
+
+    /// Gets the buffer generated code takes as its first argument.
+    pub(crate) fn as_ptr(&self) -> *mut usize {
+        self.buffer
+    }
+
+    /// Gets the memories array, imported memories first. Generated code reads a `MemoryPtr` at the
+    /// start of each.
+    pub fn memories(&self) -> &[*mut Memory] {
+        unsafe { self.array(MEMORIES_FIELD) }
+    }
+
+    /// Gets the memories array for filling it in.
+    pub fn memories_mut(&mut self) -> &mut [*mut Memory] {
+        unsafe { self.array_mut(MEMORIES_FIELD) }
+    }
+
+    /// Gets the tables array, imported tables first.
+    pub fn tables(&self) -> &[TablePtr] {
+        unsafe { self.array(TABLES_FIELD) }
+    }
+
+    /// Gets the tables array for filling it in.
+    pub fn tables_mut(&mut self) -> &mut [TablePtr] {
+        unsafe { self.array_mut(TABLES_FIELD) }
+    }
+
+    /// Gets the globals array of pointers to 64-bit cells, imported globals first.
+    pub fn globals(&self) -> &[GlobalPtr] {
+        unsafe { self.array(GLOBALS_FIELD) }
+    }
+
+    /// Gets the globals array for filling it in.
+    pub fn globals_mut(&mut self) -> &mut [GlobalPtr] {
+        unsafe { self.array_mut(GLOBALS_FIELD) }
+    }
+
+    /// Gets the addresses of the functions, by index in the function index space.
+    pub fn functions(&self) -> &[FuncPtr] {
+        unsafe { self.array(FUNCTIONS_FIELD) }
+    }
+
+    /// Gets the function addresses for filling them in.
+    pub fn functions_mut(&mut self) -> &mut [FuncPtr] {
+        unsafe { self.array_mut(FUNCTIONS_FIELD) }
+    }
+
+    /// Gets the runtime functions generated code calls, see `intrinsics`.
+    pub fn intrinsic_functions(&self) -> &[FuncPtr] {
+        unsafe { self.array(INTRINSIC_FUNCTIONS_FIELD) }
+    }
+
+    /// Gets the runtime functions for filling them in.
+    pub fn intrinsic_functions_mut(&mut self) -> &mut [FuncPtr] {
+        unsafe { self.array_mut(INTRINSIC_FUNCTIONS_FIELD) }
+    }
+
+    /// Gets what each function is called with, by index in the function index space: the
+    /// buffer of its instance, or the context of a host function.
+    pub fn function_contexts(&self) -> &[*mut usize] {
+        unsafe { self.array(FUNCTION_CONTEXTS_FIELD) }
+    }
+
+    /// Gets the function contexts for filling them in.
+    pub fn function_contexts_mut(&mut self) -> &mut [*mut usize] {
+        unsafe { self.array_mut(FUNCTION_CONTEXTS_FIELD) }
+    }
+
+    /// ###### Safety
+    /// `T` must be the element type of the array at `field`.
+    unsafe fn array<T>(&self, field: u32) -> &[T] {
+        let pointer = *self.buffer.add(field as usize) as *const T;
+
+        slice::from_raw_parts(pointer, self.lengths[field as usize])
+    }
+
+    /// ###### Safety
+    /// `T` must be the element type of the array at `field`.
+    unsafe fn array_mut<T>(&mut self, field: u32) -> &mut [T] {
+        let pointer = *self.buffer.add(field as usize) as *mut T;
+
+        slice::from_raw_parts_mut(pointer, self.lengths[field as usize])
+    }
+
+    /// Gets a memory from the InstanceContext buffer generated code passes to intrinsics.
     ///
-    pub fn new() -> Self {
-        unimplemented!()
+    /// ###### Safety
+    /// `buffer` must be a valid InstanceContext buffer with a memory at `index`.
+    pub(crate) unsafe fn memory_from_buffer(buffer: *mut usize, index: u32) -> *mut Memory {
+        let memories = *(buffer.add(MEMORIES_FIELD as usize) as *const *mut *mut Memory);
+
+        *memories.add(index as usize)
+    }
+}
+
+impl Drop for InstanceContext {
+    fn drop(&mut self) {
+        // Reclaims the boxed slice created by `new`.
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.buffer,
+                self.buffer_length,
+            )));
+        }
     }
 }
@@ -1,24 +1,30 @@
 //! This module contains compile-time and runtime information of a WebAssembly module.
+use crate::errors::MemoryError;
+use crate::memory::Memory;
+use crate::trap::UNKNOWN_OFFSET;
 use crate::types::{
-    ExportKind, Exports, FuncPtr, FuncType, GlobalPtr, Imports, MemoryPtr, TablePtr, ValueType,
+    ExportKind, Exports, FuncPtr, FuncRef, FuncType, GlobalPtr, Imports, TablePtr, ValueType,
 };
 
-use std::ptr::null;
+use std::ptr::{null, null_mut};
 
-#[derive(Debug)]
+/// Sizes in wasm pages for memories and in elements for tables.
+#[derive(Debug, Clone, Copy)]
 pub struct ResizableLimits {
-    minimum: u16,
-    maximum: u16,
+    pub minimum: u32,
+    pub maximum: Option<u32>,
 }
 
-///
+/// A linear memory of a module. Generated code reads the `MemoryPtr` at the start of `Memory`
+/// from the `InstanceContext` memories array.
 #[derive(Debug)]
 pub struct MemoryData {
-    ptr: MemoryPtr, // Nullable
-    runtime_length: usize,
+    memory: Option<Box<Memory>>, // Allocated at instantiation. Boxed so its address is stable
     limits: ResizableLimits,
 }
 
+/// `ptr` points to `runtime_length` elements owned by the table. Generated code reads the
+/// `BoundPtr` from the `InstanceContext` tables array.
 #[derive(Debug)]
 pub struct TableData {
     ptr: TablePtr, // Nullable
@@ -26,17 +32,49 @@ pub struct TableData {
     limits: ResizableLimits,
 }
 
+/// An element segment. Active segments are copied into their table at instantiation, passive
+/// segments are kept for `table.init`.
+#[derive(Debug, Clone)]
+pub struct ElementData {
+    pub table_index: u32,
+    pub offset: Option<InitExpression>, // None for passive segments
+    pub function_indices: Vec<u32>,
+}
+
+/// A constant expression that initializes a global or gives the offset of a segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitExpression {
+    /// Bits of an `*.const` value, zero-extended to 64 bits.
+    Const(u64),
+    /// Value of an imported global.
+    GetGlobal(u32),
+}
+
+/// A data segment. Active segments are copied into their memory at instantiation, passive segments
+/// are kept for `memory.init`.
+#[derive(Debug, Clone)]
+pub struct DataSegmentData {
+    pub memory_index: u32,
+    pub offset: Option<InitExpression>, // None for passive segments
+    pub bytes: Vec<u8>,
+}
+
+/// Every global lives in a 64-bit cell that generated code accesses through the
+/// `InstanceContext` globals array. Narrower values occupy the low-order bytes of the cell.
 #[derive(Debug)]
 pub struct GlobalData {
-    ptr: GlobalPtr, // Nullable
+    ptr: GlobalPtr,         // Nullable
+    cell: Option<Box<u64>>, // Allocated at instantiation for local globals
     mutable: bool,
     ty: ValueType,
+    init: Option<InitExpression>, // None for imported globals
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FuncData {
     pub ptr: FuncPtr, // Nullable
     pub type_index: u32,
+    pub body_offset: u32, // Of the body in the wasm binary, `UNKNOWN_OFFSET` for imports
 }
 
 #[derive(Debug)]
@@ -46,6 +84,8 @@ pub struct Locals {
     pub tables: Vec<TableData>,
     pub globals: Vec<GlobalData>,
     pub functions: Vec<FuncData>,
+    pub elements: Vec<ElementData>,
+    pub data_segments: Vec<DataSegmentData>,
 }
 
 #[derive(Debug)]
@@ -56,11 +96,24 @@ pub enum Data {
     Func(FuncData),
 }
 
+/// An entity a module expects from its environment. `data` describes the expected type, its
+/// pointer is filled at instantiation.
+#[derive(Debug)]
+pub struct Import {
+    pub module: String,
+    pub field: String,
+    pub data: Data,
+}
+
 #[derive(Debug)]
 pub struct ModuleData {
     pub exports: Exports,
     pub imports: Imports,
     pub locals: Locals,
+    pub start_function: Option<u32>,
+    /// Address of the `CODE_END_NAME` function once the module is compiled. The machine code of
+    /// the local functions runs from the first of them to there.
+    pub code_end: FuncPtr,
 }
 
 impl Locals {
@@ -71,6 +124,45 @@ impl Locals {
             tables: Vec::new(),
             globals: Vec::new(),
             functions: Vec::new(),
+            elements: Vec::new(),
+            data_segments: Vec::new(),
+        }
+    }
+
+    /// Creates locals of the same types that have yet to be allocated, for another instance.
+    pub fn template(&self) -> Self {
+        Self {
+            types: self.types.clone(),
+            memories: self.memories.iter().map(MemoryData::template).collect(),
+            tables: self.tables.iter().map(TableData::template).collect(),
+            globals: self.globals.iter().map(GlobalData::template).collect(),
+            functions: self.functions.clone(),
+            elements: self.elements.clone(),
+            data_segments: self.data_segments.clone(),
+        }
+    }
+}
+
+impl Data {
+    /// Creates data of the same type that has yet to be allocated or resolved, for another
+    /// instance.
+    pub fn template(&self) -> Self {
+        match self {
+            Data::Memory(memory) => Data::Memory(memory.template()),
+            Data::Table(table) => Data::Table(table.template()),
+            Data::Global(global) => Data::Global(global.template()),
+            Data::Func(func) => Data::Func(func.clone()),
+        }
+    }
+}
+
+impl Import {
+    /// Creates an import of the same entity that has yet to be resolved, for another instance.
+    pub fn template(&self) -> Self {
+        Self {
+            module: self.module.clone(),
+            field: self.field.clone(),
+            data: self.data.template(),
         }
     }
 }
@@ -81,15 +173,126 @@ impl ModuleData {
 
         Self {
             exports: HashMap::new(),
-            imports: HashMap::new(),
+            imports: Vec::new(),
             locals: Locals::new(),
+            start_function: None,
+            code_end: null(),
         }
     }
 
+    /// Creates module data with nothing allocated or resolved yet, for another instance.
+    pub fn template(&self) -> Self {
+        Self {
+            exports: self.exports.clone(),
+            imports: self.imports.iter().map(Import::template).collect(),
+            locals: self.locals.template(),
+            start_function: self.start_function,
+            code_end: self.code_end,
+        }
+    }
+
+    pub fn add_import(&mut self, module: String, field: String, data: Data) {
+        self.imports.push(Import {
+            module,
+            field,
+            data,
+        });
+    }
+
+    /// Number of imported functions. Local functions come after them in the function index space.
+    pub fn imported_function_count(&self) -> u32 {
+        self.imported_count(|data| matches!(data, Data::Func(_)))
+    }
+
+    /// Number of memories, imported ones included.
+    pub fn memory_count(&self) -> u32 {
+        let imported = self.imported_count(|data| matches!(data, Data::Memory(_)));
+
+        imported + self.locals.memories.len() as u32
+    }
+
+    /// Number of tables, imported ones included.
+    pub fn table_count(&self) -> u32 {
+        let imported = self.imported_count(|data| matches!(data, Data::Table(_)));
+
+        imported + self.locals.tables.len() as u32
+    }
+
+    /// Number of globals, imported ones included.
+    pub fn global_count(&self) -> u32 {
+        let imported = self.imported_count(|data| matches!(data, Data::Global(_)));
+
+        imported + self.locals.globals.len() as u32
+    }
+
+    /// Number of functions, imported ones included.
+    pub fn function_count(&self) -> u32 {
+        self.imported_function_count() + self.locals.functions.len() as u32
+    }
+
+    fn imported_count(&self, is_kind: impl Fn(&Data) -> bool) -> u32 {
+        self.imports
+            .iter()
+            .filter(|import| is_kind(&import.data))
+            .count() as u32
+    }
+
+    /// Gets the type of a function in the function index space.
+    pub fn function_type(&self, function_index: u32) -> Option<&FuncType> {
+        let type_index = self.function_type_index(function_index)?;
+
+        self.locals.types.get(type_index as usize)
+    }
+
     pub fn add_type(&mut self, func_type: FuncType) {
         self.locals.types.push(func_type);
     }
 
+    /// Gets the index of the first type that is structurally equal to the type at `type_index`.
+    ///
+    /// Function signatures are compared by their canonical index, so that duplicate types in the
+    /// type section still match.
+    pub fn canonical_type_index(&self, type_index: u32) -> u32 {
+        let types = &self.locals.types;
+        let func_type = &types[type_index as usize];
+
+        // Guarantee: the type is at least equal to itself.
+        types.iter().position(|ty| ty == func_type).unwrap() as u32
+    }
+
+    pub fn add_global(&mut self, global: GlobalData) {
+        self.locals.globals.push(global);
+    }
+
+    pub fn add_memory(&mut self, memory: MemoryData) {
+        self.locals.memories.push(memory);
+    }
+
+    pub fn add_table(&mut self, table: TableData) {
+        self.locals.tables.push(table);
+    }
+
+    pub fn add_element(&mut self, element: ElementData) {
+        self.locals.elements.push(element);
+    }
+
+    pub fn add_data_segment(&mut self, data_segment: DataSegmentData) {
+        self.locals.data_segments.push(data_segment);
+    }
+
+    /// Gets the type index of a function in the function index space.
+    pub fn function_type_index(&self, function_index: u32) -> Option<u32> {
+        let imported = self.imports.iter().filter_map(|import| match &import.data {
+            Data::Func(func) => Some(func),
+            _ => None,
+        });
+
+        imported
+            .chain(self.locals.functions.iter())
+            .nth(function_index as usize)
+            .map(|func| func.type_index)
+    }
+
     pub fn add_function(&mut self, func: FuncData) {
         self.locals.functions.push(func);
     }
@@ -99,8 +302,172 @@ impl ModuleData {
     }
 }
 
+impl ResizableLimits {
+    pub fn new(minimum: u32, maximum: Option<u32>) -> Self {
+        Self { minimum, maximum }
+    }
+}
+
+impl MemoryData {
+    pub fn new(limits: ResizableLimits) -> Self {
+        Self {
+            memory: None,
+            limits,
+        }
+    }
+
+    pub fn limits(&self) -> &ResizableLimits {
+        &self.limits
+    }
+
+    /// Reserves and commits the memory if that hasn't been done yet.
+    pub fn allocate(&mut self) -> Result<&mut Memory, MemoryError> {
+        if self.memory.is_none() {
+            self.memory = Some(Box::new(Memory::new(&self.limits)?));
+        }
+
+        Ok(&mut **self.memory.as_mut().unwrap())
+    }
+
+    pub fn memory(&self) -> Option<&Memory> {
+        self.memory.as_deref()
+    }
+
+    pub fn memory_mut(&mut self) -> Option<&mut Memory> {
+        self.memory.as_deref_mut()
+    }
+
+    /// Creates data with the same limits that has yet to be allocated, for another instance.
+    pub fn template(&self) -> Self {
+        Self::new(self.limits)
+    }
+}
+
+impl TableData {
+    pub fn new(limits: ResizableLimits) -> Self {
+        Self {
+            ptr: TablePtr::new(null_mut(), 0),
+            runtime_length: 0,
+            limits,
+        }
+    }
+
+    pub fn limits(&self) -> &ResizableLimits {
+        &self.limits
+    }
+
+    /// Allocates `limits.minimum` uninitialized elements if that hasn't been done yet.
+    pub fn allocate(&mut self) -> &TablePtr {
+        if self.ptr.base_ptr.is_null() {
+            let length = self.limits.minimum as usize;
+            let elements = vec![FuncRef::null(); length].into_boxed_slice();
+
+            self.ptr = TablePtr::new(Box::into_raw(elements) as *mut FuncRef, length);
+            self.runtime_length = length;
+        }
+
+        &self.ptr
+    }
+
+    pub fn ptr(&self) -> &TablePtr {
+        &self.ptr
+    }
+
+    pub fn elements(&self) -> &[FuncRef] {
+        if self.ptr.base_ptr.is_null() {
+            return &[];
+        }
+
+        unsafe { std::slice::from_raw_parts(self.ptr.base_ptr, self.runtime_length) }
+    }
+
+    /// Creates data with the same limits that has yet to be allocated, for another instance.
+    pub fn template(&self) -> Self {
+        Self::new(self.limits)
+    }
+}
+
+impl Drop for TableData {
+    fn drop(&mut self) {
+        if !self.ptr.base_ptr.is_null() {
+            // Reclaims the boxed slice created by `allocate`.
+            unsafe {
+                drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    self.ptr.base_ptr,
+                    self.runtime_length,
+                )));
+            }
+        }
+    }
+}
+
+impl GlobalData {
+    pub fn new(ty: ValueType, mutable: bool, init: InitExpression) -> Self {
+        Self {
+            ptr: null_mut(),
+            cell: None,
+            mutable,
+            ty,
+            init: Some(init),
+        }
+    }
+
+    /// Creates the expected type of an imported global.
+    pub fn import(ty: ValueType, mutable: bool) -> Self {
+        Self {
+            ptr: null_mut(),
+            cell: None,
+            mutable,
+            ty,
+            init: None,
+        }
+    }
+
+    pub fn ty(&self) -> ValueType {
+        self.ty
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    pub fn init(&self) -> Option<&InitExpression> {
+        self.init.as_ref()
+    }
+
+    /// Stores `value` in a cell owned by the global if that hasn't been done yet.
+    pub fn allocate(&mut self, value: u64) -> GlobalPtr {
+        if self.cell.is_none() {
+            let mut cell = Box::new(value);
+            self.ptr = &mut *cell;
+            self.cell = Some(cell);
+        }
+
+        self.ptr
+    }
+
+    pub fn ptr(&self) -> GlobalPtr {
+        self.ptr
+    }
+
+    /// Creates data of the same type that has yet to be allocated, for another instance.
+    pub fn template(&self) -> Self {
+        Self {
+            ptr: null_mut(),
+            cell: None,
+            mutable: self.mutable,
+            ty: self.ty,
+            init: self.init,
+        }
+    }
+}
+
 impl FuncData {
     pub fn new(ptr: FuncPtr, type_index: u32) -> Self {
-        Self { ptr, type_index }
+        Self {
+            ptr,
+            type_index,
+            body_offset: UNKNOWN_OFFSET,
+        }
     }
 }
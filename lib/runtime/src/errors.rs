@@ -1,5 +1,77 @@
-///
-pub enum LinkError {}
+use crate::data::ResizableLimits;
+use crate::trap::TrapCode;
+use crate::types::{FuncType, ValueType};
 
-///
-pub enum RuntimeError {}
+/// Errors from resolving an import of a module against what a `Linker` defines.
+#[derive(Debug)]
+pub enum LinkError {
+    /// Nothing is defined for the import.
+    UnknownImport { module: String, field: String },
+    /// What is defined for the import is a different kind of entity.
+    IncompatibleKind { module: String, field: String },
+    /// The function defined for the import has a different type.
+    IncompatibleFuncType {
+        module: String,
+        field: String,
+        expected: FuncType,
+        found: FuncType,
+    },
+    /// The memory or table defined for the import doesn't fit within the limits of the import.
+    IncompatibleLimits {
+        module: String,
+        field: String,
+        expected: ResizableLimits,
+        found: ResizableLimits,
+    },
+    /// The global defined for the import has a different type or mutability.
+    IncompatibleGlobalType {
+        module: String,
+        field: String,
+        expected: (ValueType, bool),
+        found: (ValueType, bool),
+    },
+}
+
+/// Errors from calling a function.
+#[derive(Debug)]
+pub enum RuntimeError {
+    /// The arguments of a call, or the Rust signature a function is typed with, don't match the
+    /// type of the function.
+    SignatureMismatch,
+    /// The instance has no function export with the given name.
+    ExportNotFound(String),
+    /// The instance has no function at the given index of the function index space.
+    FunctionNotFound(u32),
+    /// The wasm code trapped. The offset is that of the trapping operator in the wasm binary for
+    /// traps generated code checks for, and that of the body of the faulting function for faults,
+    /// like accesses to the guard pages of a memory. None for traps outside of wasm operators.
+    Trap {
+        code: TrapCode,
+        wasm_offset: Option<u32>,
+    },
+}
+
+/// Errors from instantiating a module.
+#[derive(Debug)]
+pub enum InstantiationError {
+    /// Some imports couldn't be resolved.
+    Link(Vec<LinkError>),
+    /// Allocating a memory failed.
+    Memory(MemoryError),
+    /// Initializing a table or memory from a segment, or running the start function, trapped.
+    Trap {
+        code: TrapCode,
+        wasm_offset: Option<u32>,
+    },
+}
+
+/// Errors from creating or accessing a linear memory.
+#[derive(Debug)]
+pub enum MemoryError {
+    /// The requested size is larger than the maximum of the memory or of any wasm memory.
+    InvalidLimits,
+    /// The OS failed to reserve or commit pages.
+    Allocation(std::io::Error),
+    /// An access went past the end of the memory.
+    OutOfBounds,
+}
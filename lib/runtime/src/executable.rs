@@ -0,0 +1,187 @@
+//! Runtime functions the `main` generated for an executable calls. It describes the module to
+//! `wasmo_module_data_*` and hands it to `wasmo_main`.
+//!
+//! Executables link against these and the intrinsics through the `wasmo-support` static library,
+//! which builds the runtime without the `jit` feature.
+use crate::container::{module::InstanceAOT, Initialize};
+use crate::data::{
+    FuncData, GlobalData, InitExpression, MemoryData, ModuleData, ResizableLimits, TableData,
+};
+use crate::imports::Linker;
+use crate::types::{FuncPtr, ValueType};
+
+/// Symbol `main` declares for `wasmo_module_data_new`.
+pub const MODULE_DATA_NEW_NAME: &str = "wasmo_module_data_new";
+
+/// Symbol `main` declares for `wasmo_module_data_add_memory`.
+pub const MODULE_DATA_ADD_MEMORY_NAME: &str = "wasmo_module_data_add_memory";
+
+/// Symbol `main` declares for `wasmo_module_data_add_table`.
+pub const MODULE_DATA_ADD_TABLE_NAME: &str = "wasmo_module_data_add_table";
+
+/// Symbol `main` declares for `wasmo_module_data_add_global`.
+pub const MODULE_DATA_ADD_GLOBAL_NAME: &str = "wasmo_module_data_add_global";
+
+/// Symbol `main` declares for `wasmo_module_data_add_function`.
+pub const MODULE_DATA_ADD_FUNCTION_NAME: &str = "wasmo_module_data_add_function";
+
+/// Symbol `main` declares for `wasmo_module_data_set_code_end`.
+pub const MODULE_DATA_SET_CODE_END_NAME: &str = "wasmo_module_data_set_code_end";
+
+/// Symbol `main` declares for `wasmo_main`.
+pub const MAIN_NAME: &str = "wasmo_main";
+
+/// Passed as the maximum of a memory or table that has none.
+pub const NO_MAXIMUM: i64 = -1;
+
+/// Passed as the `_start` function index of a module that doesn't export one.
+pub const NO_START_FUNCTION: i64 = -1;
+
+/// What executables exit with when the module can't be instantiated or traps.
+const FAILURE_STATUS: i32 = 1;
+
+/// Creates the runtime data of the module. It is freed by `wasmo_main`.
+#[no_mangle]
+pub extern "C" fn wasmo_module_data_new() -> *mut ModuleData {
+    Box::into_raw(Box::new(ModuleData::new()))
+}
+
+/// Adds a local memory. `maximum` is `NO_MAXIMUM` for memories without one.
+///
+/// ###### Safety
+/// `data` must come from `wasmo_module_data_new`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_module_data_add_memory(
+    data: *mut ModuleData,
+    minimum: u32,
+    maximum: i64,
+) {
+    (*data).add_memory(MemoryData::new(limits(minimum, maximum)));
+}
+
+/// Adds a local table. `maximum` is `NO_MAXIMUM` for tables without one.
+///
+/// ###### Safety
+/// `data` must come from `wasmo_module_data_new`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_module_data_add_table(
+    data: *mut ModuleData,
+    minimum: u32,
+    maximum: i64,
+) {
+    (*data).add_table(TableData::new(limits(minimum, maximum)));
+}
+
+/// Adds a local global initialized to `bits`. `ty` is a `ValueType` discriminant.
+///
+/// ###### Safety
+/// `data` must come from `wasmo_module_data_new`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_module_data_add_global(
+    data: *mut ModuleData,
+    ty: u32,
+    mutable: u32,
+    bits: u64,
+) {
+    let ty = match ty {
+        0 => ValueType::I32,
+        1 => ValueType::I64,
+        2 => ValueType::F32,
+        3 => ValueType::F64,
+        // Generated code only passes `ValueType` discriminants.
+        _ => std::process::abort(),
+    };
+
+    (*data).add_global(GlobalData::new(
+        ty,
+        mutable != 0,
+        InitExpression::Const(bits),
+    ));
+}
+
+/// Adds a local function of the type at `type_index`. `ptr` is its address in the executable and
+/// `body_offset` the offset of its body in the wasm binary.
+///
+/// ###### Safety
+/// `data` must come from `wasmo_module_data_new`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_module_data_add_function(
+    data: *mut ModuleData,
+    type_index: u32,
+    ptr: FuncPtr,
+    body_offset: u32,
+) {
+    let mut function = FuncData::new(ptr, type_index);
+    function.body_offset = body_offset;
+
+    (*data).add_function(function);
+}
+
+/// Sets where the code of the local functions ends, the address of the `CODE_END_NAME` function.
+///
+/// ###### Safety
+/// `data` must come from `wasmo_module_data_new`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_module_data_set_code_end(data: *mut ModuleData, code_end: FuncPtr) {
+    (*data).code_end = code_end;
+}
+
+/// Instantiates the module, which runs its start function, then calls the function at
+/// `start_function`, the `_start` export. Returns what the executable exits with, the result of
+/// `_start` if it returns one and 0 otherwise.
+///
+/// Instantiation errors and traps are printed to stderr and exit with a failure status.
+///
+/// ###### Safety
+/// `data` must come from `wasmo_module_data_new` and is freed. `initialize` must be the
+/// initialization function of the module. `_start` must take nothing and return an i32 if
+/// `start_returns_status` is not 0, or nothing otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_main(
+    data: *mut ModuleData,
+    initialize: Initialize,
+    start_function: i64,
+    start_returns_status: u32,
+) -> i32 {
+    let data = *Box::from_raw(data);
+
+    let instance = match InstanceAOT::instantiate(data, initialize, &Linker::new()) {
+        Ok(instance) => instance,
+        Err(error) => {
+            eprintln!("Failed to instantiate the module: {:?}", error);
+            return FAILURE_STATUS;
+        }
+    };
+
+    if start_function == NO_START_FUNCTION {
+        return 0;
+    }
+
+    let start_function = start_function as u32;
+    // Guarantee: `main` passes the index of a local function.
+    let result = if start_returns_status != 0 {
+        let start = instance.get_typed_func_unchecked::<(), i32>(start_function);
+        start.unwrap().call()
+    } else {
+        let start = instance.get_typed_func_unchecked::<(), ()>(start_function);
+        start.unwrap().call().map(|()| 0)
+    };
+
+    match result {
+        Ok(status) => status,
+        Err(error) => {
+            eprintln!("`_start` failed: {:?}", error);
+            FAILURE_STATUS
+        }
+    }
+}
+
+fn limits(minimum: u32, maximum: i64) -> ResizableLimits {
+    let maximum = if maximum == NO_MAXIMUM {
+        None
+    } else {
+        Some(maximum as u32)
+    };
+
+    ResizableLimits::new(minimum, maximum)
+}
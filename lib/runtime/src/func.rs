@@ -1,11 +1,230 @@
+use crate::errors::RuntimeError;
+use crate::trap::catch_traps;
+use crate::types::{FuncPtr, FuncType, Value, ValueType};
 
 use std::marker::PhantomData;
 
-///
+/// Signature of the generated trampolines, see `TRAMPOLINE_NAME_PREFIX`.
+pub type Trampoline = unsafe extern "C" fn(*mut usize, FuncPtr, *const u64, *mut u64);
+
+/// A function exported by an instance. It borrows the instance it is called with.
+#[derive(Debug)]
+pub struct Func<'a> {
+    addr: FuncPtr,
+    trampoline: Trampoline,
+    context: *mut usize,
+    ty: &'a FuncType,
+}
+
+impl<'a> Func<'a> {
+    pub(crate) fn new(
+        addr: FuncPtr,
+        trampoline: Trampoline,
+        context: *mut usize,
+        ty: &'a FuncType,
+    ) -> Self {
+        Self {
+            addr,
+            trampoline,
+            context,
+            ty,
+        }
+    }
+
+    pub fn ty(&self) -> &FuncType {
+        self.ty
+    }
+
+    /// Calls the function through the trampoline of its type. Use `typed` instead when the
+    /// signature is known at compile time.
+    ///
+    /// Traps are returned as `RuntimeError::Trap`.
+    pub fn call(&self, args: &[Value]) -> Result<Vec<Value>, RuntimeError> {
+        let params = self.ty.params();
+        if args.len() != params.len() || args.iter().zip(params).any(|(arg, ty)| arg.ty() != *ty) {
+            return Err(RuntimeError::SignatureMismatch);
+        }
+
+        let arguments = args.iter().map(Value::to_bits).collect::<Vec<_>>();
+        let mut results = vec![0_u64; self.ty.returns().len()];
+
+        catch_traps(|| unsafe {
+            (self.trampoline)(
+                self.context,
+                self.addr,
+                arguments.as_ptr(),
+                results.as_mut_ptr(),
+            )
+        })?;
+
+        Ok(self
+            .ty
+            .returns()
+            .iter()
+            .zip(results)
+            .map(|(ty, bits)| Value::from_bits(*ty, bits))
+            .collect())
+    }
+
+    /// Checks that the function takes `Params` and returns `Results`.
+    pub fn typed<Params, Results>(&self) -> Result<TypedFunc<'a, Params, Results>, RuntimeError>
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+    {
+        if self.ty.params() != &Params::types()[..] || self.ty.returns() != &Results::types()[..] {
+            return Err(RuntimeError::SignatureMismatch);
+        }
+
+        Ok(TypedFunc::new(self.addr, self.context))
+    }
+}
+
+/// A function whose signature has been checked against `Params` and `Results`. It is called
+/// directly, without a trampoline.
 #[derive(Debug)]
-pub struct Func<'a, Params = ()> {
-    addr: *const (),
-    phantom: PhantomData<&'a Params>,
+pub struct TypedFunc<'a, Params, Results> {
+    addr: FuncPtr,
+    context: *mut usize,
+    phantom: PhantomData<(&'a FuncType, Params, Results)>,
+}
+
+impl<'a, Params, Results> TypedFunc<'a, Params, Results> {
+    pub(crate) fn new(addr: FuncPtr, context: *mut usize) -> Self {
+        Self {
+            addr,
+            context,
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Rust types that can be passed to and returned from wasm functions.
+pub trait WasmType: Copy {
+    const TYPE: ValueType;
+}
+
+impl WasmType for i32 {
+    const TYPE: ValueType = ValueType::I32;
+}
+
+impl WasmType for i64 {
+    const TYPE: ValueType = ValueType::I64;
 }
 
-// TODO: Inline all .call methods.
+impl WasmType for f32 {
+    const TYPE: ValueType = ValueType::F32;
+}
+
+impl WasmType for f64 {
+    const TYPE: ValueType = ValueType::F64;
+}
+
+/// Tuples of `WasmType`s a function takes.
+pub trait WasmParams {
+    fn types() -> Vec<ValueType>;
+}
+
+/// What a function returns, `()` or a single `WasmType`.
+pub trait WasmResults {
+    fn types() -> Vec<ValueType>;
+}
+
+impl WasmResults for () {
+    fn types() -> Vec<ValueType> {
+        Vec::new()
+    }
+}
+
+impl<T: WasmType> WasmResults for T {
+    fn types() -> Vec<ValueType> {
+        vec![T::TYPE]
+    }
+}
+
+/// For implementing `WasmParams` and `TypedFunc::call` for every number of parameters.
+macro_rules! recurse_typed_call_impl {
+    () => {
+        typed_call_impl!();
+    };
+    ($ty0:ident $(, $ty:ident )*) => {
+        typed_call_impl!($ty0 $(, $ty )*);
+        recurse_typed_call_impl!($( $ty ),*);
+    };
+}
+
+macro_rules! typed_call_impl {
+    ($( $ty:ident ),*) => {
+        impl<$( $ty: WasmType ),*> WasmParams for ($( $ty, )*) {
+            fn types() -> Vec<ValueType> {
+                vec![$( $ty::TYPE ),*]
+            }
+        }
+
+        impl<'a, $( $ty: WasmType, )* R: WasmResults> TypedFunc<'a, ($( $ty, )*), R> {
+            /// Calls the function with the InstanceContext buffer of its instance first.
+            #[allow(non_snake_case, clippy::too_many_arguments)]
+            pub fn call(&self, $( $ty: $ty ),*) -> Result<R, RuntimeError> {
+                // Guarantee: the signature was checked when the function was typed.
+                let function: unsafe extern "C" fn(*mut usize $(, $ty )*) -> R =
+                    unsafe { std::mem::transmute(self.addr) };
+
+                catch_traps(|| unsafe { function(self.context $(, $ty )*) })
+            }
+        }
+    };
+}
+
+// Support for 12 parameters for now.
+recurse_typed_call_impl!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr::{null, null_mut};
+
+    unsafe extern "C" fn trampoline(_: *mut usize, _: FuncPtr, _: *const u64, _: *mut u64) {
+        unreachable!("Mismatched calls don't reach the trampoline")
+    }
+
+    #[test]
+    fn typed_checks_the_signature() {
+        let ty = FuncType::new(vec![ValueType::I32, ValueType::F64], vec![ValueType::I64]);
+        let func = Func::new(null(), trampoline, null_mut(), &ty);
+
+        assert!(func.typed::<(i32, f64), i64>().is_ok());
+
+        let mismatches = [
+            func.typed::<(i32, f32), i64>().err(),
+            func.typed::<(i32,), i64>().err(),
+            func.typed::<(i32, f64, i32), i64>().err(),
+            func.typed::<(i32, f64), i32>().err(),
+            func.typed::<(i32, f64), ()>().err(),
+        ];
+        for mismatch in mismatches.iter() {
+            match mismatch {
+                Some(RuntimeError::SignatureMismatch) => {}
+                other => panic!("Expected a signature mismatch, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn call_checks_the_arguments() {
+        let ty = FuncType::new(vec![ValueType::I32], vec![]);
+        let func = Func::new(null(), trampoline, null_mut(), &ty);
+
+        for args in [
+            vec![],
+            vec![Value::I64(1)],
+            vec![Value::I32(1), Value::I32(2)],
+        ]
+        .iter()
+        {
+            match func.call(args) {
+                Err(RuntimeError::SignatureMismatch) => {}
+                other => panic!("Expected a signature mismatch, got {:?}", other),
+            }
+        }
+    }
+}
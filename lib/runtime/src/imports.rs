@@ -0,0 +1,296 @@
+//! This module contains the entities a host provides to satisfy the imports of a module.
+use crate::context::InstanceContext;
+use crate::data::{GlobalData, InitExpression, MemoryData, ResizableLimits, TableData};
+use crate::errors::MemoryError;
+use crate::func::{WasmParams, WasmResults, WasmType};
+use crate::memory::Memory;
+use crate::trap::without_traps;
+use crate::types::{FuncPtr, FuncType, Value};
+
+use hashbrown::HashMap;
+use std::any::Any;
+use std::cell::UnsafeCell;
+use std::rc::Rc;
+
+/// An entity a `Linker` defines for imports. Clones share the entity: an instance keeps a clone of
+/// everything it imports, so what it imports lives as long as it does.
+///
+/// Entities are reference counted with `Rc` rather than `Arc`, host functions and memories can't
+/// be sent to other threads.
+#[derive(Debug, Clone)]
+pub enum Extern {
+    Func(Rc<HostFunc>),
+    Memory(Shared<MemoryData>),
+    Table(Shared<TableData>),
+    Global(Shared<GlobalData>),
+}
+
+/// A memory, table or global shared by a linker and the instances importing it.
+///
+/// Generated code of every importing instance writes to it through the pointers of its
+/// InstanceContext, so it is kept in an `UnsafeCell` and only handed out as a raw pointer.
+#[derive(Debug)]
+pub struct Shared<T>(Rc<UnsafeCell<T>>);
+
+/// A Rust function or closure wasm code can import.
+///
+/// Generated code calls `entry` with the wasm arguments, after a `HostContext` in place of the
+/// InstanceContext buffer. The context points to `function`.
+///
+/// Faults while the function runs are not wasm traps, and crash the process. Panics unwind the wasm
+/// code calling it like traps, and resume where the embedder called into wasm code.
+#[derive(Debug)]
+pub struct HostFunc {
+    function: Box<dyn Any>,
+    entry: FuncPtr,
+    ty: FuncType,
+}
+
+/// What host functions are called with first. Instances create one for every host function they
+/// import.
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct HostContext {
+    function: *const (),
+    caller: *mut usize, // InstanceContext buffer of the importing instance
+    memory_count: usize,
+}
+
+/// The instance calling a host function.
+#[derive(Debug)]
+pub struct Caller {
+    context: *mut usize,
+    memory_count: usize,
+}
+
+/// Defines entities for the imports of modules, keyed by module name then field name. Instances
+/// share what they import with the linker, which can be dropped before them.
+#[derive(Debug, Default)]
+pub struct Linker {
+    modules: HashMap<String, HashMap<String, Extern>>,
+}
+
+impl HostFunc {
+    pub fn ty(&self) -> &FuncType {
+        &self.ty
+    }
+
+    pub(crate) fn entry(&self) -> FuncPtr {
+        self.entry
+    }
+
+    /// Creates the context an instance calls the function with.
+    pub(crate) fn context(&self, caller: *mut usize, memory_count: usize) -> HostContext {
+        HostContext {
+            function: &*self.function as *const dyn Any as *const (),
+            caller,
+            memory_count,
+        }
+    }
+}
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Shared(Rc::new(UnsafeCell::new(value)))
+    }
+
+    /// Gets a pointer to the entity. It is valid as long as a clone of `self` is alive.
+    pub fn as_ptr(&self) -> *mut T {
+        self.0.get()
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Rc::clone(&self.0))
+    }
+}
+
+impl Caller {
+    /// Gets a memory of the calling instance, imported ones included.
+    pub fn memory(&mut self, index: u32) -> Option<&mut Memory> {
+        if index as usize >= self.memory_count {
+            return None;
+        }
+
+        Some(unsafe { &mut *InstanceContext::memory_from_buffer(self.context, index) })
+    }
+}
+
+impl Linker {
+    /// Creates a linker that defines nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines `value` for imports of `field` from `module`, replacing what was defined before.
+    pub fn define(&mut self, module: &str, field: &str, value: Extern) -> &mut Self {
+        self.modules
+            .entry(module.to_string())
+            .or_default()
+            .insert(field.to_string(), value);
+
+        self
+    }
+
+    /// Defines a host function that takes wasm values.
+    pub fn func<Params, Results>(
+        &mut self,
+        module: &str,
+        field: &str,
+        function: impl HostFn<Params, Results>,
+    ) -> &mut Self
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+    {
+        let host_func = HostFunc {
+            entry: function.entry(),
+            function: Box::new(function),
+            ty: FuncType::new(Params::types(), Results::types()),
+        };
+
+        self.define(module, field, Extern::Func(Rc::new(host_func)))
+    }
+
+    /// Defines a host function that takes the calling instance before wasm values.
+    pub fn func_with_caller<Params, Results>(
+        &mut self,
+        module: &str,
+        field: &str,
+        function: impl HostFnWithCaller<Params, Results>,
+    ) -> &mut Self
+    where
+        Params: WasmParams,
+        Results: WasmResults,
+    {
+        let host_func = HostFunc {
+            entry: function.entry(),
+            function: Box::new(function),
+            ty: FuncType::new(Params::types(), Results::types()),
+        };
+
+        self.define(module, field, Extern::Func(Rc::new(host_func)))
+    }
+
+    /// Defines a memory of `limits.minimum` pages and gets it, so the host can fill it.
+    pub fn memory(
+        &mut self,
+        module: &str,
+        field: &str,
+        limits: ResizableLimits,
+    ) -> Result<&mut Memory, MemoryError> {
+        let mut memory = MemoryData::new(limits);
+        memory.allocate()?;
+        let memory = Shared::new(memory);
+        let data = memory.as_ptr();
+        self.define(module, field, Extern::Memory(memory));
+
+        // Guarantee: the memory was just allocated and no instance imports it yet. Instantiating
+        // one takes the linker, which stays mutably borrowed as long as the memory.
+        Ok(unsafe { (*data).memory_mut().unwrap() })
+    }
+
+    /// Defines a table of `limits.minimum` uninitialized elements.
+    pub fn table(&mut self, module: &str, field: &str, limits: ResizableLimits) -> &mut Self {
+        let mut table = TableData::new(limits);
+        table.allocate();
+
+        self.define(module, field, Extern::Table(Shared::new(table)))
+    }
+
+    /// Defines a global holding `value`.
+    pub fn global(&mut self, module: &str, field: &str, value: Value, mutable: bool) -> &mut Self {
+        let bits = value.to_bits();
+        let mut global = GlobalData::new(value.ty(), mutable, InitExpression::Const(bits));
+        global.allocate(bits);
+
+        self.define(module, field, Extern::Global(Shared::new(global)))
+    }
+
+    pub fn get(&self, module: &str, field: &str) -> Option<&Extern> {
+        self.modules.get(module)?.get(field)
+    }
+}
+
+/// Rust functions and closures that take `Params` and return `Results`.
+pub trait HostFn<Params, Results>: 'static {
+    /// Gets the entry generated code calls, see `HostFunc`.
+    fn entry(&self) -> FuncPtr;
+}
+
+/// Rust functions and closures that take a `Caller` and `Params` and return `Results`.
+pub trait HostFnWithCaller<Params, Results>: 'static {
+    /// Gets the entry generated code calls, see `HostFunc`.
+    fn entry(&self) -> FuncPtr;
+}
+
+/// For implementing `HostFn` and `HostFnWithCaller` for every number of parameters.
+macro_rules! recurse_host_fn_impl {
+    () => {
+        host_fn_impl!();
+    };
+    ($ty0:ident $(, $ty:ident )*) => {
+        host_fn_impl!($ty0 $(, $ty )*);
+        recurse_host_fn_impl!($( $ty ),*);
+    };
+}
+
+macro_rules! host_fn_impl {
+    ($( $ty:ident ),*) => {
+        impl<Function, $( $ty: WasmType, )* R: WasmResults> HostFn<($( $ty, )*), R> for Function
+        where
+            Function: Fn($( $ty ),*) -> R + 'static,
+        {
+            fn entry(&self) -> FuncPtr {
+                #[allow(non_snake_case)]
+                unsafe extern "C" fn entry<Function, $( $ty, )* R>(
+                    context: *mut HostContext
+                    $(, $ty: $ty )*
+                ) -> R
+                where
+                    Function: Fn($( $ty ),*) -> R,
+                {
+                    let function = &*((*context).function as *const Function);
+
+                    // Guarantee: the frames of generated code own nothing.
+                    without_traps(|| function($( $ty ),*))
+                }
+
+                entry::<Function, $( $ty, )* R> as FuncPtr
+            }
+        }
+
+        impl<Function, $( $ty: WasmType, )* R: WasmResults> HostFnWithCaller<($( $ty, )*), R>
+            for Function
+        where
+            Function: Fn(&mut Caller $(, $ty )*) -> R + 'static,
+        {
+            fn entry(&self) -> FuncPtr {
+                #[allow(non_snake_case)]
+                unsafe extern "C" fn entry<Function, $( $ty, )* R>(
+                    context: *mut HostContext
+                    $(, $ty: $ty )*
+                ) -> R
+                where
+                    Function: Fn(&mut Caller $(, $ty )*) -> R,
+                {
+                    let context = &*context;
+                    let function = &*(context.function as *const Function);
+                    let mut caller = Caller {
+                        context: context.caller,
+                        memory_count: context.memory_count,
+                    };
+
+                    // Guarantee: the frames of generated code own nothing.
+                    without_traps(|| function(&mut caller $(, $ty )*))
+                }
+
+                entry::<Function, $( $ty, )* R> as FuncPtr
+            }
+        }
+    };
+}
+
+// Support for 12 parameters for now.
+recurse_host_fn_impl!(A, B, C, D, E, F, G, H, I, J, K, L);
@@ -0,0 +1,67 @@
+//! Runtime functions generated code calls through the intrinsic functions array of
+//! `InstanceContext`.
+//!
+//! The runtime fills the array with these functions when it creates an instance, jitted or AOT,
+//! so both modes share one implementation.
+use crate::context::InstanceContext;
+use crate::trap::{raise_trap, TrapCode, UNKNOWN_OFFSET};
+
+/// Index of `wasmo_memory_grow` in the intrinsic functions array.
+pub const MEMORY_GROW_INDEX: u32 = 0;
+
+/// Index of `wasmo_memory_size` in the intrinsic functions array.
+pub const MEMORY_SIZE_INDEX: u32 = 1;
+
+/// Index of `wasmo_trap` in the intrinsic functions array.
+pub const TRAP_INDEX: u32 = 2;
+
+/// Number of entries in the intrinsic functions array.
+pub const INTRINSIC_COUNT: usize = 3;
+
+/// Implements `memory.grow`. Returns the previous size in pages or -1 if the memory can't grow
+/// by `delta` pages.
+///
+/// ###### Safety
+/// `context` must be the InstanceContext buffer of an instance with a memory at `memory_index`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_memory_grow(
+    context: *mut usize,
+    delta: u32,
+    memory_index: u32,
+) -> i32 {
+    let memory = &mut *InstanceContext::memory_from_buffer(context, memory_index);
+
+    match memory.grow(delta) {
+        Some(previous_size) => previous_size as i32,
+        None => -1,
+    }
+}
+
+/// Implements `memory.size`. Returns the current size in pages.
+///
+/// ###### Safety
+/// `context` must be the InstanceContext buffer of an instance with a memory at `memory_index`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_memory_size(context: *mut usize, memory_index: u32) -> i32 {
+    let memory = &*InstanceContext::memory_from_buffer(context, memory_index);
+
+    memory.size() as i32
+}
+
+/// Raises the trap a check in generated code failed with. `code` is a `TrapCode` and `offset` is
+/// the offset of the trapping operator in the wasm binary, or `UNKNOWN_OFFSET`.
+///
+/// ###### Safety
+/// Must be called by wasm code running under `catch_traps`. The frames in between are unwound.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_trap(_context: *mut usize, code: u32, offset: u32) -> ! {
+    // Guarantee: generated code only passes `TrapCode` discriminants.
+    let code = TrapCode::from_u32(code).unwrap();
+    let offset = if offset == UNKNOWN_OFFSET {
+        None
+    } else {
+        Some(offset)
+    };
+
+    raise_trap(code, offset)
+}
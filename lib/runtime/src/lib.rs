@@ -1,5 +1,12 @@
 pub mod data;
+pub mod errors;
+pub mod executable;
 pub mod func;
+pub mod imports;
+pub mod intrinsics;
+pub mod library;
+pub mod memory;
+pub mod trap;
 pub mod types;
 pub mod options;
 pub mod context;
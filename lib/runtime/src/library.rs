@@ -0,0 +1,121 @@
+//! Runtime functions the C API of a static library calls. A library exports a generated
+//! instantiation function and wrappers of the functions the module exports, its header declares
+//! accessors of the exported memories and globals.
+//!
+//! Like executables, libraries link against these through the `wasmo-support` static library.
+use crate::container::{module::InstanceAOT, Initialize};
+use crate::data::ModuleData;
+use crate::errors::RuntimeError;
+use crate::func::Trampoline;
+use crate::imports::Linker;
+use crate::types::GlobalPtr;
+
+use std::ptr::null_mut;
+
+/// Symbol the instantiation function declares for `wasmo_instantiate`.
+pub const INSTANTIATE_NAME: &str = "wasmo_instantiate";
+
+/// Symbol function wrappers declare for `wasmo_instance_call`.
+pub const INSTANCE_CALL_NAME: &str = "wasmo_instance_call";
+
+/// What `wasmo_instance_call` returns when the function doesn't trap. Traps return their
+/// `TrapCode` plus one.
+pub const CALL_OK: i32 = 0;
+
+/// What `wasmo_instance_call` returns when the call fails without trapping, e.g. because the
+/// function index is out of bounds.
+pub const CALL_ERROR: i32 = -1;
+
+/// Instantiates the module. Returns null if that fails, after printing why to stderr.
+///
+/// ###### Safety
+/// `data` must come from `wasmo_module_data_new` and is freed. `initialize` must be the
+/// initialization function of the module.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_instantiate(
+    data: *mut ModuleData,
+    initialize: Initialize,
+) -> *mut InstanceAOT {
+    let data = *Box::from_raw(data);
+
+    match InstanceAOT::instantiate(data, initialize, &Linker::new()) {
+        Ok(instance) => Box::into_raw(Box::new(instance)),
+        Err(error) => {
+            eprintln!("Failed to instantiate the module: {:?}", error);
+            null_mut()
+        }
+    }
+}
+
+/// Frees an instance along with its memories, tables and globals.
+///
+/// ###### Safety
+/// `instance` must come from `wasmo_instantiate`, and is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_instance_free(instance: *mut InstanceAOT) {
+    if !instance.is_null() {
+        drop(Box::from_raw(instance));
+    }
+}
+
+/// Calls a function of the instance, see `InstanceAOT::call_with_trampoline`. Returns `CALL_OK`,
+/// the `TrapCode` the function trapped with plus one, or `CALL_ERROR` after printing why the
+/// call failed otherwise to stderr.
+///
+/// ###### Safety
+/// `instance` must come from `wasmo_instantiate`. The rest is as for `call_with_trampoline`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_instance_call(
+    instance: *mut InstanceAOT,
+    function_index: u32,
+    trampoline: Trampoline,
+    args: *const u64,
+    results: *mut u64,
+) -> i32 {
+    match (*instance).call_with_trampoline(function_index, trampoline, args, results) {
+        Ok(()) => CALL_OK,
+        Err(RuntimeError::Trap { code, .. }) => code as i32 + 1,
+        Err(error) => {
+            eprintln!("Failed to call the function: {:?}", error);
+            CALL_ERROR
+        }
+    }
+}
+
+/// Gets the committed bytes of a memory and stores their length in `size`. Returns null if
+/// there is no memory at `memory_index`.
+///
+/// The memory is reserved up front, so the bytes stay put when it grows. Only its size changes.
+///
+/// ###### Safety
+/// `instance` must come from `wasmo_instantiate`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_instance_memory(
+    instance: *mut InstanceAOT,
+    memory_index: u32,
+    size: *mut usize,
+) -> *mut u8 {
+    match (*instance).get_memory_mut(memory_index) {
+        Some(memory) => {
+            let bytes = memory.as_mut_slice();
+            *size = bytes.len();
+            bytes.as_mut_ptr()
+        }
+        None => null_mut(),
+    }
+}
+
+/// Gets the 64-bit cell of a global, narrower values are in its low-order bits. Returns null if
+/// there is no global at `global_index`.
+///
+/// ###### Safety
+/// `instance` must come from `wasmo_instantiate`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmo_instance_global(
+    instance: *mut InstanceAOT,
+    global_index: u32,
+) -> GlobalPtr {
+    (*instance)
+        .get_global(global_index)
+        .unwrap_or_else(null_mut)
+}
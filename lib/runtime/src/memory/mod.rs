@@ -0,0 +1,6 @@
+#[cfg(unix)]
+#[path = "unix.rs"]
+mod unix;
+
+#[cfg(unix)]
+pub use unix::Memory;
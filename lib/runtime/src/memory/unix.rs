@@ -0,0 +1,187 @@
+use crate::data::ResizableLimits;
+use crate::errors::MemoryError;
+use crate::types::MemoryPtr;
+
+use libc::{
+    mmap, mprotect, munmap, MAP_ANON, MAP_FAILED, MAP_PRIVATE, PROT_NONE, PROT_READ, PROT_WRITE,
+};
+
+use std::io;
+use std::ptr::null_mut;
+use std::slice;
+
+/// A linear memory backed by a guard-page reservation.
+///
+/// The whole address range generated code can reach is reserved upfront as inaccessible pages.
+/// Only the first `size` wasm pages are committed as readable and writable, so an out of bounds
+/// access faults instead of touching other data. The base address never changes as the memory
+/// grows.
+///
+/// ###### Reservation
+/// An effective address is a 32-bit address plus a 32-bit offset, so it is always below 8 GiB.
+/// One more wasm page covers the bytes of an access that starts just below that.
+///
+/// ###### Layout
+/// `InstanceContext` points to memories, and generated code reads their base and length through
+/// that pointer. `ptr` must stay the first field.
+#[repr(C)]
+#[derive(Debug)]
+pub struct Memory {
+    ptr: MemoryPtr,
+    maximum: Option<u32>,
+}
+
+impl Memory {
+    /// Size of a wasm page in bytes.
+    pub const PAGE_SIZE: usize = 0x1_0000;
+
+    /// Maximum number of wasm pages a memory can have.
+    pub const MAX_PAGES: u32 = 0x1_0000;
+
+    /// Size of the address space reserved for every memory in bytes.
+    pub const RESERVED_SIZE: usize = 0x2_0000_0000 + Memory::PAGE_SIZE;
+
+    /// Reserves the address space of a memory and commits its initial pages.
+    pub fn new(limits: &ResizableLimits) -> Result<Self, MemoryError> {
+        let maximum = limits.maximum.unwrap_or(Memory::MAX_PAGES);
+
+        if limits.minimum > maximum || maximum > Memory::MAX_PAGES {
+            return Err(MemoryError::InvalidLimits);
+        }
+
+        let base = unsafe {
+            mmap(
+                null_mut(),
+                Memory::RESERVED_SIZE,
+                PROT_NONE,
+                MAP_PRIVATE | MAP_ANON,
+                -1,
+                0,
+            )
+        };
+
+        if base == MAP_FAILED {
+            return Err(MemoryError::Allocation(io::Error::last_os_error()));
+        }
+
+        let mut memory = Self {
+            ptr: MemoryPtr::new(base as *mut u8, 0),
+            maximum: limits.maximum,
+        };
+
+        // Dropping `memory` unmaps the reservation if this fails.
+        memory.commit(limits.minimum)?;
+
+        Ok(memory)
+    }
+
+    /// Current size in wasm pages.
+    pub fn size(&self) -> u32 {
+        (self.ptr.size / Memory::PAGE_SIZE) as u32
+    }
+
+    /// Current length in bytes.
+    pub fn len(&self) -> usize {
+        self.ptr.size
+    }
+
+    /// Whether the memory has no committed pages, e.g. a memory with a minimum of 0 pages.
+    pub fn is_empty(&self) -> bool {
+        self.ptr.size == 0
+    }
+
+    /// Grows the memory by `delta` wasm pages and returns its previous size in pages.
+    ///
+    /// Returns `None`, leaving the memory unchanged, if the new size would exceed the maximum or
+    /// the pages could not be committed.
+    pub fn grow(&mut self, delta: u32) -> Option<u32> {
+        let previous_size = self.size();
+
+        self.commit(delta).ok()?;
+
+        Some(previous_size)
+    }
+
+    /// Copies bytes starting at `offset` into `buffer`.
+    pub fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), MemoryError> {
+        let end = self.check_range(offset, buffer.len())?;
+
+        buffer.copy_from_slice(&self.as_slice()[offset..end]);
+
+        Ok(())
+    }
+
+    /// Copies `bytes` into the memory starting at `offset`.
+    pub fn write(&mut self, offset: usize, bytes: &[u8]) -> Result<(), MemoryError> {
+        let end = self.check_range(offset, bytes.len())?;
+
+        self.as_mut_slice()[offset..end].copy_from_slice(bytes);
+
+        Ok(())
+    }
+
+    /// The committed pages.
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.base_ptr, self.len()) }
+    }
+
+    /// The committed pages.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.base_ptr, self.len()) }
+    }
+
+    /// Base pointer and length as read by generated code. The length is kept up to date as the
+    /// memory grows.
+    pub fn as_memory_ptr(&mut self) -> *mut MemoryPtr {
+        &mut self.ptr
+    }
+
+    /// Makes the next `delta` wasm pages after the committed ones readable and writable.
+    fn commit(&mut self, delta: u32) -> Result<(), MemoryError> {
+        let maximum = self.maximum.unwrap_or(Memory::MAX_PAGES);
+        let new_size = self
+            .size()
+            .checked_add(delta)
+            .filter(|new_size| *new_size <= maximum)
+            .ok_or(MemoryError::InvalidLimits)?;
+
+        if delta == 0 {
+            return Ok(());
+        }
+
+        let result = unsafe {
+            mprotect(
+                self.ptr.base_ptr.add(self.len()) as *mut _,
+                delta as usize * Memory::PAGE_SIZE,
+                PROT_READ | PROT_WRITE,
+            )
+        };
+
+        if result != 0 {
+            return Err(MemoryError::Allocation(io::Error::last_os_error()));
+        }
+
+        self.ptr.size = new_size as usize * Memory::PAGE_SIZE;
+
+        Ok(())
+    }
+
+    /// Returns the end of the range if it is within the committed pages.
+    fn check_range(&self, offset: usize, length: usize) -> Result<usize, MemoryError> {
+        offset
+            .checked_add(length)
+            .filter(|end| *end <= self.len())
+            .ok_or(MemoryError::OutOfBounds)
+    }
+}
+
+// The memory owns its reservation, so moving it to another thread is fine.
+unsafe impl Send for Memory {}
+
+impl Drop for Memory {
+    fn drop(&mut self) {
+        unsafe {
+            munmap(self.ptr.base_ptr as *mut _, Memory::RESERVED_SIZE);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Turns traps in generated code into `RuntimeError::Trap` for the embedder.
+//!
+//! Generated code reports the traps it checks for itself through the `wasmo_trap` intrinsic.
+//! Everything else, like accesses to the guard pages of a memory or overflowing the stack, faults
+//! and is caught by signal handlers. Only faults in the code of wasm functions, which instances
+//! register with `register_code`, are traps. Either way, execution unwinds to the closest
+//! `catch_traps`.
+#[cfg(unix)]
+#[path = "unix.rs"]
+mod unix;
+
+#[cfg(unix)]
+pub use unix::catch_traps;
+
+#[cfg(unix)]
+pub(crate) use unix::{raise_trap, register_code, without_traps, CodeRegistration};
+
+/// Offset generated code reports for traps that don't come from a wasm operator, like those of
+/// segment initialization.
+pub const UNKNOWN_OFFSET: u32 = u32::MAX;
+
+/// Why wasm code trapped. Generated code passes the discriminant to `wasmo_trap`.
+#[repr(u32)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TrapCode {
+    /// An `unreachable` operator was executed.
+    Unreachable = 0,
+    /// A load, store or segment went past the end of a memory.
+    MemoryOutOfBounds = 1,
+    /// A `call_indirect` or segment went past the end of a table.
+    TableOutOfBounds = 2,
+    /// A `call_indirect` got an uninitialized table element.
+    IndirectCallToNull = 3,
+    /// A `call_indirect` got a function of another type.
+    SignatureMismatch = 4,
+    /// An integer division or remainder had a zero divisor.
+    IntegerDivisionByZero = 5,
+    /// A signed integer division overflowed.
+    IntegerOverflow = 6,
+    /// A float to integer conversion got NaN or a value out of range.
+    InvalidConversionToInteger = 7,
+    /// The call stack ran out.
+    StackOverflow = 8,
+}
+
+impl TrapCode {
+    /// Gets the trap code with discriminant `code`, as generated code passes it. None for values
+    /// that aren't one.
+    pub fn from_u32(code: u32) -> Option<Self> {
+        Some(match code {
+            0 => TrapCode::Unreachable,
+            1 => TrapCode::MemoryOutOfBounds,
+            2 => TrapCode::TableOutOfBounds,
+            3 => TrapCode::IndirectCallToNull,
+            4 => TrapCode::SignatureMismatch,
+            5 => TrapCode::IntegerDivisionByZero,
+            6 => TrapCode::IntegerOverflow,
+            7 => TrapCode::InvalidConversionToInteger,
+            8 => TrapCode::StackOverflow,
+            _ => return None,
+        })
+    }
+}
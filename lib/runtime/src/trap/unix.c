@@ -0,0 +1,53 @@
+/*
+ * The parts of trap handling Rust can't express: `sigsetjmp` returns twice, which only C
+ * compilers know to account for, and the layout of the signal context is platform specific.
+ */
+#if defined(__linux__) && !defined(_GNU_SOURCE)
+#define _GNU_SOURCE
+#endif
+
+#include <setjmp.h>
+#include <signal.h>
+#include <stdint.h>
+
+/*
+ * Calls `body` with `payload` and a buffer `wasmo_unwind` jumps back to. Returns 0 when `body`
+ * returns, or the value passed to `wasmo_unwind` otherwise.
+ *
+ * The signal mask is not saved, so jumping out of a signal handler leaves the signal unblocked
+ * only if it was installed with `SA_NODEFER`.
+ */
+int wasmo_catch_traps(void (*body)(void *payload, void *buffer), void *payload) {
+    sigjmp_buf buffer;
+    int value = sigsetjmp(buffer, 0);
+
+    if (value == 0) {
+        body(payload, buffer);
+    }
+
+    return value;
+}
+
+/* Jumps back to the `wasmo_catch_traps` that created `buffer`, which returns `value`. */
+void wasmo_unwind(void *buffer, int value) {
+    siglongjmp(*(sigjmp_buf *)buffer, value);
+}
+
+/* Gets the address of the faulting instruction from the context of a signal handler. */
+uintptr_t wasmo_fault_pc(void *context) {
+    ucontext_t *ucontext = (ucontext_t *)context;
+
+#if defined(__linux__) && defined(__x86_64__)
+    return (uintptr_t)ucontext->uc_mcontext.gregs[REG_RIP];
+#elif defined(__linux__) && defined(__aarch64__)
+    return (uintptr_t)ucontext->uc_mcontext.pc;
+#elif defined(__APPLE__) && defined(__x86_64__)
+    return (uintptr_t)ucontext->uc_mcontext->__ss.__rip;
+#elif defined(__APPLE__) && defined(__aarch64__)
+    return (uintptr_t)ucontext->uc_mcontext->__ss.__pc;
+#elif defined(__FreeBSD__) && defined(__x86_64__)
+    return (uintptr_t)ucontext->uc_mcontext.mc_rip;
+#else
+#error "wasmo doesn't know where signal contexts keep the faulting address on this platform"
+#endif
+}
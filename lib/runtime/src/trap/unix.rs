@@ -0,0 +1,498 @@
+use super::TrapCode;
+use crate::errors::RuntimeError;
+
+use lazy_static::lazy_static;
+use libc::{
+    c_int, c_void, mmap, munmap, sigaction, sigaltstack, sigemptyset, siginfo_t, stack_t, MAP_ANON,
+    MAP_FAILED, MAP_PRIVATE, PROT_READ, PROT_WRITE, SA_NODEFER, SA_ONSTACK, SA_SIGINFO, SIGBUS,
+    SIGFPE, SIGILL, SIGSEGV, SIG_DFL, SIG_IGN, SS_DISABLE,
+};
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::hint;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Once, RwLock, RwLockWriteGuard, TryLockError};
+use std::thread;
+
+// Defined in `unix.c`.
+extern "C" {
+    fn wasmo_catch_traps(
+        body: unsafe extern "C" fn(*mut c_void, *mut c_void),
+        payload: *mut c_void,
+    ) -> c_int;
+    fn wasmo_unwind(jmp_buf: *mut c_void, value: c_int) -> !;
+    fn wasmo_fault_pc(context: *mut c_void) -> usize;
+}
+
+/// What `catch_traps` passes through `wasmo_catch_traps`: the function to call and its result.
+struct Call<F, T> {
+    function: Option<F>,
+    result: Option<thread::Result<T>>,
+}
+
+/// Sets the jmp_buf traps unwind to back to what it was when dropped, however the frame holding
+/// it is left.
+struct RestoreJmpBuf(*mut c_void);
+
+/// Machine code of wasm functions. Faults in it are traps.
+struct CodeRange {
+    id: usize,
+    end: usize,
+    functions: Vec<(usize, u32)>, // Sorted start addresses, with the wasm offsets of the bodies
+}
+
+/// Keeps code registered with `register_code` until dropped.
+#[derive(Debug)]
+pub(crate) struct CodeRegistration {
+    id: usize,
+}
+
+/// Stack the signal handlers run on, so they still have one when wasm code overflows the stack of
+/// the thread.
+struct AltStack {
+    base: *mut c_void,
+}
+
+/// What `wasmo_unwind` makes `wasmo_catch_traps` return when a trap unwinds.
+const TRAPPED: c_int = 1;
+
+/// What `wasmo_unwind` makes `wasmo_catch_traps` return when a host function panics.
+const PANICKED: c_int = 2;
+
+/// Faults at most this far below the frame of `catch_traps` are taken as stack overflows. Memories
+/// are mapped further away than that from thread stacks.
+const MAX_STACK_SIZE: usize = 0x400_0000;
+
+/// Size of the stack the signal handlers run on.
+const ALT_STACK_SIZE: usize = 0x1_0000;
+
+/// Signals faults raise.
+const SIGNALS: [c_int; 4] = [SIGSEGV, SIGBUS, SIGFPE, SIGILL];
+
+static INSTALL_HANDLERS: Once = Once::new();
+
+lazy_static! {
+    /// Actions `SIGNALS` had before `install_handlers`, which signals that aren't traps are passed
+    /// to.
+    static ref PREVIOUS_ACTIONS: [sigaction; 4] = previous_actions();
+
+    static ref CODE_RANGES: RwLock<Vec<CodeRange>> = RwLock::new(Vec::new());
+}
+
+static NEXT_CODE_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Where a trap on this thread unwinds to. Null when no wasm code is running.
+    static CURRENT_JMP_BUF: Cell<*mut c_void> = Cell::new(null_mut());
+
+    /// The trap that is unwinding, with its offset in the wasm binary.
+    static CURRENT_TRAP: Cell<Option<(TrapCode, Option<u32>)>> = Cell::new(None);
+
+    /// The panic of a host function that is unwinding.
+    static CURRENT_PANIC: RefCell<Option<Box<dyn Any + Send>>> = RefCell::new(None);
+
+    static ALT_STACK: RefCell<Option<AltStack>> = RefCell::new(None);
+}
+
+/// Calls `function`, which runs wasm code, and returns the trap it raises, if any. Panics in
+/// `function` resume once it is left. So do those of host functions wasm code calls, which can't
+/// unwind through wasm code: they unwind it like traps, and resume from here.
+///
+/// Traps unwind the frames of `function` without dropping anything they own.
+pub fn catch_traps<F, T>(function: F) -> Result<T, RuntimeError>
+where
+    F: FnOnce() -> T,
+{
+    install_handlers();
+    install_alt_stack();
+
+    let _restore = RestoreJmpBuf(CURRENT_JMP_BUF.with(Cell::get));
+    let mut call = Call {
+        function: Some(function),
+        result: None,
+    };
+
+    // Setting the jump point up and calling `function` happens in C, `setjmp` returns twice.
+    let value =
+        unsafe { wasmo_catch_traps(call_function::<F, T>, &mut call as *mut _ as *mut c_void) };
+
+    if value == PANICKED {
+        // Guarantee: the panic is stored before jumping back.
+        let panic = CURRENT_PANIC.with(|current| current.borrow_mut().take()).unwrap();
+
+        panic::resume_unwind(panic);
+    }
+
+    if value == TRAPPED {
+        // Guarantee: the trap is stored before jumping back.
+        let (code, wasm_offset) = CURRENT_TRAP.with(Cell::take).unwrap();
+
+        return Err(RuntimeError::Trap { code, wasm_offset });
+    }
+
+    // Guarantee: `call_function` stores the result unless it traps.
+    match call.result.unwrap() {
+        Ok(result) => Ok(result),
+        Err(panic) => panic::resume_unwind(panic),
+    }
+}
+
+/// Calls the function of a `Call` with traps unwinding to `jmp_buf`. Panics are stored with the
+/// result, they can't unwind through C.
+unsafe extern "C" fn call_function<F, T>(call: *mut c_void, jmp_buf: *mut c_void)
+where
+    F: FnOnce() -> T,
+{
+    // Guarantee: `catch_traps` passes its `Call`, and calls this once.
+    let call = &mut *(call as *mut Call<F, T>);
+    let function = call.function.take().unwrap();
+
+    CURRENT_JMP_BUF.with(|current| current.set(jmp_buf));
+    call.result = Some(panic::catch_unwind(AssertUnwindSafe(function)));
+}
+
+/// Calls `function`, which runs host code for wasm code. Faults in it are not traps, and crash.
+/// Panics in it unwind to the closest `catch_traps`, which resumes them.
+///
+/// ###### Safety
+/// Frames between the caller and `catch_traps` are not dropped when `function` panics.
+pub(crate) unsafe fn without_traps<T>(function: impl FnOnce() -> T) -> T {
+    let result = {
+        let _restore = RestoreJmpBuf(CURRENT_JMP_BUF.with(|current| current.replace(null_mut())));
+
+        panic::catch_unwind(AssertUnwindSafe(function))
+    };
+
+    match result {
+        Ok(result) => result,
+        Err(panic) => raise_panic(panic),
+    }
+}
+
+/// Unwinds to the closest `catch_traps` with the panic of a host function. Aborts if there is
+/// none.
+///
+/// ###### Safety
+/// Frames between the caller and `catch_traps` are not dropped.
+unsafe fn raise_panic(panic: Box<dyn Any + Send>) -> ! {
+    let jmp_buf = CURRENT_JMP_BUF.with(Cell::get);
+    if jmp_buf.is_null() {
+        eprintln!("host function panicked outside of the runtime");
+        std::process::abort();
+    }
+
+    CURRENT_PANIC.with(|current| *current.borrow_mut() = Some(panic));
+    wasmo_unwind(jmp_buf, PANICKED)
+}
+
+/// Unwinds to the closest `catch_traps` with `code`. Aborts if there is none.
+///
+/// ###### Safety
+/// Frames between the caller and `catch_traps` are not dropped.
+pub(crate) unsafe fn raise_trap(code: TrapCode, wasm_offset: Option<u32>) -> ! {
+    let jmp_buf = CURRENT_JMP_BUF.with(Cell::get);
+    if jmp_buf.is_null() {
+        eprintln!("wasm code trapped outside of the runtime: {:?}", code);
+        std::process::abort();
+    }
+
+    CURRENT_TRAP.with(|trap| trap.set(Some((code, wasm_offset))));
+    wasmo_unwind(jmp_buf, TRAPPED)
+}
+
+/// Registers the machine code of wasm functions, so that faults in it become traps. `functions`
+/// are the addresses of the functions with the offsets of their bodies in the wasm binary. The
+/// code runs from the first function to `end`.
+pub(crate) fn register_code(
+    functions: impl Iterator<Item = (usize, u32)>,
+    end: usize,
+) -> CodeRegistration {
+    let mut functions: Vec<_> = functions.collect();
+    functions.sort_unstable();
+
+    let id = NEXT_CODE_ID.fetch_add(1, Ordering::Relaxed);
+    let range = CodeRange { id, end, functions };
+    write_code_ranges().push(range);
+
+    CodeRegistration { id }
+}
+
+/// Gets the wasm offset of the body of the function `pc` is in. None if `pc` is not in registered
+/// code.
+fn function_offset(pc: usize) -> Option<u32> {
+    // Spins instead of blocking, which signal handlers must not do. Writers only hold the lock
+    // to add or remove a range.
+    let code_ranges = loop {
+        match CODE_RANGES.try_read() {
+            Ok(code_ranges) => break code_ranges,
+            Err(TryLockError::Poisoned(error)) => break error.into_inner(),
+            Err(TryLockError::WouldBlock) => hint::spin_loop(),
+        }
+    };
+
+    code_ranges
+        .iter()
+        .find_map(|code_range| code_range.function_offset(pc))
+}
+
+fn write_code_ranges() -> RwLockWriteGuard<'static, Vec<CodeRange>> {
+    CODE_RANGES
+        .write()
+        .unwrap_or_else(|error| error.into_inner())
+}
+
+/// Handles the faults of all threads. Faults in wasm code become traps, other faults go to the
+/// previous action of the signal.
+unsafe extern "C" fn handle_signal(signum: c_int, info: *mut siginfo_t, context: *mut c_void) {
+    let jmp_buf = CURRENT_JMP_BUF.try_with(Cell::get).unwrap_or(null_mut());
+
+    let function_offset = if jmp_buf.is_null() {
+        None
+    } else {
+        function_offset(wasmo_fault_pc(context))
+    };
+
+    let function_offset = match function_offset {
+        Some(function_offset) => function_offset,
+        None => return chain_signal(signum, info, context),
+    };
+
+    let code = match signum {
+        SIGSEGV | SIGBUS => {
+            let address = fault_address(info) as usize;
+            // The stack grows down from the frame of `catch_traps`, next to the jmp_buf.
+            let stack_top = jmp_buf as usize;
+            if address < stack_top && stack_top - address <= MAX_STACK_SIZE {
+                TrapCode::StackOverflow
+            } else {
+                TrapCode::MemoryOutOfBounds
+            }
+        }
+        SIGFPE => TrapCode::IntegerDivisionByZero,
+        _ => TrapCode::Unreachable,
+    };
+
+    // Faults only tell which function the trapping operator is in.
+    raise_trap(code, Some(function_offset))
+}
+
+/// Passes a signal that isn't a trap to the action it had before `install_handlers`.
+unsafe fn chain_signal(signum: c_int, info: *mut siginfo_t, context: *mut c_void) {
+    // Guarantee: the previous actions are stored before the handlers are installed.
+    let index = SIGNALS.iter().position(|signal| *signal == signum).unwrap();
+    let previous = &PREVIOUS_ACTIONS[index];
+
+    if previous.sa_flags & SA_SIGINFO != 0 {
+        let handler = mem::transmute::<usize, extern "C" fn(c_int, *mut siginfo_t, *mut c_void)>(
+            previous.sa_sigaction,
+        );
+        handler(signum, info, context);
+    } else if previous.sa_sigaction == SIG_DFL || previous.sa_sigaction == SIG_IGN {
+        // The faulting instruction runs again and gets the previous action this time.
+        sigaction(signum, previous, null_mut());
+    } else {
+        let handler = mem::transmute::<usize, extern "C" fn(c_int)>(previous.sa_sigaction);
+        handler(signum);
+    }
+}
+
+/// Installs `handle_signal` for `SIGNALS`, once for the process.
+fn install_handlers() {
+    INSTALL_HANDLERS.call_once(|| unsafe {
+        // The signal handlers must not be the ones initializing the statics they read.
+        lazy_static::initialize(&PREVIOUS_ACTIONS);
+        lazy_static::initialize(&CODE_RANGES);
+
+        let mut action: sigaction = mem::zeroed();
+        action.sa_sigaction = handle_signal as *const () as usize;
+        // The handler jumps out instead of returning, so the signal must not stay blocked.
+        action.sa_flags = SA_SIGINFO | SA_ONSTACK | SA_NODEFER;
+        sigemptyset(&mut action.sa_mask);
+
+        for signum in SIGNALS.iter() {
+            if sigaction(*signum, &action, null_mut()) != 0 {
+                panic!("Failed to install the handler of signal {}", signum);
+            }
+        }
+    });
+}
+
+/// Gets the actions `SIGNALS` have.
+fn previous_actions() -> [sigaction; 4] {
+    unsafe {
+        let mut previous_actions: [sigaction; 4] = mem::zeroed();
+        for (signum, previous) in SIGNALS.iter().zip(previous_actions.iter_mut()) {
+            if sigaction(*signum, null(), previous) != 0 {
+                panic!("Failed to get the action of signal {}", signum);
+            }
+        }
+
+        previous_actions
+    }
+}
+
+/// Gives the current thread a stack for the signal handlers, once for the thread.
+fn install_alt_stack() {
+    ALT_STACK.with(|alt_stack| {
+        let mut alt_stack = alt_stack.borrow_mut();
+        if alt_stack.is_none() {
+            *alt_stack = Some(AltStack::new());
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+unsafe fn fault_address(info: *mut siginfo_t) -> *mut c_void {
+    (*info).si_addr()
+}
+
+#[cfg(not(target_os = "linux"))]
+unsafe fn fault_address(info: *mut siginfo_t) -> *mut c_void {
+    (*info).si_addr
+}
+
+impl Drop for RestoreJmpBuf {
+    fn drop(&mut self) {
+        CURRENT_JMP_BUF.with(|current| current.set(self.0));
+    }
+}
+
+impl CodeRange {
+    fn function_offset(&self, pc: usize) -> Option<u32> {
+        if pc >= self.end {
+            return None;
+        }
+
+        // The function `pc` is in is the last one starting at or before it.
+        let index = match self
+            .functions
+            .binary_search_by_key(&pc, |(address, _)| *address)
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+
+        Some(self.functions[index].1)
+    }
+}
+
+impl Drop for CodeRegistration {
+    fn drop(&mut self) {
+        write_code_ranges().retain(|code_range| code_range.id != self.id);
+    }
+}
+
+impl AltStack {
+    fn new() -> Self {
+        let base = unsafe {
+            mmap(
+                null_mut(),
+                ALT_STACK_SIZE,
+                PROT_READ | PROT_WRITE,
+                MAP_PRIVATE | MAP_ANON,
+                -1,
+                0,
+            )
+        };
+
+        if base == MAP_FAILED {
+            panic!("Failed to allocate the signal stack");
+        }
+
+        let stack = stack_t {
+            ss_sp: base,
+            ss_flags: 0,
+            ss_size: ALT_STACK_SIZE,
+        };
+
+        if unsafe { sigaltstack(&stack, null_mut()) } != 0 {
+            panic!("Failed to install the signal stack");
+        }
+
+        Self { base }
+    }
+}
+
+impl Drop for AltStack {
+    fn drop(&mut self) {
+        let stack = stack_t {
+            ss_sp: null_mut(),
+            ss_flags: SS_DISABLE,
+            ss_size: ALT_STACK_SIZE,
+        };
+
+        unsafe {
+            sigaltstack(&stack, null_mut());
+            munmap(self.base, ALT_STACK_SIZE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn current_jmp_buf() -> *mut c_void {
+        CURRENT_JMP_BUF.with(Cell::get)
+    }
+
+    #[test]
+    fn traps_unwind_to_the_closest_catch_traps() {
+        let result = catch_traps(|| {
+            let inner =
+                catch_traps(|| -> u32 { unsafe { raise_trap(TrapCode::Unreachable, Some(7)) } });
+            match inner {
+                Err(RuntimeError::Trap { code, wasm_offset }) => {
+                    assert_eq!(code, TrapCode::Unreachable);
+                    assert_eq!(wasm_offset, Some(7));
+                }
+                other => panic!("Expected a trap, got {:?}", other),
+            }
+
+            // The outer jmp_buf is current again.
+            assert!(!current_jmp_buf().is_null());
+            42
+        });
+
+        assert_eq!(result.unwrap(), 42);
+        assert!(current_jmp_buf().is_null());
+    }
+
+    #[test]
+    fn panics_resume_after_catch_traps() {
+        let result = panic::catch_unwind(|| catch_traps(|| panic!("host function panicked")));
+
+        assert!(result.is_err());
+        assert!(current_jmp_buf().is_null());
+    }
+
+    #[test]
+    fn panics_of_host_code_unwind_like_traps() {
+        let result = panic::catch_unwind(|| {
+            catch_traps(|| -> u32 { unsafe { without_traps(|| panic!("host function panicked")) } })
+        });
+
+        let panic = result.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"host function panicked"));
+        assert!(current_jmp_buf().is_null());
+    }
+
+    #[test]
+    fn registered_code_maps_to_function_offsets() {
+        let registration = register_code(vec![(0x3000, 30), (0x1000, 10)].into_iter(), 0x4000);
+
+        assert_eq!(function_offset(0x0fff), None);
+        assert_eq!(function_offset(0x1000), Some(10));
+        assert_eq!(function_offset(0x2fff), Some(10));
+        assert_eq!(function_offset(0x3fff), Some(30));
+        assert_eq!(function_offset(0x4000), None);
+
+        drop(registration);
+        assert_eq!(function_offset(0x1000), None);
+    }
+}
@@ -1,14 +1,14 @@
-use crate::data::{FuncData, GlobalData, MemoryData, TableData, Data};
+use crate::data::Import;
 use hashbrown::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FuncType {
     params: Vec<ValueType>,
     returns: Vec<ValueType>,
 }
 
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValueType {
     I32,
     I64,
@@ -16,8 +16,16 @@ pub enum ValueType {
     F64,
 }
 
+/// A wasm value passed to or returned from a function.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ExportKind {
     Memory(u32),
     Table(u32),
@@ -36,23 +44,54 @@ pub enum Type {
 #[repr(C)]
 #[derive(Debug)]
 pub struct BoundPtr<T> {
-    base_ptr: *mut T,
-    size: usize,
+    pub(crate) base_ptr: *mut T,
+    pub(crate) size: usize,
 }
 
 impl<T> BoundPtr<T> {
+    pub(crate) fn new(base_ptr: *mut T, size: usize) -> Self {
+        Self { base_ptr, size }
+    }
+
     pub(crate) fn as_mut_ptr(&mut self) -> *mut T {
         self.base_ptr
     }
 }
 
+/// A typed function reference, the element of a table.
+///
+/// `type_id` is the canonical type index of the function, see `ModuleData::canonical_type_index`.
+/// `call_indirect` compares it against the expected signature. A null `func` is an uninitialized
+/// element. `context` is what the function takes first, the InstanceContext buffer of the
+/// instance that defines it or the context of a host function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FuncRef {
+    pub func: FuncPtr,
+    pub type_id: u32,
+    pub context: *mut usize,
+}
+
+impl FuncRef {
+    /// An uninitialized element.
+    pub fn null() -> Self {
+        Self {
+            func: std::ptr::null(),
+            type_id: 0,
+            context: std::ptr::null_mut(),
+        }
+    }
+}
+
 /// ###### Security
 /// - protected by bounds checks
-pub type TablePtr = BoundPtr<u32>;
+pub type TablePtr = BoundPtr<FuncRef>;
 
+/// `size` is the current length of the memory in bytes. Generated code reads it for bounds checks.
+///
 /// ###### Security
-/// - protected by guard page
-pub type MemoryPtr = *mut u8;
+/// - protected by bounds checks or guard pages, see `CodegenOptions`
+pub type MemoryPtr = BoundPtr<u8>;
 
 ///
 pub type GlobalPtr = *mut u64;
@@ -61,11 +100,53 @@ pub type GlobalPtr = *mut u64;
 pub type FuncPtr = *const ();
 
 /// Imports and Exports
-pub type Imports = HashMap<String, HashMap<String, Data>>;
+///
+/// Imports are kept in declaration order because imported entities come first in their index
+/// spaces.
+pub type Imports = Vec<Import>;
 pub type Exports = HashMap<String, ExportKind>;
 
 impl FuncType {
     pub fn new(params: Vec<ValueType>, returns: Vec<ValueType>) -> Self {
         Self { params, returns }
     }
+
+    pub fn params(&self) -> &[ValueType] {
+        &self.params
+    }
+
+    pub fn returns(&self) -> &[ValueType] {
+        &self.returns
+    }
+}
+
+impl Value {
+    pub fn ty(&self) -> ValueType {
+        match self {
+            Value::I32(_) => ValueType::I32,
+            Value::I64(_) => ValueType::I64,
+            Value::F32(_) => ValueType::F32,
+            Value::F64(_) => ValueType::F64,
+        }
+    }
+
+    /// Zero-extends the bits of the value to 64 bits, the layout of argument and result cells.
+    pub fn to_bits(&self) -> u64 {
+        match self {
+            Value::I32(value) => u64::from(*value as u32),
+            Value::I64(value) => *value as u64,
+            Value::F32(value) => u64::from(value.to_bits()),
+            Value::F64(value) => value.to_bits(),
+        }
+    }
+
+    /// Gets a value of type `ty` from the low-order bytes of `bits`.
+    pub fn from_bits(ty: ValueType, bits: u64) -> Self {
+        match ty {
+            ValueType::I32 => Value::I32(bits as u32 as i32),
+            ValueType::I64 => Value::I64(bits as i64),
+            ValueType::F32 => Value::F32(f32::from_bits(bits as u32)),
+            ValueType::F64 => Value::F64(f64::from_bits(bits)),
+        }
+    }
 }
@@ -0,0 +1,13 @@
+//! Runtime support library executables and static libraries compiled by wasmo are linked
+//! against. Its exports are the functions generated code calls, see `executable`, `library` and
+//! `intrinsics`.
+pub use wasmo_runtime::executable::{
+    wasmo_main, wasmo_module_data_add_function, wasmo_module_data_add_global,
+    wasmo_module_data_add_memory, wasmo_module_data_add_table, wasmo_module_data_new,
+    wasmo_module_data_set_code_end,
+};
+pub use wasmo_runtime::intrinsics::{wasmo_memory_grow, wasmo_memory_size, wasmo_trap};
+pub use wasmo_runtime::library::{
+    wasmo_instance_call, wasmo_instance_free, wasmo_instance_global, wasmo_instance_memory,
+    wasmo_instantiate,
+};
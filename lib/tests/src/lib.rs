@@ -0,0 +1,45 @@
+//! End-to-end tests of wasmo, in `tests`. They compile wasm modules and run them. This crate
+//! holds their helpers.
+
+use std::fmt::Debug;
+use wasmo_codegen::compile::Compile;
+use wasmo_runtime::errors::RuntimeError;
+use wasmo_runtime::imports::Linker;
+use wasmo_runtime::module::{Instance, Module};
+use wasmo_runtime::trap::TrapCode;
+use wasmo_utils::file::{convert_wat_source_to_wasm, convert_wat_to_wasm};
+
+/// Jit-compiles a module from wat source.
+pub fn compile_wat(source: &str) -> Module {
+    let wasm = convert_wat_source_to_wasm(source).unwrap();
+
+    Module::new(&wasm).unwrap()
+}
+
+/// Jit-compiles and instantiates a module from wat source, with no imports.
+pub fn instantiate_wat(source: &str) -> Instance {
+    compile_wat(source).instantiate(&Linker::new()).unwrap()
+}
+
+/// Jit-compiles and instantiates a module from a wat file of the repository, with no imports.
+pub fn instantiate_wat_file(path: &str) -> Instance {
+    let path = format!("{}/../../{}", env!("CARGO_MANIFEST_DIR"), path);
+    let wasm = convert_wat_to_wasm(path.as_str()).unwrap();
+
+    instantiate_wasm(&wasm)
+}
+
+/// Jit-compiles and instantiates a module from a wasm binary, with no imports.
+pub fn instantiate_wasm(wasm: &[u8]) -> Instance {
+    let module = Module::new(wasm).unwrap();
+
+    module.instantiate(&Linker::new()).unwrap()
+}
+
+/// Gets the code of the trap `result` failed with.
+pub fn trap_code<T: Debug>(result: Result<T, RuntimeError>) -> TrapCode {
+    match result {
+        Err(RuntimeError::Trap { code, .. }) => code,
+        other => panic!("Expected a trap, got {:?}", other),
+    }
+}
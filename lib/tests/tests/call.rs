@@ -0,0 +1,40 @@
+use wasmo_runtime::errors::RuntimeError;
+use wasmo_runtime::types::Value;
+use wasmo_tests::instantiate_wat;
+
+#[test]
+fn exported_functions_are_called_with_values() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (func (export "add") (param i32 i64) (result i64)
+                (i64.add (i64.extend_i32_s (local.get 0)) (local.get 1)))
+            (func (export "nothing"))
+        )
+        "#,
+    );
+
+    let add = instance.get_func("add").unwrap();
+    let results = add.call(&[Value::I32(-2), Value::I64(5)]).unwrap();
+    assert_eq!(results, vec![Value::I64(3)]);
+
+    let nothing = instance.get_func("nothing").unwrap();
+    assert_eq!(nothing.call(&[]).unwrap(), vec![]);
+    assert!(instance.get_func("missing").is_none());
+}
+
+#[test]
+fn arguments_are_checked_against_the_function_type() {
+    let instance = instantiate_wat(r#"(module (func (export "f") (param f32)))"#);
+    let f = instance.get_func("f").unwrap();
+
+    match f.call(&[Value::F64(1.0)]) {
+        Err(RuntimeError::SignatureMismatch) => (),
+        other => panic!("Expected a signature mismatch, got {:?}", other),
+    }
+    match f.call(&[]) {
+        Err(RuntimeError::SignatureMismatch) => (),
+        other => panic!("Expected a signature mismatch, got {:?}", other),
+    }
+    assert_eq!(f.call(&[Value::F32(1.0)]).unwrap(), vec![]);
+}
@@ -0,0 +1,62 @@
+use wasmo_tests::{instantiate_wat, instantiate_wat_file};
+
+#[test]
+fn control_flow_results() {
+    let instance = instantiate_wat_file("examples/wat/valid/control-flow.wat");
+
+    let pick = instance.get_typed_func::<(i32,), i32>("pick").unwrap();
+    assert_eq!(pick.call(0).unwrap(), 10);
+    assert_eq!(pick.call(1).unwrap(), 20);
+    assert_eq!(pick.call(2).unwrap(), 30);
+    // Out of range indices take the default target.
+    assert_eq!(pick.call(7).unwrap(), 30);
+    assert_eq!(pick.call(-1).unwrap(), 30);
+
+    let max = instance.get_typed_func::<(i32, i32), i32>("max").unwrap();
+    assert_eq!(max.call(3, 9).unwrap(), 9);
+    assert_eq!(max.call(-1, -5).unwrap(), -1);
+}
+
+#[test]
+fn branches_carry_block_results() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (func (export "br_value") (param i32) (result i32)
+                (block $out (result i32)
+                    (drop (br_if $out (i32.const 1) (local.get 0)))
+                    (br $out (i32.const 2))
+                )
+            )
+            (func (export "loop_sum") (param i32) (result i32)
+                (local i32)
+                (loop $again (result i32)
+                    (local.set 1 (i32.add (local.get 1) (local.get 0)))
+                    (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+                    (br_if $again (local.get 0))
+                    (local.get 1)
+                )
+            )
+            (func (export "if_no_else") (param i32) (result i32)
+                (local i32)
+                (local.set 1 (i32.const 5))
+                (if (local.get 0) (then (local.set 1 (i32.const 6))))
+                (local.get 1)
+            )
+        )
+        "#,
+    );
+
+    let br_value = instance.get_typed_func::<(i32,), i32>("br_value").unwrap();
+    assert_eq!(br_value.call(1).unwrap(), 1);
+    assert_eq!(br_value.call(0).unwrap(), 2);
+
+    let loop_sum = instance.get_typed_func::<(i32,), i32>("loop_sum").unwrap();
+    assert_eq!(loop_sum.call(4).unwrap(), 10);
+
+    let if_no_else = instance
+        .get_typed_func::<(i32,), i32>("if_no_else")
+        .unwrap();
+    assert_eq!(if_no_else.call(1).unwrap(), 6);
+    assert_eq!(if_no_else.call(0).unwrap(), 5);
+}
@@ -0,0 +1,79 @@
+use wasmo_runtime::trap::TrapCode;
+use wasmo_tests::{instantiate_wasm, instantiate_wat, trap_code};
+
+#[test]
+fn trapping_truncation_bounds() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (func (export "i32_f32_s") (param f32) (result i32)
+                (i32.trunc_f32_s (local.get 0)))
+            (func (export "i32_f64_u") (param f64) (result i32)
+                (i32.trunc_f64_u (local.get 0)))
+            (func (export "i64_f64_s") (param f64) (result i64)
+                (i64.trunc_f64_s (local.get 0)))
+        )
+        "#,
+    );
+    let invalid = TrapCode::InvalidConversionToInteger;
+
+    let i32_f32_s = instance.get_typed_func::<(f32,), i32>("i32_f32_s").unwrap();
+    assert_eq!(i32_f32_s.call(-2_147_483_648.0).unwrap(), i32::MIN);
+    assert_eq!(i32_f32_s.call(2_147_483_520.0).unwrap(), 2_147_483_520);
+    assert_eq!(i32_f32_s.call(-1.9).unwrap(), -1);
+    assert_eq!(trap_code(i32_f32_s.call(2_147_483_648.0)), invalid);
+    assert_eq!(trap_code(i32_f32_s.call(-2_147_483_904.0)), invalid);
+    assert_eq!(trap_code(i32_f32_s.call(f32::NAN)), invalid);
+    assert_eq!(trap_code(i32_f32_s.call(f32::INFINITY)), invalid);
+
+    let i32_f64_u = instance.get_typed_func::<(f64,), i32>("i32_f64_u").unwrap();
+    assert_eq!(i32_f64_u.call(-0.9).unwrap(), 0);
+    assert_eq!(i32_f64_u.call(4_294_967_295.9).unwrap(), -1);
+    assert_eq!(trap_code(i32_f64_u.call(-1.0)), invalid);
+    assert_eq!(trap_code(i32_f64_u.call(4_294_967_296.0)), invalid);
+
+    let i64_f64_s = instance.get_typed_func::<(f64,), i64>("i64_f64_s").unwrap();
+    assert_eq!(
+        i64_f64_s.call(-9_223_372_036_854_775_808.0).unwrap(),
+        i64::MIN
+    );
+    assert_eq!(
+        trap_code(i64_f64_s.call(9_223_372_036_854_775_808.0)),
+        invalid
+    );
+    assert_eq!(trap_code(i64_f64_s.call(f64::NAN)), invalid);
+}
+
+#[test]
+fn saturating_truncation_bounds() {
+    // wabt doesn't parse the saturating operators, so the module is encoded by hand.
+    #[rustfmt::skip]
+    let wasm = [
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+        // Types: (f32) -> i32, (f64) -> i32
+        0x01, 0x0b, 0x02, 0x60, 0x01, 0x7d, 0x01, 0x7f, 0x60, 0x01, 0x7c, 0x01, 0x7f,
+        // Functions
+        0x03, 0x03, 0x02, 0x00, 0x01,
+        // Exports: "f32_s", "f64_u"
+        0x07, 0x11, 0x02,
+        0x05, b'f', b'3', b'2', b'_', b's', 0x00, 0x00,
+        0x05, b'f', b'6', b'4', b'_', b'u', 0x00, 0x01,
+        // Code: i32.trunc_sat_f32_s, i32.trunc_sat_f64_u
+        0x0a, 0x0f, 0x02,
+        0x06, 0x00, 0x20, 0x00, 0xfc, 0x00, 0x0b,
+        0x06, 0x00, 0x20, 0x00, 0xfc, 0x03, 0x0b,
+    ];
+    let instance = instantiate_wasm(&wasm);
+
+    let f32_s = instance.get_typed_func::<(f32,), i32>("f32_s").unwrap();
+    assert_eq!(f32_s.call(-1.9).unwrap(), -1);
+    assert_eq!(f32_s.call(1e10).unwrap(), i32::MAX);
+    assert_eq!(f32_s.call(-1e10).unwrap(), i32::MIN);
+    assert_eq!(f32_s.call(f32::NAN).unwrap(), 0);
+
+    let f64_u = instance.get_typed_func::<(f64,), i32>("f64_u").unwrap();
+    assert_eq!(f64_u.call(-5.0).unwrap(), 0);
+    assert_eq!(f64_u.call(1e20).unwrap(), -1);
+    assert_eq!(f64_u.call(f64::NAN).unwrap(), 0);
+    assert_eq!(f64_u.call(4_294_967_295.0).unwrap(), -1);
+}
@@ -0,0 +1,86 @@
+use wasmo_runtime::trap::TrapCode;
+use wasmo_tests::{instantiate_wat, trap_code};
+
+#[test]
+fn integer_division_traps() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (func (export "i32_div_s") (param i32 i32) (result i32)
+                (i32.div_s (local.get 0) (local.get 1)))
+            (func (export "i32_div_u") (param i32 i32) (result i32)
+                (i32.div_u (local.get 0) (local.get 1)))
+            (func (export "i32_rem_s") (param i32 i32) (result i32)
+                (i32.rem_s (local.get 0) (local.get 1)))
+            (func (export "i32_rem_u") (param i32 i32) (result i32)
+                (i32.rem_u (local.get 0) (local.get 1)))
+            (func (export "i64_div_s") (param i64 i64) (result i64)
+                (i64.div_s (local.get 0) (local.get 1)))
+            (func (export "i64_rem_s") (param i64 i64) (result i64)
+                (i64.rem_s (local.get 0) (local.get 1)))
+        )
+        "#,
+    );
+
+    let i32_div_s = instance
+        .get_typed_func::<(i32, i32), i32>("i32_div_s")
+        .unwrap();
+    assert_eq!(i32_div_s.call(-7, 2).unwrap(), -3);
+    assert_eq!(
+        trap_code(i32_div_s.call(1, 0)),
+        TrapCode::IntegerDivisionByZero
+    );
+    assert_eq!(
+        trap_code(i32_div_s.call(i32::MIN, -1)),
+        TrapCode::IntegerOverflow
+    );
+
+    let i32_div_u = instance
+        .get_typed_func::<(i32, i32), i32>("i32_div_u")
+        .unwrap();
+    assert_eq!(i32_div_u.call(-1, 2).unwrap(), 0x7fff_ffff);
+    assert_eq!(
+        trap_code(i32_div_u.call(1, 0)),
+        TrapCode::IntegerDivisionByZero
+    );
+
+    let i32_rem_s = instance
+        .get_typed_func::<(i32, i32), i32>("i32_rem_s")
+        .unwrap();
+    assert_eq!(i32_rem_s.call(-7, 2).unwrap(), -1);
+    assert_eq!(i32_rem_s.call(i32::MIN, -1).unwrap(), 0);
+    assert_eq!(
+        trap_code(i32_rem_s.call(1, 0)),
+        TrapCode::IntegerDivisionByZero
+    );
+
+    let i32_rem_u = instance
+        .get_typed_func::<(i32, i32), i32>("i32_rem_u")
+        .unwrap();
+    assert_eq!(i32_rem_u.call(-1, 10).unwrap(), 5);
+    assert_eq!(
+        trap_code(i32_rem_u.call(1, 0)),
+        TrapCode::IntegerDivisionByZero
+    );
+
+    let i64_div_s = instance
+        .get_typed_func::<(i64, i64), i64>("i64_div_s")
+        .unwrap();
+    assert_eq!(
+        trap_code(i64_div_s.call(1, 0)),
+        TrapCode::IntegerDivisionByZero
+    );
+    assert_eq!(
+        trap_code(i64_div_s.call(i64::MIN, -1)),
+        TrapCode::IntegerOverflow
+    );
+
+    let i64_rem_s = instance
+        .get_typed_func::<(i64, i64), i64>("i64_rem_s")
+        .unwrap();
+    assert_eq!(i64_rem_s.call(i64::MIN, -1).unwrap(), 0);
+    assert_eq!(
+        trap_code(i64_rem_s.call(1, 0)),
+        TrapCode::IntegerDivisionByZero
+    );
+}
@@ -0,0 +1,86 @@
+use wasmo_tests::instantiate_wat;
+
+#[test]
+fn float_min_max_nearest() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (func (export "f32_min") (param f32 f32) (result f32)
+                (f32.min (local.get 0) (local.get 1)))
+            (func (export "f32_max") (param f32 f32) (result f32)
+                (f32.max (local.get 0) (local.get 1)))
+            (func (export "f64_min") (param f64 f64) (result f64)
+                (f64.min (local.get 0) (local.get 1)))
+            (func (export "f64_max") (param f64 f64) (result f64)
+                (f64.max (local.get 0) (local.get 1)))
+            (func (export "f32_nearest") (param f32) (result f32)
+                (f32.nearest (local.get 0)))
+            (func (export "f64_nearest") (param f64) (result f64)
+                (f64.nearest (local.get 0)))
+            (func (export "f64_copysign") (param f64 f64) (result f64)
+                (f64.copysign (local.get 0) (local.get 1)))
+        )
+        "#,
+    );
+
+    let f32_min = instance
+        .get_typed_func::<(f32, f32), f32>("f32_min")
+        .unwrap();
+    assert_eq!(
+        f32_min.call(0.0, -0.0).unwrap().to_bits(),
+        (-0.0f32).to_bits()
+    );
+    assert_eq!(
+        f32_min.call(-0.0, 0.0).unwrap().to_bits(),
+        (-0.0f32).to_bits()
+    );
+    assert!(f32_min.call(f32::NAN, 1.0).unwrap().is_nan());
+    assert!(f32_min.call(1.0, f32::NAN).unwrap().is_nan());
+    assert_eq!(f32_min.call(1.0, 2.0).unwrap(), 1.0);
+
+    let f32_max = instance
+        .get_typed_func::<(f32, f32), f32>("f32_max")
+        .unwrap();
+    assert_eq!(f32_max.call(0.0, -0.0).unwrap().to_bits(), 0.0f32.to_bits());
+    assert_eq!(f32_max.call(-0.0, 0.0).unwrap().to_bits(), 0.0f32.to_bits());
+    assert!(f32_max.call(f32::NAN, 1.0).unwrap().is_nan());
+
+    let f64_min = instance
+        .get_typed_func::<(f64, f64), f64>("f64_min")
+        .unwrap();
+    assert_eq!(
+        f64_min.call(0.0, -0.0).unwrap().to_bits(),
+        (-0.0f64).to_bits()
+    );
+    assert!(f64_min.call(1.0, f64::NAN).unwrap().is_nan());
+
+    let f64_max = instance
+        .get_typed_func::<(f64, f64), f64>("f64_max")
+        .unwrap();
+    assert_eq!(f64_max.call(-0.0, 0.0).unwrap().to_bits(), 0.0f64.to_bits());
+    assert!(f64_max.call(f64::NAN, 1.0).unwrap().is_nan());
+
+    // Rounds half to even, keeping the sign of zero.
+    let f32_nearest = instance
+        .get_typed_func::<(f32,), f32>("f32_nearest")
+        .unwrap();
+    assert_eq!(f32_nearest.call(2.5).unwrap(), 2.0);
+    assert_eq!(f32_nearest.call(3.5).unwrap(), 4.0);
+    assert_eq!(
+        f32_nearest.call(-0.5).unwrap().to_bits(),
+        (-0.0f32).to_bits()
+    );
+    assert!(f32_nearest.call(f32::NAN).unwrap().is_nan());
+
+    let f64_nearest = instance
+        .get_typed_func::<(f64,), f64>("f64_nearest")
+        .unwrap();
+    assert_eq!(f64_nearest.call(-2.5).unwrap(), -2.0);
+    assert_eq!(f64_nearest.call(0.5).unwrap().to_bits(), 0.0f64.to_bits());
+
+    let f64_copysign = instance
+        .get_typed_func::<(f64, f64), f64>("f64_copysign")
+        .unwrap();
+    assert_eq!(f64_copysign.call(1.5, -0.0).unwrap(), -1.5);
+    assert_eq!(f64_copysign.call(-1.5, 0.0).unwrap(), 1.5);
+}
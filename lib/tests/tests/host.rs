@@ -0,0 +1,40 @@
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use wasmo_runtime::imports::Linker;
+use wasmo_tests::compile_wat;
+
+#[test]
+fn panics_in_host_functions_resume_in_the_embedder() {
+    let module = compile_wat(
+        r#"
+        (module
+            (import "env" "host" (func $host (param i32) (result i32)))
+            (func (export "call_host") (param i32) (result i32)
+                (i32.add (call $host (local.get 0)) (i32.const 1)))
+        )
+        "#,
+    );
+
+    let calls = Rc::new(Cell::new(0));
+    let host_calls = Rc::clone(&calls);
+    let mut linker = Linker::new();
+    linker.func("env", "host", move |value: i32| {
+        host_calls.set(host_calls.get() + 1);
+        if value < 0 {
+            panic!("negative value");
+        }
+        value * 2
+    });
+
+    let instance = module.instantiate(&linker).unwrap();
+    let call_host = instance.get_typed_func::<(i32,), i32>("call_host").unwrap();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| call_host.call(-1)));
+    let panic = result.unwrap_err();
+    assert_eq!(panic.downcast_ref::<&str>(), Some(&"negative value"));
+
+    // The instance is still usable once the panic unwound out of it.
+    assert_eq!(call_host.call(20).unwrap(), 41);
+    assert_eq!(calls.get(), 2);
+}
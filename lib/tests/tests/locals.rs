@@ -0,0 +1,39 @@
+use wasmo_tests::{instantiate_wat, instantiate_wat_file};
+
+#[test]
+fn locals_round_trip() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (func (export "swap_sub") (param i32 i32) (result i32)
+                (local i32)
+                (local.set 2 (local.get 0))
+                (local.set 0 (local.get 1))
+                (local.set 1 (local.get 2))
+                (i32.sub (local.get 0) (local.get 1))
+            )
+            (func (export "tee") (param i64) (result i64)
+                (local i64)
+                (i64.add (local.tee 1 (i64.mul (local.get 0) (i64.const 2))) (local.get 1))
+            )
+        )
+        "#,
+    );
+
+    let swap_sub = instance
+        .get_typed_func::<(i32, i32), i32>("swap_sub")
+        .unwrap();
+    assert_eq!(swap_sub.call(3, 10).unwrap(), 7);
+
+    let tee = instance.get_typed_func::<(i64,), i64>("tee").unwrap();
+    assert_eq!(tee.call(5).unwrap(), 20);
+}
+
+#[test]
+fn locals_round_trip_in_control_flow() {
+    let instance = instantiate_wat_file("examples/wat/valid/control-flow.wat");
+
+    let fac = instance.get_typed_func::<(i64,), i64>("fac").unwrap();
+    assert_eq!(fac.call(0).unwrap(), 1);
+    assert_eq!(fac.call(5).unwrap(), 120);
+}
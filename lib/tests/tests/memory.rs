@@ -0,0 +1,47 @@
+use wasmo_runtime::trap::TrapCode;
+use wasmo_tests::{instantiate_wat, trap_code};
+
+#[test]
+fn memory_grow_stops_at_maximum() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (memory 1 3)
+            (func (export "grow") (param i32) (result i32)
+                (memory.grow (local.get 0)))
+            (func (export "size") (result i32)
+                (memory.size))
+            (func (export "store_load") (param i32 i32) (result i32)
+                (i32.store (local.get 0) (local.get 1))
+                (i32.load (local.get 0)))
+        )
+        "#,
+    );
+
+    let grow = instance.get_typed_func::<(i32,), i32>("grow").unwrap();
+    let size = instance.get_typed_func::<(), i32>("size").unwrap();
+    let store_load = instance
+        .get_typed_func::<(i32, i32), i32>("store_load")
+        .unwrap();
+
+    assert_eq!(
+        trap_code(store_load.call(65536, 1)),
+        TrapCode::MemoryOutOfBounds
+    );
+    assert_eq!(grow.call(1).unwrap(), 1);
+    assert_eq!(size.call().unwrap(), 2);
+    // The grown page is accessible.
+    assert_eq!(store_load.call(65536, 7).unwrap(), 7);
+
+    // Past the maximum, the memory keeps its size.
+    assert_eq!(grow.call(2).unwrap(), -1);
+    assert_eq!(grow.call(-1).unwrap(), -1);
+    assert_eq!(size.call().unwrap(), 2);
+    assert_eq!(grow.call(0).unwrap(), 2);
+    assert_eq!(grow.call(1).unwrap(), 2);
+    assert_eq!(size.call().unwrap(), 3);
+    assert_eq!(
+        trap_code(store_load.call(3 * 65536, 1)),
+        TrapCode::MemoryOutOfBounds
+    );
+}
@@ -0,0 +1,18 @@
+use wasmo_tests::instantiate_wat;
+
+#[test]
+fn start_function_runs_at_instantiation() {
+    let instance = instantiate_wat(
+        r#"
+        (module
+            (global $counter (mut i32) (i32.const 1))
+            (func $start (global.set $counter (i32.add (global.get $counter) (i32.const 41))))
+            (func (export "counter") (result i32) (global.get $counter))
+            (start $start)
+        )
+        "#,
+    );
+
+    let counter = instance.get_typed_func::<(), i32>("counter").unwrap();
+    assert_eq!(counter.call().unwrap(), 42);
+}
@@ -0,0 +1,76 @@
+use wasmo_codegen::compile::Compile;
+use wasmo_codegen::options::{CodegenOptions, MemoryStrategy};
+use wasmo_runtime::errors::{InstantiationError, RuntimeError};
+use wasmo_runtime::imports::Linker;
+use wasmo_runtime::module::Module;
+use wasmo_runtime::trap::TrapCode;
+use wasmo_tests::compile_wat;
+use wasmo_utils::file::convert_wat_source_to_wasm;
+use wasmparser::{Parser, ParserState, WasmDecoder};
+
+#[test]
+fn trapping_start_function_fails_instantiation() {
+    let module = compile_wat(
+        r#"
+        (module
+            (func $start (drop (i32.div_u (i32.const 1) (i32.const 0))))
+            (start $start)
+        )
+        "#,
+    );
+
+    match module.instantiate(&Linker::new()) {
+        Err(InstantiationError::Trap { code, wasm_offset }) => {
+            assert_eq!(code, TrapCode::IntegerDivisionByZero);
+            assert!(wasm_offset.is_some());
+        }
+        other => panic!("Expected a trap, got {:?}", other),
+    }
+
+    let module = compile_wat("(module (func $start (unreachable)) (start $start))");
+    match module.instantiate(&Linker::new()) {
+        Err(InstantiationError::Trap { code, .. }) => assert_eq!(code, TrapCode::Unreachable),
+        other => panic!("Expected a trap, got {:?}", other),
+    }
+}
+
+#[test]
+fn guard_page_faults_report_the_faulting_function() {
+    let wasm = convert_wat_source_to_wasm(
+        r#"
+        (module
+            (memory 1)
+            (func (export "size") (result i32)
+                (memory.size))
+            (func (export "load") (param i32) (result i32)
+                (i32.load (local.get 0)))
+        )
+        "#,
+    )
+    .unwrap();
+
+    let mut body_offsets = Vec::new();
+    let mut parser = Parser::new(&wasm);
+    loop {
+        match parser.read() {
+            ParserState::BeginFunctionBody { range } => body_offsets.push(range.start as u32),
+            ParserState::EndWasm => break,
+            _ => (),
+        }
+    }
+
+    let mut options = CodegenOptions::default();
+    options.memory_strategy = MemoryStrategy::GuardPages;
+    let module = Module::with_options(&wasm, &options).unwrap();
+    let instance = module.instantiate(&Linker::new()).unwrap();
+    let load = instance.get_typed_func::<(i32,), i32>("load").unwrap();
+
+    assert_eq!(load.call(0).unwrap(), 0);
+    match load.call(65536) {
+        Err(RuntimeError::Trap { code, wasm_offset }) => {
+            assert_eq!(code, TrapCode::MemoryOutOfBounds);
+            assert_eq!(wasm_offset, Some(body_offsets[1]));
+        }
+        other => panic!("Expected a trap, got {:?}", other),
+    }
+}
@@ -0,0 +1,16 @@
+use wasmo_runtime::errors::RuntimeError;
+use wasmo_tests::instantiate_wat_file;
+
+#[test]
+fn typed_funcs_check_exports_and_signatures() {
+    let instance = instantiate_wat_file("examples/wat/valid/control-flow.wat");
+
+    match instance.get_typed_func::<(i32,), i64>("fac") {
+        Err(RuntimeError::SignatureMismatch) => {}
+        other => panic!("Expected a signature mismatch, got {:?}", other),
+    }
+    match instance.get_typed_func::<(i64,), i64>("missing") {
+        Err(RuntimeError::ExportNotFound(name)) => assert_eq!(name, "missing"),
+        other => panic!("Expected a missing export, got {:?}", other),
+    }
+}
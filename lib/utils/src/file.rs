@@ -3,7 +3,7 @@ use std::io;
 use std::io::prelude::Read;
 use std::path::Path;
 use std::fmt::Debug;
-use wabt;
+use wabt::Wat2Wasm;
 
 /// Gets the bytes of a file as a vector of u8s.
 pub fn get_file_bytes<P: AsRef<Path> + Debug + Copy>(file_path: P) -> Result<Vec<u8>, String> {
@@ -32,6 +32,7 @@ pub fn is_wasm_file<P: AsRef<Path> + Debug + Copy>(file_path: P) -> Result<bool,
     Ok(bytes.starts_with(b"\0asm"))
 }
 
+/// Converts a wat file to a wasm binary.
 pub fn convert_wat_to_wasm<P: AsRef<Path> + Debug + Copy>(file_path: P) -> Result<Vec<u8>, String> {
     let mut file = File::open(file_path).expect("Unable to open the file");
 
@@ -40,5 +41,15 @@ pub fn convert_wat_to_wasm<P: AsRef<Path> + Debug + Copy>(file_path: P) -> Resul
     file.read_to_string(&mut contents)
         .map_err(|_| format!("Unable to read file: {:?}", file_path))?;
 
-    wabt::wat2wasm(contents).map_err(|e| format!("Conversion error: {:?}", e))
+    convert_wat_source_to_wasm(&contents)
+}
+
+/// Converts wat source text to a wasm binary. The binary isn't validated, wasmo validates the
+/// modules it compiles itself.
+pub fn convert_wat_source_to_wasm(source: &str) -> Result<Vec<u8>, String> {
+    Wat2Wasm::new()
+        .validate(false)
+        .convert(source)
+        .map(|binary| binary.as_ref().to_vec())
+        .map_err(|e| format!("Conversion error: {:?}", e))
 }
@@ -23,6 +23,46 @@ impl<'a> Arguments<'a> {
                     .help("WebAssembly file to run")
                     .index(1),
             )
+            .arg(
+                Arg::with_name("output")
+                    .short("o")
+                    .long("output")
+                    .value_name("OUTPUT")
+                    .takes_value(true)
+                    .requires("FILE")
+                    .help("Compile FILE to an executable at OUTPUT"),
+            )
+            .arg(
+                Arg::with_name("static-library")
+                    .short("c")
+                    .long("static-library")
+                    .value_name("OUTPUT")
+                    .takes_value(true)
+                    .requires("FILE")
+                    .conflicts_with("output")
+                    .help("Compile FILE to a static library at OUTPUT, with a C header next to it"),
+            )
+            .arg(
+                Arg::with_name("target")
+                    .long("target")
+                    .value_name("TRIPLE")
+                    .takes_value(true)
+                    .help("Compile for the target TRIPLE instead of the host, e.g. aarch64-unknown-linux-gnu"),
+            )
+            .arg(
+                Arg::with_name("cpu")
+                    .long("cpu")
+                    .value_name("CPU")
+                    .takes_value(true)
+                    .help("Compile for the CPU of the target, e.g. cortex-a72"),
+            )
+            .arg(
+                Arg::with_name("features")
+                    .long("features")
+                    .value_name("FEATURES")
+                    .takes_value(true)
+                    .help("Enable or disable features of the target, e.g. +neon,-crypto"),
+            )
             .arg(
                 Arg::with_name("version")
                     .short("v")
@@ -41,4 +81,24 @@ impl<'a> Arguments<'a> {
 
         Ok(None)
     }
+
+    pub(crate) fn get_output_path(&self) -> Option<String> {
+        self.matches.value_of("output").map(str::to_owned)
+    }
+
+    pub(crate) fn get_static_library_path(&self) -> Option<String> {
+        self.matches.value_of("static-library").map(str::to_owned)
+    }
+
+    pub(crate) fn get_target_triple(&self) -> Option<String> {
+        self.matches.value_of("target").map(str::to_owned)
+    }
+
+    pub(crate) fn get_cpu(&self) -> Option<String> {
+        self.matches.value_of("cpu").map(str::to_owned)
+    }
+
+    pub(crate) fn get_features(&self) -> Option<String> {
+        self.matches.value_of("features").map(str::to_owned)
+    }
 }
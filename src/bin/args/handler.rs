@@ -1,6 +1,15 @@
 use super::Arguments;
+use crate::link::{archive_static_library, link_executable};
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use wasmo_codegen::compile::Compile;
 use wasmo_codegen::generator::ModuleGenerator;
+use wasmo_codegen::library::{c_identifier, generate_header};
 use wasmo_codegen::options::CodegenOptions;
+use wasmo_llvm::CodeGenFileType;
+use wasmo_runtime::module::ModuleAOT;
 use wasmo_utils::file::{convert_wat_to_wasm, get_file_bytes, is_wasm_file};
 
 ///
@@ -29,12 +38,25 @@ impl<'a> ArgumentsHandler<'a> {
                 convert_wat_to_wasm(&file_path)?
             };
 
+            if let Some(output_path) = self.args.get_output_path() {
+                return self.compile_executable(&wasm_binary, &output_path);
+            }
+
+            if let Some(output_path) = self.args.get_static_library_path() {
+                return self.compile_static_library(&wasm_binary, &output_path);
+            }
+
             // TODO: The following APIS are meant to be accessed via wasmo_runtime::Module and co.
             // TODO: Use default codegen options for now
             let options = &CodegenOptions::default();
 
             // Generate llvm module and from wasm binary.
-            let result = ModuleGenerator::new(&wasm_binary, options).generate_module();
+            let mut generator = match ModuleGenerator::new(&wasm_binary, options) {
+                Err(error) => panic!("Target Error! = {:?}", error),
+                Ok(generator) => generator,
+            };
+
+            let result = generator.generate_module();
 
             // Error handing
             let _result = match result {
@@ -50,6 +72,81 @@ impl<'a> ArgumentsHandler<'a> {
         Ok(())
     }
 
+    /// Codegen options for the target given with `--target`, the host by default.
+    fn target_options(&self) -> CodegenOptions {
+        CodegenOptions {
+            target_triple: self.args.get_target_triple(),
+            cpu: self.args.get_cpu().unwrap_or_default(),
+            features: self.args.get_features().unwrap_or_default(),
+            ..CodegenOptions::default()
+        }
+    }
+
+    /// Compiles the module to an object with a `main` and links it into an executable.
+    ///
+    /// Executables for other targets need a `CC` that links for them, see `link_executable`.
+    fn compile_executable(&self, wasm_binary: &[u8], output_path: &str) -> Result<(), String> {
+        let options = CodegenOptions {
+            generate_main: true,
+            ..self.target_options()
+        };
+
+        let module = ModuleAOT::with_options(wasm_binary, &options)
+            .map_err(|error| format!("Compilation Error! = {:?}", error))?;
+
+        let output_path = Path::new(output_path);
+        let object_name = output_path
+            .file_name()
+            .ok_or_else(|| format!("Invalid output path {:?}", output_path))?;
+        let object_path = env::temp_dir().join(object_name).with_extension("o");
+
+        module
+            .write_object(&object_path)
+            .map_err(|error| format!("Failed to write {:?}: {}", object_path, error))?;
+
+        let target_triple = options.target_triple.as_deref();
+        let result = link_executable(&object_path, output_path, target_triple);
+        let _ = fs::remove_file(&object_path);
+
+        result
+    }
+
+    /// Compiles the module to an object with a C API, archives it into a static library and
+    /// writes the header of the API next to it. The API is named after the library, e.g.
+    /// `server_instantiate` for `libserver.a`.
+    fn compile_static_library(&self, wasm_binary: &[u8], output_path: &str) -> Result<(), String> {
+        let output_path = Path::new(output_path);
+        let name = output_path
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .ok_or_else(|| format!("Invalid output path {:?}", output_path))?;
+        let prefix = c_identifier(name.trim_start_matches("lib"));
+
+        let mut generator = ModuleGenerator::new(wasm_binary, &self.target_options())
+            .map_err(|error| format!("Target Error! = {:?}", error))?;
+        let (module, data) = generator
+            .generate_library_module(&prefix)
+            .map_err(|error| format!("Compilation Error! = {:?}", error))?;
+        let object = generator
+            .target_machine()
+            .emit_to_memory_buffer(&module, CodeGenFileType::ObjectFile)
+            .map_err(|error| format!("Compilation Error! = {:?}", error))?;
+        let header = generate_header(&prefix, &data)
+            .map_err(|error| format!("Compilation Error! = {:?}", error))?;
+
+        let object_path = env::temp_dir().join(format!("{}.o", prefix));
+        fs::write(&object_path, object.as_slice())
+            .map_err(|error| format!("Failed to write {:?}: {}", object_path, error))?;
+
+        let result = archive_static_library(&object_path, output_path);
+        let _ = fs::remove_file(&object_path);
+        result?;
+
+        let header_path = output_path.with_file_name(format!("{}.h", prefix));
+        fs::write(&header_path, header)
+            .map_err(|error| format!("Failed to write {:?}: {}", header_path, error))
+    }
+
     pub fn setup(&self) -> Result<(), String> {
         self.run_optional_file()
     }
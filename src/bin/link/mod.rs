@@ -0,0 +1,124 @@
+//! Links compiled modules into executables with the system linker, or archives them into static
+//! libraries.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use wasmo_llvm::target::Target;
+
+/// The static library executables are linked against, see `lib/support`.
+const SUPPORT_LIBRARY_NAME: &str = "libwasmo_support.a";
+
+/// System libraries the Rust standard library in the support library needs on Apple targets.
+const APPLE_SYSTEM_LIBRARIES: &[&str] = &["-lSystem", "-lresolv", "-lc", "-lm"];
+/// System libraries the Rust standard library in the support library needs on other targets.
+const SYSTEM_LIBRARIES: &[&str] = &["-lpthread", "-ldl", "-lm", "-lrt", "-lutil"];
+
+/// Drops the parts of the support library executables don't use, with the Apple linker.
+const APPLE_DEAD_CODE_FLAGS: &[&str] = &["-Wl,-dead_strip"];
+/// Drops the parts of the support library executables don't use, with other linkers.
+const DEAD_CODE_FLAGS: &[&str] = &["-Wl,--gc-sections"];
+
+/// The C compiler driver used as linker when `CC` isn't set.
+const DEFAULT_LINKER: &str = "cc";
+
+/// Links `object`, which defines `main`, with the support library for `target_triple` into an
+/// executable at `output`. The C compiler driver in `CC`, `cc` by default, is the linker, it knows
+/// where the C runtime and the system libraries are. It must link for `target_triple`.
+pub fn link_executable(
+    object: &Path,
+    output: &Path,
+    target_triple: Option<&str>,
+) -> Result<(), String> {
+    let support_library = find_support_library(target_triple)?;
+    let linker = env::var("CC").unwrap_or_else(|_| DEFAULT_LINKER.to_string());
+
+    let host_triple = Target::get_default_triple().to_string();
+    let (system_libraries, dead_code_flags) = if is_apple(target_triple.unwrap_or(&host_triple)) {
+        (APPLE_SYSTEM_LIBRARIES, APPLE_DEAD_CODE_FLAGS)
+    } else {
+        (SYSTEM_LIBRARIES, DEAD_CODE_FLAGS)
+    };
+
+    let status = Command::new(&linker)
+        .arg(object)
+        .arg(&support_library)
+        .arg("-o")
+        .arg(output)
+        .args(system_libraries)
+        .args(dead_code_flags)
+        .status()
+        .map_err(|error| format!("Failed to run the linker `{}`: {}", linker, error))?;
+
+    if !status.success() {
+        return Err(format!(
+            "Linking failed! `{}` exited with {}",
+            linker, status
+        ));
+    }
+
+    Ok(())
+}
+
+/// Archives `object` into a static library at `output` with the system archiver `ar`. Programs
+/// linking the library link the support library too.
+pub fn archive_static_library(object: &Path, output: &Path) -> Result<(), String> {
+    // `ar` adds to existing archives.
+    if output.exists() {
+        fs::remove_file(output)
+            .map_err(|error| format!("Failed to remove {:?}: {}", output, error))?;
+    }
+
+    let status = Command::new("ar")
+        .arg("rcs")
+        .arg(output)
+        .arg(object)
+        .status()
+        .map_err(|error| format!("Failed to run the archiver `ar`: {}", error))?;
+
+    if !status.success() {
+        return Err(format!("Archiving failed! `ar` exited with {}", status));
+    }
+
+    Ok(())
+}
+
+/// The support library for the host is built into the same directory as the `wasmo` executable,
+/// e.g. `target/debug`. Cargo builds the one for another target into the directory of that target,
+/// e.g. `target/aarch64-unknown-linux-gnu/debug`.
+fn find_support_library(target_triple: Option<&str>) -> Result<PathBuf, String> {
+    let executable = env::current_exe().map_err(|error| error.to_string())?;
+    // Guarantee: an executable path always has a parent directory.
+    let directory = executable.parent().unwrap();
+
+    let (path, build_command) = match target_triple {
+        Some(target_triple) => {
+            let profile = directory.file_name().unwrap_or_default();
+            let path = directory
+                .with_file_name(target_triple)
+                .join(profile)
+                .join(SUPPORT_LIBRARY_NAME);
+            let build_command = format!("cargo build -p wasmo-support --target {}", target_triple);
+
+            (path, build_command)
+        }
+        None => (
+            directory.join(SUPPORT_LIBRARY_NAME),
+            "cargo build -p wasmo-support".to_string(),
+        ),
+    };
+
+    if !path.is_file() {
+        return Err(format!(
+            "Couldn't find {:?}. Build it with `{}`",
+            path, build_command
+        ));
+    }
+
+    Ok(path)
+}
+
+/// Whether `target_triple` is of an Apple OS, e.g. `x86_64-apple-darwin`.
+fn is_apple(target_triple: &str) -> bool {
+    target_triple.split('-').nth(1) == Some("apple")
+}
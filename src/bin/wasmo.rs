@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate wasmo_utils;
 mod args;
+mod link;
 
 use args::ArgumentsHandler;
 